@@ -16,6 +16,7 @@ fn run_loop_contract() {
 		block_coinbase: Default::default(),
 		block_timestamp: Default::default(),
 		block_difficulty: Default::default(),
+		block_randomness: None,
 		block_gas_limit: Default::default(),
 		chain_id: U256::one(),
 	};
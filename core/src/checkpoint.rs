@@ -0,0 +1,321 @@
+//! Binary checkpoints of a machine, to persist a paused machine and resume it
+//! later, possibly in another process.
+//!
+//! The code is not part of a checkpoint, and is given back when loading it.
+//! All integers are big endian, and lengths are `u64`. A checkpoint is, in
+//! order:
+//!
+//! * the format version, a byte, currently `1`;
+//! * the data, as its length and bytes;
+//! * the position, as `0` and the program counter, or `1` and the exit reason
+//!   of an exited machine;
+//! * the exit position, as `0`, or `1`, the program counter and the opcode;
+//! * the start and end of the return range, as 32 byte words;
+//! * the effective length of the memory, as a 32 byte word, and the memory
+//!   written so far, as its length and bytes;
+//! * the stack, as its length and words, from the bottom.
+//!
+//! Exit reasons are a kind byte (`0` succeed, `1` revert, `2` error, `3`
+//! fatal) and a variant byte, followed, for `Other`, by the message as its
+//! length and UTF-8 bytes, and for `CallErrorAsFatal`, by the error.
+
+use core::convert::TryFrom;
+use alloc::{borrow::Cow, rc::Rc, string::String, vec::Vec};
+use primitive_types::{H256, U256};
+use crate::{ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed, Machine, Memory, Opcode,
+	Stack};
+
+const VERSION: u8 = 1;
+
+fn malformed() -> ExitError {
+	ExitError::Other("malformed machine checkpoint".into())
+}
+
+impl Machine {
+	/// Serialize the data, position, memory, stack and return range of the
+	/// machine, in the format of the `checkpoint` module documentation.
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut out = Vec::new();
+		out.push(VERSION);
+		write_bytes(&mut out, &self.data);
+
+		match &self.position {
+			Ok(position) => {
+				out.push(0);
+				write_u64(&mut out, *position as u64);
+			},
+			Err(reason) => {
+				out.push(1);
+				write_reason(&mut out, reason);
+			},
+		}
+
+		match self.exit_position {
+			None => out.push(0),
+			Some((position, opcode)) => {
+				out.push(1);
+				write_u64(&mut out, position as u64);
+				out.push(opcode.as_u8());
+			},
+		}
+
+		write_u256(&mut out, self.return_range.start);
+		write_u256(&mut out, self.return_range.end);
+
+		write_u256(&mut out, self.memory.effective_len());
+		write_bytes(&mut out, self.memory.data());
+
+		write_u64(&mut out, self.stack.len() as u64);
+		for i in (0..self.stack.len()).rev() {
+			let value = self.stack.peek(i).expect("index is within the stack; qed");
+			out.extend_from_slice(&value[..]);
+		}
+
+		out
+	}
+
+	/// Load a machine serialized by `serialize`, running the given code with
+	/// the given limits. Fails with `InvalidJump` if the position is not the
+	/// start of an instruction of the code, as `set_position` does, with
+	/// `InvalidRange` if the return range or the memory do not fit the memory
+	/// limit, or the memory written is longer than its effective length, with `StackOverflow` if the stack does not fit the stack limit,
+	/// and with `Other` if the checkpoint is malformed.
+	pub fn deserialize(
+		bytes: &[u8],
+		code: Rc<Vec<u8>>,
+		stack_limit: usize,
+		memory_limit: usize,
+	) -> Result<Self, ExitError> {
+		let mut reader = Reader(bytes);
+		if reader.u8()? != VERSION {
+			return Err(malformed())
+		}
+
+		let data = reader.bytes()?.to_vec();
+		let mut machine = Machine::new(code, Rc::new(data), stack_limit, memory_limit);
+
+		match reader.u8()? {
+			0 => machine.set_position(reader.usize()?)?,
+			1 => machine.position = Err(read_reason(&mut reader)?),
+			_ => return Err(malformed()),
+		}
+
+		machine.exit_position = match reader.u8()? {
+			0 => None,
+			1 => {
+				let position = reader.usize()?;
+				let opcode = Opcode(reader.u8()?);
+				if machine.code.get(position) != Some(&opcode.as_u8()) {
+					return Err(malformed())
+				}
+				Some((position, opcode))
+			},
+			_ => return Err(malformed()),
+		};
+
+		let start = reader.u256()?;
+		let end = reader.u256()?;
+		machine.set_return_range(start..end)?;
+
+		machine.memory = read_memory(&mut reader, memory_limit)?;
+		machine.stack = read_stack(&mut reader, stack_limit)?;
+
+		if !reader.0.is_empty() {
+			return Err(malformed())
+		}
+
+		Ok(machine)
+	}
+}
+
+fn read_memory(reader: &mut Reader, limit: usize) -> Result<Memory, ExitError> {
+	let effective_len = reader.u256()?;
+	let data = reader.bytes()?;
+
+	if effective_len % U256::from(32) != U256::zero() ||
+		effective_len > U256::from(limit) ||
+		U256::from(data.len()) > effective_len
+	{
+		return Err(ExitError::InvalidRange)
+	}
+
+	let mut memory = Memory::new(limit);
+	memory.resize_end(effective_len)?;
+	memory.set(0, data, None).map_err(|_| ExitError::InvalidRange)?;
+	Ok(memory)
+}
+
+fn read_stack(reader: &mut Reader, limit: usize) -> Result<Stack, ExitError> {
+	let len = reader.usize()?;
+	if len > limit {
+		return Err(ExitError::StackOverflow)
+	}
+
+	let mut stack = Stack::new(limit);
+	for _ in 0..len {
+		stack.push(H256::from_slice(reader.take(32)?))?;
+	}
+	Ok(stack)
+}
+
+fn write_u64(out: &mut Vec<u8>, value: u64) {
+	out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_u256(out: &mut Vec<u8>, value: U256) {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	out.extend_from_slice(&word);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+	write_u64(out, bytes.len() as u64);
+	out.extend_from_slice(bytes);
+}
+
+fn write_reason(out: &mut Vec<u8>, reason: &ExitReason) {
+	match reason {
+		ExitReason::Succeed(succeed) => {
+			out.push(0);
+			out.push(match succeed {
+				ExitSucceed::Stopped => 0,
+				ExitSucceed::Returned => 1,
+				ExitSucceed::Suicided => 2,
+			});
+		},
+		ExitReason::Revert(ExitRevert::Reverted) => {
+			out.push(1);
+			out.push(0);
+		},
+		ExitReason::Error(error) => {
+			out.push(2);
+			write_error(out, error);
+		},
+		ExitReason::Fatal(fatal) => {
+			out.push(3);
+			match fatal {
+				ExitFatal::NotSupported => out.push(0),
+				ExitFatal::UnhandledInterrupt => out.push(1),
+				ExitFatal::CallErrorAsFatal(error) => {
+					out.push(2);
+					write_error(out, error);
+				},
+				ExitFatal::Other(message) => {
+					out.push(3);
+					write_bytes(out, message.as_bytes());
+				},
+			}
+		},
+	}
+}
+
+fn write_error(out: &mut Vec<u8>, error: &ExitError) {
+	out.push(match error {
+		ExitError::StackUnderflow => 0,
+		ExitError::StackOverflow => 1,
+		ExitError::InvalidJump => 2,
+		ExitError::InvalidRange => 3,
+		ExitError::DesignatedInvalid => 4,
+		ExitError::CallTooDeep => 5,
+		ExitError::CreateCollision => 6,
+		ExitError::CreateContractLimit => 7,
+		ExitError::StaticModeViolation => 8,
+		ExitError::InvalidEofCode => 9,
+		ExitError::BannedOpcode => 10,
+		ExitError::OutOfOffset => 11,
+		ExitError::OutOfGas => 12,
+		ExitError::OutOfFund => 13,
+		ExitError::OutOfUpfrontFund => 14,
+		ExitError::SenderHasCode => 15,
+		ExitError::InitCodeLimit => 16,
+		ExitError::PCUnderflow => 17,
+		ExitError::CreateEmpty => 18,
+		ExitError::Other(message) => {
+			out.push(19);
+			write_bytes(out, message.as_bytes());
+			return
+		},
+	});
+}
+
+fn read_reason(reader: &mut Reader) -> Result<ExitReason, ExitError> {
+	Ok(match (reader.u8()?, reader.u8()?) {
+		(0, 0) => ExitSucceed::Stopped.into(),
+		(0, 1) => ExitSucceed::Returned.into(),
+		(0, 2) => ExitSucceed::Suicided.into(),
+		(1, 0) => ExitRevert::Reverted.into(),
+		(2, variant) => read_error(reader, variant)?.into(),
+		(3, 0) => ExitFatal::NotSupported.into(),
+		(3, 1) => ExitFatal::UnhandledInterrupt.into(),
+		(3, 2) => {
+			let variant = reader.u8()?;
+			ExitFatal::CallErrorAsFatal(read_error(reader, variant)?).into()
+		},
+		(3, 3) => ExitFatal::Other(reader.message()?).into(),
+		_ => return Err(malformed()),
+	})
+}
+
+fn read_error(reader: &mut Reader, variant: u8) -> Result<ExitError, ExitError> {
+	Ok(match variant {
+		0 => ExitError::StackUnderflow,
+		1 => ExitError::StackOverflow,
+		2 => ExitError::InvalidJump,
+		3 => ExitError::InvalidRange,
+		4 => ExitError::DesignatedInvalid,
+		5 => ExitError::CallTooDeep,
+		6 => ExitError::CreateCollision,
+		7 => ExitError::CreateContractLimit,
+		8 => ExitError::StaticModeViolation,
+		9 => ExitError::InvalidEofCode,
+		10 => ExitError::BannedOpcode,
+		11 => ExitError::OutOfOffset,
+		12 => ExitError::OutOfGas,
+		13 => ExitError::OutOfFund,
+		14 => ExitError::OutOfUpfrontFund,
+		15 => ExitError::SenderHasCode,
+		16 => ExitError::InitCodeLimit,
+		17 => ExitError::PCUnderflow,
+		18 => ExitError::CreateEmpty,
+		19 => ExitError::Other(reader.message()?),
+		_ => return Err(malformed()),
+	})
+}
+
+/// Cursor over the bytes of a checkpoint.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, len: usize) -> Result<&'a [u8], ExitError> {
+		if self.0.len() < len {
+			return Err(malformed())
+		}
+		let (taken, rest) = self.0.split_at(len);
+		self.0 = rest;
+		Ok(taken)
+	}
+
+	fn u8(&mut self) -> Result<u8, ExitError> {
+		Ok(self.take(1)?[0])
+	}
+
+	fn usize(&mut self) -> Result<usize, ExitError> {
+		let mut bytes = [0u8; 8];
+		bytes.copy_from_slice(self.take(8)?);
+		usize::try_from(u64::from_be_bytes(bytes)).map_err(|_| malformed())
+	}
+
+	fn u256(&mut self) -> Result<U256, ExitError> {
+		Ok(U256::from_big_endian(self.take(32)?))
+	}
+
+	fn bytes(&mut self) -> Result<&'a [u8], ExitError> {
+		let len = self.usize()?;
+		self.take(len)
+	}
+
+	fn message(&mut self) -> Result<Cow<'static, str>, ExitError> {
+		let bytes = self.bytes()?.to_vec();
+		String::from_utf8(bytes).map(Cow::Owned).map_err(|_| malformed())
+	}
+}
@@ -0,0 +1,8 @@
+/// Magic prefix of EOF formatted bytecode (EIP-3540).
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+
+/// Whether the bytecode is EOF formatted, that is, starts with the EOF magic
+/// followed by a version byte.
+pub fn is_eof(code: &[u8]) -> bool {
+	code.len() > EOF_MAGIC.len() && code.starts_with(&EOF_MAGIC)
+}
@@ -1,3 +1,4 @@
+use core::fmt;
 use alloc::borrow::Cow;
 use crate::Opcode;
 
@@ -64,6 +65,20 @@ impl ExitReason {
 	}
 }
 
+impl fmt::Display for ExitReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Succeed(s) => s.fmt(f),
+			Self::Error(e) => e.fmt(f),
+			Self::Revert(r) => r.fmt(f),
+			Self::Fatal(e) => e.fmt(f),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExitReason {}
+
 /// Exit succeed reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "with-codec", derive(codec::Encode, codec::Decode))]
@@ -77,6 +92,16 @@ pub enum ExitSucceed {
 	Suicided,
 }
 
+impl fmt::Display for ExitSucceed {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Self::Stopped => "stopped",
+			Self::Returned => "returned",
+			Self::Suicided => "suicided",
+		})
+	}
+}
+
 impl From<ExitSucceed> for ExitReason {
 	fn from(s: ExitSucceed) -> Self {
 		Self::Succeed(s)
@@ -92,6 +117,17 @@ pub enum ExitRevert {
 	Reverted,
 }
 
+impl fmt::Display for ExitRevert {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Reverted => f.write_str("reverted"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExitRevert {}
+
 impl From<ExitRevert> for ExitReason {
 	fn from(s: ExitRevert) -> Self {
 		Self::Revert(s)
@@ -119,6 +155,12 @@ pub enum ExitError {
 	CreateCollision,
 	/// Create init code exceeds limit (runtime).
 	CreateContractLimit,
+	/// Attempt to modify state in a static call (runtime).
+	StaticModeViolation,
+	/// Encountered EOF formatted code, which cannot be executed (runtime).
+	InvalidEofCode,
+	/// Encountered an opcode banned by the executor (runtime).
+	BannedOpcode,
 
 	///	An opcode accesses external information, but the request is off offset
 	///	limit (runtime).
@@ -127,6 +169,13 @@ pub enum ExitError {
 	OutOfGas,
 	/// Not enough fund to start the execution (runtime).
 	OutOfFund,
+	/// Not enough fund to pay for the transaction value and its maximum gas
+	/// fee upfront (runtime).
+	OutOfUpfrontFund,
+	/// Transaction sender has deployed code (runtime, EIP-3607).
+	SenderHasCode,
+	/// Init code of a create exceeds the limit (runtime, EIP-3860).
+	InitCodeLimit,
 
 	/// PC underflowed (unused).
 	PCUnderflow,
@@ -137,6 +186,36 @@ pub enum ExitError {
 	Other(Cow<'static, str>),
 }
 
+impl fmt::Display for ExitError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Self::StackUnderflow => "stack underflow",
+			Self::StackOverflow => "stack overflow",
+			Self::InvalidJump => "invalid jump destination",
+			Self::InvalidRange => "invalid memory range",
+			Self::DesignatedInvalid => "designated invalid opcode",
+			Self::CallTooDeep => "call stack too deep",
+			Self::CreateCollision => "create collision",
+			Self::CreateContractLimit => "contract code size limit exceeded",
+			Self::StaticModeViolation => "state modification in static call",
+			Self::InvalidEofCode => "EOF code cannot be executed",
+			Self::BannedOpcode => "banned opcode",
+			Self::OutOfOffset => "out of offset",
+			Self::OutOfGas => "out of gas",
+			Self::OutOfFund => "out of fund",
+			Self::OutOfUpfrontFund => "out of fund for the upfront cost",
+			Self::SenderHasCode => "sender has deployed code",
+			Self::InitCodeLimit => "init code size limit exceeded",
+			Self::PCUnderflow => "pc underflow",
+			Self::CreateEmpty => "create empty account",
+			Self::Other(message) => message,
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExitError {}
+
 impl From<ExitError> for ExitReason {
 	fn from(s: ExitError) -> Self {
 		Self::Error(s)
@@ -159,6 +238,27 @@ pub enum ExitFatal {
 	Other(Cow<'static, str>),
 }
 
+impl fmt::Display for ExitFatal {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::NotSupported => f.write_str("operation not supported"),
+			Self::UnhandledInterrupt => f.write_str("unhandled interrupt"),
+			Self::CallErrorAsFatal(e) => write!(f, "call error as fatal: {}", e),
+			Self::Other(message) => f.write_str(message),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExitFatal {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::CallErrorAsFatal(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
 impl From<ExitFatal> for ExitReason {
 	fn from(s: ExitFatal) -> Self {
 		Self::Fatal(s)
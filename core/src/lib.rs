@@ -8,6 +8,7 @@
 extern crate core;
 extern crate alloc;
 
+mod eof;
 mod memory;
 mod stack;
 mod valids;
@@ -15,8 +16,10 @@ mod opcode;
 mod error;
 mod eval;
 mod utils;
+mod checkpoint;
 
-pub use crate::memory::Memory;
+pub use crate::eof::{is_eof, EOF_MAGIC};
+pub use crate::memory::{Memory, memory_expansion_cost};
 pub use crate::stack::Stack;
 pub use crate::valids::Valids;
 pub use crate::opcode::Opcode;
@@ -28,6 +31,21 @@ use alloc::rc::Rc;
 use primitive_types::U256;
 use crate::eval::{eval, Control};
 
+/// Snapshot of the mutable state of a machine, taken by
+/// `Machine::snapshot`.
+///
+/// The snapshot owns a full copy of the machine stack and memory, so taking
+/// one costs time and space linear in the current memory size. Code, data
+/// and jump destination maps are not copied.
+#[derive(Clone, Debug)]
+pub struct MachineSnapshot {
+	position: Result<usize, ExitReason>,
+	exit_position: Option<(usize, Opcode)>,
+	return_range: Range<U256>,
+	memory: Memory,
+	stack: Stack,
+}
+
 /// Core execution layer for EVM.
 pub struct Machine {
 	/// Program data.
@@ -36,6 +54,8 @@ pub struct Machine {
 	code: Rc<Vec<u8>>,
 	/// Program counter.
 	position: Result<usize, ExitReason>,
+	/// Program counter and opcode the machine exited at.
+	exit_position: Option<(usize, Opcode)>,
 	/// Return value.
 	return_range: Range<U256>,
 	/// Code validity maps.
@@ -55,6 +75,18 @@ impl Machine {
 	pub fn memory(&self) -> &Memory { &self.memory }
 	/// Mutable reference of machine memory.
 	pub fn memory_mut(&mut self) -> &mut Memory { &mut self.memory }
+	/// Reference of machine code.
+	pub fn code(&self) -> &[u8] { &self.code }
+	/// Reference of machine data.
+	pub fn data(&self) -> &[u8] { &self.data }
+	/// Program counter, or the exit reason once the machine has exited.
+	pub fn position(&self) -> Result<usize, ExitReason> { self.position.clone() }
+	/// Program counter and opcode the machine exited at, such as the jump of
+	/// an `InvalidJump` error. Only set for exits from evaluating an opcode in
+	/// `step`, not for explicit exits.
+	pub fn exit_position(&self) -> Option<(usize, Opcode)> { self.exit_position }
+	/// Memory range returned by `return_value`, as set by RETURN or REVERT.
+	pub fn return_range(&self) -> &Range<U256> { &self.return_range }
 
 	/// Create a new machine with given code and data.
 	pub fn new(
@@ -69,6 +101,7 @@ impl Machine {
 			data,
 			code,
 			position: Ok(0),
+			exit_position: None,
 			return_range: U256::zero()..U256::zero(),
 			valids,
 			memory: Memory::new(memory_limit),
@@ -76,6 +109,72 @@ impl Machine {
 		}
 	}
 
+	/// Take a snapshot of the program counter, return range, memory and stack.
+	/// See `MachineSnapshot` for its cost.
+	pub fn snapshot(&self) -> MachineSnapshot {
+		MachineSnapshot {
+			position: self.position.clone(),
+			exit_position: self.exit_position,
+			return_range: self.return_range.clone(),
+			memory: self.memory.clone(),
+			stack: self.stack.clone(),
+		}
+	}
+
+	/// Restore the machine to a snapshot previously taken from it.
+	pub fn restore(&mut self, snapshot: MachineSnapshot) {
+		self.position = snapshot.position;
+		self.exit_position = snapshot.exit_position;
+		self.return_range = snapshot.return_range;
+		self.memory = snapshot.memory;
+		self.stack = snapshot.stack;
+	}
+
+	/// Move the program counter to the given position, resuming the machine
+	/// if it has exited. The position must be the start of an instruction,
+	/// or the end of the code, and not inside the data of a push, otherwise
+	/// it fails with `InvalidJump` and the machine is unchanged.
+	pub fn set_position(&mut self, position: usize) -> Result<(), ExitError> {
+		if position > self.code.len() {
+			return Err(ExitError::InvalidJump)
+		}
+
+		if !self.valids.is_valid(position) {
+			let mut i = 0;
+			while i < position {
+				i += match Opcode(self.code[i]).is_push() {
+					Some(n) => n as usize + 1,
+					None => 1,
+				};
+			}
+
+			if i != position {
+				return Err(ExitError::InvalidJump)
+			}
+		}
+
+		self.position = Ok(position);
+		self.exit_position = None;
+		Ok(())
+	}
+
+	/// Set the memory range returned by `return_value`, as RETURN or REVERT
+	/// would, for returns built by the embedder. Fails with `InvalidRange`,
+	/// leaving the machine unchanged, if the range is reversed or a non-empty
+	/// range ends past the memory limit. The memory is not resized.
+	pub fn set_return_range(&mut self, range: Range<U256>) -> Result<(), ExitError> {
+		if range.start > range.end {
+			return Err(ExitError::InvalidRange)
+		}
+
+		if range.start != range.end && range.end > U256::from(self.memory.limit()) {
+			return Err(ExitError::InvalidRange)
+		}
+
+		self.return_range = range;
+		Ok(())
+	}
+
 	/// Explict exit of the machine. Further step will return error.
 	pub fn exit(&mut self, reason: ExitReason) {
 		self.position = Err(reason);
@@ -137,6 +236,7 @@ impl Machine {
 					},
 					Control::Exit(e) => {
 						self.position = Err(e.clone());
+						self.exit_position = Some((position, opcode));
 						Err(Capture::Exit(e))
 					},
 					Control::Jump(p) => {
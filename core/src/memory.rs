@@ -1,8 +1,29 @@
 use primitive_types::U256;
 use core::cmp::{min, max};
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use crate::{ExitError, ExitFatal};
 
+/// Standard gas cost of expanding the memory from `current_words` to
+/// `new_words` words, the difference of `3 * words + words * words / 512`
+/// between both sizes. Saturates at `u64::MAX` for sizes no gas can pay for.
+pub fn memory_expansion_cost(current_words: usize, new_words: usize) -> u64 {
+	fn words_cost(words: usize) -> U256 {
+		let words = U256::from(words);
+		U256::from(3) * words + words * words / U256::from(512)
+	}
+
+	if new_words <= current_words {
+		return 0
+	}
+
+	let cost = words_cost(new_words) - words_cost(current_words);
+	if cost > U256::from(u64::MAX) {
+		u64::MAX
+	} else {
+		cost.as_u64()
+	}
+}
+
 /// A sequencial memory. It uses Rust's `Vec` for internal
 /// representation.
 #[derive(Clone, Debug)]
@@ -27,12 +48,19 @@ impl Memory {
 		self.limit
 	}
 
-	/// Get the length of the current memory range.
+	/// Get the length of the memory written so far. It can be shorter than
+	/// the effective length, which the unwritten tail reads as zeros.
 	pub fn len(&self) -> usize {
 		self.data.len()
 	}
 
-	/// Get the effective length.
+	/// Get the memory written so far, of `len` bytes.
+	pub fn data(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Get the effective length, the accessed memory range rounded up to
+	/// whole words, as used for the memory expansion cost.
 	pub fn effective_len(&self) -> U256 {
 		self.effective_len
 	}
@@ -42,6 +70,27 @@ impl Memory {
 		self.len() == 0
 	}
 
+	/// Gas cost of expanding the memory to cover `offset..(offset + len)`,
+	/// relative to the current effective length, as of
+	/// `memory_expansion_cost`. Zero-length regions and regions already
+	/// covered cost nothing. The memory is not changed.
+	pub fn expansion_cost(&self, offset: usize, len: usize) -> u64 {
+		if len == 0 {
+			return 0
+		}
+
+		let end = U256::from(offset) + U256::from(len);
+		let new_words = (end + U256::from(31)) / U256::from(32);
+		let current_words = self.effective_len / U256::from(32);
+		let current_words = if current_words > U256::from(usize::MAX) {
+			usize::MAX
+		} else {
+			current_words.as_usize()
+		};
+
+		memory_expansion_cost(current_words, new_words.as_usize())
+	}
+
 	/// Resize the memory, making it cover the memory region of `offset..(offset
 	/// + len)`, with 32 bytes as the step. If the length is zero, this function
 	/// does nothing.
@@ -92,6 +141,28 @@ impl Memory {
 		ret
 	}
 
+	/// Get memory region at given offset, as `get`. The offset and size are
+	/// considered untrusted: a region overflowing `usize` or ending past the
+	/// memory limit fails with `InvalidRange`. An empty region never fails.
+	pub fn try_get(&self, offset: usize, size: usize) -> Result<Vec<u8>, ExitError> {
+		if size == 0 {
+			return Ok(Vec::new())
+		}
+
+		let end = offset.checked_add(size).ok_or(ExitError::InvalidRange)?;
+		if end > self.limit {
+			return Err(ExitError::InvalidRange)
+		}
+
+		let mut ret = vec![0; size];
+		if offset < self.data.len() {
+			let copied = min(end, self.data.len()) - offset;
+			ret[..copied].copy_from_slice(&self.data[offset..offset + copied]);
+		}
+
+		Ok(ret)
+	}
+
 	/// Set memory region at given offset. The offset and value is considered
 	/// untrusted.
 	pub fn set(
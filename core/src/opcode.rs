@@ -214,6 +214,10 @@ impl Opcode {
 	pub const SSTORE: Opcode = Opcode(0x55);
 	/// `GAS`
 	pub const GAS: Opcode = Opcode(0x5a);
+	/// `TLOAD`
+	pub const TLOAD: Opcode = Opcode(0x5c);
+	/// `TSTORE`
+	pub const TSTORE: Opcode = Opcode(0x5d);
 	/// `LOGn`
 	pub const LOG0: Opcode = Opcode(0xa0);
 	pub const LOG1: Opcode = Opcode(0xa1);
@@ -239,16 +243,38 @@ impl Opcode {
 }
 
 impl Opcode {
-	/// Whether the opcode is a push opcode.
-	pub fn is_push(&self) -> Option<u8> {
-		let value = self.0;
-		if value >= 0x60 && value <= 0x7f {
-			Some(value - 0x60 + 1)
-		} else {
-			None
+	/// Whether the opcode is a push opcode, with the number of bytes pushed.
+	pub const fn is_push(&self) -> Option<u8> {
+		match self.0 {
+			value @ 0x60..=0x7f => Some(value - 0x60 + 1),
+			_ => None,
 		}
 	}
 
+	/// Whether the opcode is a dup opcode, with the position of the stack
+	/// item duplicated, from 1 for the top.
+	pub const fn is_dup(&self) -> Option<u8> {
+		match self.0 {
+			value @ 0x80..=0x8f => Some(value - 0x80 + 1),
+			_ => None,
+		}
+	}
+
+	/// Whether the opcode is a swap opcode, with the position of the stack
+	/// item swapped with the top, from 1 for the one below it.
+	pub const fn is_swap(&self) -> Option<u8> {
+		match self.0 {
+			value @ 0x90..=0x9f => Some(value - 0x90 + 1),
+			_ => None,
+		}
+	}
+
+	/// Whether the opcode always ends the execution of the machine: `STOP`,
+	/// `RETURN`, `REVERT`, `INVALID` or `SUICIDE`.
+	pub const fn is_terminating(&self) -> bool {
+		matches!(*self, Opcode::STOP | Opcode::RETURN | Opcode::REVERT | Opcode::INVALID | Opcode::SUICIDE)
+	}
+
 	#[inline]
 	pub const fn as_u8(&self) -> u8 {
 		self.0
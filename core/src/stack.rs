@@ -1,4 +1,4 @@
-use primitive_types::H256;
+use primitive_types::{H256, U256};
 use alloc::vec::Vec;
 use crate::ExitError;
 
@@ -48,6 +48,34 @@ impl Stack {
 		Ok(())
 	}
 
+	#[inline]
+	/// Pop a value from the stack, as the word it is. Same as `pop`.
+	pub fn pop_h256(&mut self) -> Result<H256, ExitError> {
+		self.pop()
+	}
+
+	#[inline]
+	/// Push a word into the stack. Same as `push`.
+	pub fn push_h256(&mut self, value: H256) -> Result<(), ExitError> {
+		self.push(value)
+	}
+
+	#[inline]
+	/// Pop a value from the stack, as a number read from the word in big
+	/// endian, as the EVM does.
+	pub fn pop_u256(&mut self) -> Result<U256, ExitError> {
+		self.pop().map(|value| U256::from_big_endian(&value[..]))
+	}
+
+	#[inline]
+	/// Push a number into the stack, as a word written in big endian, as the
+	/// EVM does.
+	pub fn push_u256(&mut self, value: U256) -> Result<(), ExitError> {
+		let mut word = H256::default();
+		value.to_big_endian(&mut word[..]);
+		self.push(word)
+	}
+
 	#[inline]
 	/// Peek a value at given index for the stack, where the top of
 	/// the stack is at index `0`. If the index is too large,
@@ -1,21 +1,24 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use crate::Opcode;
 
-/// Mapping of valid jump destination from code.
+/// Mapping of valid jump destination from code, packed as one bit per code
+/// byte.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Valids(Vec<bool>);
+pub struct Valids {
+	bits: Vec<u8>,
+	len: usize,
+}
 
 impl Valids {
 	/// Create a new valid mapping from given code bytes.
 	pub fn new(code: &[u8]) -> Self {
-		let mut valids: Vec<bool> = Vec::with_capacity(code.len());
-		valids.resize(code.len(), false);
+		let mut bits = vec![0u8; code.len().div_ceil(8)];
 
 		let mut i = 0;
 		while i < code.len() {
 			let opcode = Opcode(code[i]);
 			if opcode == Opcode::JUMPDEST {
-				valids[i] = true;
+				bits[i / 8] |= 1 << (i % 8);
 				i += 1;
 			} else if let Some(v) = opcode.is_push() {
 				i += v as usize + 1;
@@ -24,14 +27,14 @@ impl Valids {
 			}
 		}
 
-		Valids(valids)
+		Valids { bits, len: code.len() }
 	}
 
 	/// Get the length of the valid mapping. This is the same as the
 	/// code bytes.
 	#[inline]
 	pub fn len(&self) -> usize {
-		self.0.len()
+		self.len
 	}
 
 	/// Returns true if the valids list is empty
@@ -43,14 +46,10 @@ impl Valids {
 	/// Returns `true` if the position is a valid jump destination. If
 	/// not, returns `false`.
 	pub fn is_valid(&self, position: usize) -> bool {
-		if position >= self.0.len() {
-			return false;
-		}
-
-		if !self.0[position] {
+		if position >= self.len {
 			return false;
 		}
 
-		true
+		self.bits[position / 8] & (1 << (position % 8)) != 0
 	}
 }
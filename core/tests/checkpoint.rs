@@ -0,0 +1,151 @@
+use std::rc::Rc;
+use evm_core::{Capture, ExitError, ExitReason, ExitSucceed, Machine};
+
+/// Sums 5 down to 1 in a loop, and returns the sum and the first word of the
+/// data.
+const CODE: &str = "600060055b8091019060019003806004575060005260003560205260406000f3";
+
+fn code() -> Rc<Vec<u8>> {
+	Rc::new(hex::decode(CODE).unwrap())
+}
+
+fn machine() -> Machine {
+	let mut data = vec![0u8; 32];
+	data[31] = 0x2a;
+	Machine::new(code(), Rc::new(data), 1024, 10000)
+}
+
+fn run(machine: &mut Machine) -> Vec<u8> {
+	assert_eq!(machine.run(), Capture::Exit(ExitSucceed::Returned.into()));
+	machine.return_value()
+}
+
+#[test]
+fn resumes_from_checkpoint_to_the_same_result() {
+	let mut expected = machine();
+	let expected = run(&mut expected);
+	assert_eq!(expected[31], 15);
+	assert_eq!(expected[63], 0x2a);
+
+	for steps in [0, 1, 5, 20, 40] {
+		let mut machine = machine();
+		for _ in 0..steps {
+			machine.step().unwrap();
+		}
+
+		let bytes = machine.serialize();
+		let mut loaded = Machine::deserialize(&bytes, code(), 1024, 10000).unwrap();
+		assert_eq!(loaded.position(), machine.position());
+		assert_eq!(loaded.stack().len(), machine.stack().len());
+		assert_eq!(loaded.serialize(), bytes);
+
+		assert_eq!(run(&mut loaded), expected);
+		assert_eq!(run(&mut machine), expected);
+	}
+}
+
+#[test]
+fn checkpoint_of_exited_machine() {
+	let mut machine = machine();
+	let output = run(&mut machine);
+
+	let loaded = Machine::deserialize(&machine.serialize(), code(), 1024, 10000).unwrap();
+	assert_eq!(loaded.position(), Err(ExitReason::Succeed(ExitSucceed::Returned)));
+	assert_eq!(loaded.exit_position(), machine.exit_position());
+	assert_eq!(loaded.return_range(), machine.return_range());
+	assert_eq!(loaded.memory().effective_len(), 64.into());
+	assert_eq!(loaded.return_value(), output);
+
+	let mut failed = Machine::new(code(), Rc::new(Vec::new()), 1024, 10000);
+	failed.exit(ExitError::Other("custom".into()).into());
+	let loaded = Machine::deserialize(&failed.serialize(), code(), 1024, 10000).unwrap();
+	assert_eq!(loaded.position(), Err(ExitError::Other("custom".into()).into()));
+}
+
+#[test]
+fn position_is_validated_against_the_code() {
+	let mut machine = machine();
+	// Past the first PUSH1, at the second one.
+	machine.step().unwrap();
+	let bytes = machine.serialize();
+
+	// PUSH2, whose data covers the saved position.
+	let other = Rc::new(hex::decode("61000000").unwrap());
+	assert_eq!(Machine::deserialize(&bytes, other, 1024, 10000).err(), Some(ExitError::InvalidJump));
+	let short = Rc::new(hex::decode("00").unwrap());
+	assert_eq!(Machine::deserialize(&bytes, short, 1024, 10000).err(), Some(ExitError::InvalidJump));
+}
+
+#[test]
+fn limits_are_validated() {
+	let mut machine = machine();
+	for _ in 0..3 {
+		machine.step().unwrap();
+	}
+	assert_eq!(machine.stack().len(), 2);
+	let bytes = machine.serialize();
+
+	assert_eq!(Machine::deserialize(&bytes, code(), 1, 10000).err(), Some(ExitError::StackOverflow));
+	assert!(Machine::deserialize(&bytes, code(), 2, 10000).is_ok());
+
+	let mut machine = self::machine();
+	let output = run(&mut machine);
+	assert_eq!(output.len(), 64);
+	let bytes = machine.serialize();
+	assert_eq!(Machine::deserialize(&bytes, code(), 1024, 32).err(), Some(ExitError::InvalidRange));
+}
+
+/// Checkpoint of a machine that has not started, with the given effective
+/// length and written bytes of memory.
+fn with_memory(effective_len: u64, data: &[u8]) -> Vec<u8> {
+	let bytes = machine().serialize();
+	// Effective length, memory length and stack length of a machine with an
+	// empty memory and stack.
+	let mut out = bytes[..bytes.len() - 32 - 8 - 8].to_vec();
+	out.extend_from_slice(&[0; 24]);
+	out.extend_from_slice(&effective_len.to_be_bytes());
+	out.extend_from_slice(&(data.len() as u64).to_be_bytes());
+	out.extend_from_slice(data);
+	out.extend_from_slice(&0u64.to_be_bytes());
+	out
+}
+
+#[test]
+fn memory_is_validated_against_its_effective_length() {
+	assert_eq!(with_memory(0, &[]), machine().serialize());
+
+	let loaded = Machine::deserialize(&with_memory(64, &[1; 40]), code(), 1024, 10000).unwrap();
+	assert_eq!(loaded.memory().effective_len(), 64.into());
+	assert_eq!(loaded.memory().data(), &[1; 40][..]);
+
+	let invalid = [
+		// Not a whole number of words.
+		with_memory(33, &[]),
+		// Beyond the memory limit.
+		with_memory(10016, &[]),
+		with_memory(1 << 40, &[]),
+		// Written bytes beyond the effective length.
+		with_memory(32, &[1; 33]),
+		with_memory(0, &[1]),
+	];
+	for bytes in &invalid {
+		assert_eq!(Machine::deserialize(bytes, code(), 1024, 10000).err(), Some(ExitError::InvalidRange));
+	}
+}
+
+#[test]
+fn malformed_checkpoints_are_rejected() {
+	let bytes = machine().serialize();
+	let malformed = Some(ExitError::Other("malformed machine checkpoint".into()));
+
+	assert_eq!(Machine::deserialize(&[], code(), 1024, 10000).err(), malformed);
+	assert_eq!(Machine::deserialize(&bytes[..bytes.len() - 1], code(), 1024, 10000).err(), malformed);
+
+	let mut longer = bytes.clone();
+	longer.push(0);
+	assert_eq!(Machine::deserialize(&longer, code(), 1024, 10000).err(), malformed);
+
+	let mut version = bytes;
+	version[0] = 2;
+	assert_eq!(Machine::deserialize(&version, code(), 1024, 10000).err(), malformed);
+}
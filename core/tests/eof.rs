@@ -0,0 +1,20 @@
+use evm_core::is_eof;
+
+#[test]
+fn legacy_code_is_not_eof() {
+	assert!(!is_eof(&[]));
+	assert!(!is_eof(&[0x60, 0x00, 0x00]));
+}
+
+#[test]
+fn ef_prefixed_code_without_magic_is_not_eof() {
+	assert!(!is_eof(&[0xef]));
+	assert!(!is_eof(&[0xef, 0x01, 0x00]));
+	// Magic without a version byte.
+	assert!(!is_eof(&[0xef, 0x00]));
+}
+
+#[test]
+fn eof_magic_and_version() {
+	assert!(is_eof(&[0xef, 0x00, 0x01]));
+}
@@ -0,0 +1,44 @@
+use std::borrow::Cow;
+use evm_core::{ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
+
+#[test]
+fn display_messages() {
+	assert_eq!(ExitError::OutOfGas.to_string(), "out of gas");
+	assert_eq!(ExitError::InvalidJump.to_string(), "invalid jump destination");
+	assert_eq!(ExitError::BannedOpcode.to_string(), "banned opcode");
+	assert_eq!(ExitError::Other(Cow::Borrowed("custom")).to_string(), "custom");
+	assert_eq!(ExitRevert::Reverted.to_string(), "reverted");
+	assert_eq!(ExitSucceed::Returned.to_string(), "returned");
+	assert_eq!(ExitFatal::NotSupported.to_string(), "operation not supported");
+	assert_eq!(
+		ExitFatal::CallErrorAsFatal(ExitError::StackOverflow).to_string(),
+		"call error as fatal: stack overflow",
+	);
+}
+
+#[test]
+fn exit_reason_displays_its_inner_reason() {
+	assert_eq!(ExitReason::Error(ExitError::OutOfGas).to_string(), "out of gas");
+	assert_eq!(ExitReason::Revert(ExitRevert::Reverted).to_string(), "reverted");
+	assert_eq!(ExitReason::Succeed(ExitSucceed::Stopped).to_string(), "stopped");
+	assert_eq!(
+		ExitReason::Fatal(ExitFatal::UnhandledInterrupt).to_string(),
+		"unhandled interrupt",
+	);
+}
+
+#[test]
+fn exit_reasons_are_std_errors() {
+	fn fails() -> Result<(), Box<dyn std::error::Error>> {
+		Err(ExitError::InvalidJump)?;
+		Ok(())
+	}
+	assert_eq!(fails().unwrap_err().to_string(), "invalid jump destination");
+
+	let fatal = ExitFatal::CallErrorAsFatal(ExitError::OutOfGas);
+	let source = std::error::Error::source(&fatal).unwrap();
+	assert_eq!(source.to_string(), "out of gas");
+
+	let reason: Box<dyn std::error::Error> = Box::new(ExitReason::Revert(ExitRevert::Reverted));
+	assert_eq!(reason.to_string(), "reverted");
+}
@@ -0,0 +1,44 @@
+use std::rc::Rc;
+use evm_core::{Capture, ExitError, ExitReason, Machine, Opcode};
+
+fn run(code: &str) -> Machine {
+	let mut machine = Machine::new(
+		Rc::new(hex::decode(code).unwrap()), Rc::new(Vec::new()), 1024, 10000,
+	);
+	let _ = machine.run();
+	machine
+}
+
+#[test]
+fn invalid_jump_reports_position() {
+	// PUSH1 0x01 POP PUSH1 0x03 JUMP
+	let machine = run("600150600356");
+
+	assert_eq!(machine.position(), Err(ExitReason::Error(ExitError::InvalidJump)));
+	assert_eq!(machine.exit_position(), Some((5, Opcode::JUMP)));
+}
+
+#[test]
+fn invalid_opcode_reports_position() {
+	// PUSH1 0x00 INVALID
+	let machine = run("6000fe");
+
+	assert_eq!(machine.exit_position(), Some((2, Opcode(0xfe))));
+}
+
+#[test]
+fn running_machine_has_no_exit_position() {
+	// PUSH1 0x00
+	let mut machine = Machine::new(
+		Rc::new(hex::decode("6000").unwrap()), Rc::new(Vec::new()), 1024, 10000,
+	);
+	assert!(machine.step().is_ok());
+	assert_eq!(machine.exit_position(), None);
+
+	match machine.step() {
+		Err(Capture::Exit(reason)) => assert!(reason.is_succeed()),
+		_ => panic!("expected the machine to stop"),
+	}
+	// Running off the end of the code is not an opcode exit.
+	assert_eq!(machine.exit_position(), None);
+}
@@ -0,0 +1,16 @@
+use std::rc::Rc;
+use evm_core::Machine;
+
+#[test]
+fn code_and_data_are_those_given() {
+	let code = hex::decode("60003560005260206000f3").unwrap();
+	let data = hex::decode("2a2a").unwrap();
+	let machine = Machine::new(Rc::new(code.clone()), Rc::new(data.clone()), 1024, 10000);
+
+	assert_eq!(machine.code(), &code[..]);
+	assert_eq!(machine.data(), &data[..]);
+
+	let empty = Machine::new(Rc::new(Vec::new()), Rc::new(Vec::new()), 1024, 10000);
+	assert!(empty.code().is_empty());
+	assert!(empty.data().is_empty());
+}
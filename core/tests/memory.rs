@@ -0,0 +1,87 @@
+use std::rc::Rc;
+use evm_core::{memory_expansion_cost, ExitError, Machine, Memory};
+
+#[test]
+fn expansion_cost_from_empty() {
+	let memory = Memory::new(usize::MAX);
+
+	assert_eq!(memory.expansion_cost(0, 0), 0);
+	assert_eq!(memory.expansion_cost(1024, 0), 0);
+	assert_eq!(memory.expansion_cost(0, 1), 3);
+	assert_eq!(memory.expansion_cost(0, 32), 3);
+	assert_eq!(memory.expansion_cost(0, 33), 6);
+	assert_eq!(memory.expansion_cost(0, 1024), 98);
+	assert_eq!(memory.expansion_cost(0, 32 * 1024), 5120);
+	assert_eq!(memory.expansion_cost(0, 1024 * 1024), 2195456);
+}
+
+#[test]
+fn expansion_cost_is_relative() {
+	let mut memory = Memory::new(usize::MAX);
+	memory.resize_offset(0.into(), 1024.into()).unwrap();
+
+	assert_eq!(memory.expansion_cost(0, 1024), 0);
+	assert_eq!(memory.expansion_cost(992, 32), 0);
+	assert_eq!(memory.expansion_cost(1024, 32), 3 * 33 + 33 * 33 / 512 - 98);
+	assert_eq!(memory.expansion_cost(0, 32 * 1024), 5120 - 98);
+	assert_eq!(memory.effective_len(), 1024.into());
+}
+
+#[test]
+fn expansion_cost_saturates() {
+	let memory = Memory::new(usize::MAX);
+
+	assert_eq!(memory.expansion_cost(usize::MAX, usize::MAX), u64::MAX);
+	assert_eq!(memory_expansion_cost(0, 1 << 40), u64::MAX);
+}
+
+#[test]
+fn expansion_cost_is_the_cost_of_the_words() {
+	let mut memory = Memory::new(usize::MAX);
+	memory.resize_offset(0.into(), 1024.into()).unwrap();
+
+	assert_eq!(memory.expansion_cost(0, 2049 * 32), memory_expansion_cost(32, 2049));
+	assert_eq!(memory.expansion_cost(0, 2049 * 32), memory_expansion_cost(0, 2049) - 98);
+	assert_eq!(memory_expansion_cost(2049, 32), 0);
+}
+
+#[test]
+fn effective_len_rounds_to_words_after_mstore() {
+	// PUSH1 0x00 PUSH1 0x00 MSTORE
+	let mut machine = Machine::new(
+		Rc::new(hex::decode("6000600052").unwrap()), Rc::new(Vec::new()), 1024, usize::MAX,
+	);
+	let _ = machine.run();
+	assert_eq!(machine.memory().effective_len(), 32.into());
+	assert_eq!(machine.memory().len(), 32);
+
+	// PUSH1 0x00 PUSH1 0x21 MSTORE
+	let mut machine = Machine::new(
+		Rc::new(hex::decode("6000602152").unwrap()), Rc::new(Vec::new()), 1024, usize::MAX,
+	);
+	let _ = machine.run();
+	assert_eq!(machine.memory().effective_len(), 96.into());
+	assert_eq!(machine.memory().len(), 65);
+}
+
+#[test]
+fn try_get_reads_in_range_and_pads() {
+	let mut memory = Memory::new(1024);
+	memory.set(0, &[1, 2, 3, 4], None).unwrap();
+
+	assert_eq!(memory.try_get(1, 2), Ok(vec![2, 3]));
+	assert_eq!(memory.try_get(2, 4), Ok(vec![3, 4, 0, 0]));
+	assert_eq!(memory.try_get(100, 2), Ok(vec![0, 0]));
+	assert_eq!(memory.try_get(usize::MAX, 0), Ok(Vec::new()));
+}
+
+#[test]
+fn try_get_rejects_overflow_and_limit() {
+	let memory = Memory::new(1024);
+
+	assert_eq!(memory.try_get(usize::MAX, 1), Err(ExitError::InvalidRange));
+	assert_eq!(memory.try_get(usize::MAX - 1, 2), Err(ExitError::InvalidRange));
+	assert_eq!(memory.try_get(1, usize::MAX), Err(ExitError::InvalidRange));
+	assert_eq!(memory.try_get(1000, 24), Ok(vec![0; 24]));
+	assert_eq!(memory.try_get(1000, 25), Err(ExitError::InvalidRange));
+}
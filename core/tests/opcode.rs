@@ -0,0 +1,28 @@
+use evm_core::Opcode;
+
+#[test]
+fn push_dup_and_swap_ranges() {
+	assert_eq!(Opcode(0x63).is_push(), Some(4));
+	assert_eq!(Opcode::PUSH1.is_push(), Some(1));
+	assert_eq!(Opcode::PUSH32.is_push(), Some(32));
+	assert_eq!(Opcode(0x5f).is_push(), None);
+	assert_eq!(Opcode::DUP1.is_push(), None);
+
+	assert_eq!(Opcode::DUP1.is_dup(), Some(1));
+	assert_eq!(Opcode::DUP16.is_dup(), Some(16));
+	assert_eq!(Opcode::PUSH32.is_dup(), None);
+	assert_eq!(Opcode::SWAP1.is_dup(), None);
+
+	assert_eq!(Opcode::SWAP1.is_swap(), Some(1));
+	assert_eq!(Opcode::SWAP16.is_swap(), Some(16));
+	assert_eq!(Opcode::DUP16.is_swap(), None);
+	assert_eq!(Opcode::LOG0.is_swap(), None);
+}
+
+#[test]
+fn terminating_opcodes() {
+	assert!(Opcode(0xf3).is_terminating());
+
+	let terminating: Vec<u8> = (0..=u8::MAX).filter(|&byte| Opcode(byte).is_terminating()).collect();
+	assert_eq!(terminating, vec![0x00, 0xf3, 0xfd, 0xfe, 0xff]);
+}
@@ -0,0 +1,37 @@
+use std::rc::Rc;
+use evm_core::{Capture, ExitError, ExitReason, ExitSucceed, Machine};
+use primitive_types::H256;
+
+/// PUSH1 0x2a JUMPDEST PUSH1 0x01 STOP
+fn machine() -> Machine {
+	Machine::new(Rc::new(hex::decode("602a5b600100").unwrap()), Rc::new(Vec::new()), 1024, 10000)
+}
+
+#[test]
+fn set_position_to_jumpdest_and_resume() {
+	let mut machine = machine();
+	assert_eq!(machine.set_position(2), Ok(()));
+	assert_eq!(machine.position(), Ok(2));
+
+	assert!(matches!(machine.run(), Capture::Exit(ExitReason::Succeed(ExitSucceed::Stopped))));
+	assert_eq!(machine.stack().len(), 1);
+	assert_eq!(machine.stack().peek(0), Ok(H256::from_low_u64_be(1)));
+
+	// An exited machine resumes from the new position.
+	assert_eq!(machine.set_position(3), Ok(()));
+	assert!(matches!(machine.run(), Capture::Exit(ExitReason::Succeed(ExitSucceed::Stopped))));
+	assert_eq!(machine.stack().len(), 2);
+}
+
+#[test]
+fn set_position_rejects_push_data_and_out_of_code() {
+	let mut machine = machine();
+
+	assert_eq!(machine.set_position(1), Err(ExitError::InvalidJump));
+	assert_eq!(machine.set_position(4), Err(ExitError::InvalidJump));
+	assert_eq!(machine.set_position(7), Err(ExitError::InvalidJump));
+	assert_eq!(machine.position(), Ok(0));
+
+	// The end of the code is the start of an implicit STOP.
+	assert_eq!(machine.set_position(6), Ok(()));
+}
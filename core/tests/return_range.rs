@@ -0,0 +1,50 @@
+use std::rc::Rc;
+use evm_core::{Capture, ExitError, ExitReason, ExitSucceed, Machine};
+use primitive_types::U256;
+
+fn machine(code: &str) -> Machine {
+	Machine::new(Rc::new(hex::decode(code).unwrap()), Rc::new(Vec::new()), 1024, 256)
+}
+
+#[test]
+fn set_return_range_selects_returned_bytes() {
+	let mut machine = machine("");
+	machine.memory_mut().set(0, &[0xde, 0xad, 0xbe, 0xef], None).unwrap();
+
+	assert_eq!(machine.set_return_range(U256::from(1)..U256::from(3)), Ok(()));
+	assert_eq!(machine.return_range(), &(U256::from(1)..U256::from(3)));
+	assert_eq!(machine.return_value(), vec![0xad, 0xbe]);
+
+	// Bytes past the written memory read as zeros.
+	assert_eq!(machine.set_return_range(U256::from(2)..U256::from(6)), Ok(()));
+	assert_eq!(machine.return_value(), vec![0xbe, 0xef, 0, 0]);
+}
+
+#[test]
+fn set_return_range_rejects_invalid_ranges() {
+	let mut machine = machine("");
+	let range = U256::from(1)..U256::from(3);
+	machine.set_return_range(range.clone()).unwrap();
+
+	assert_eq!(
+		machine.set_return_range(U256::from(3)..U256::from(1)),
+		Err(ExitError::InvalidRange),
+	);
+	assert_eq!(
+		machine.set_return_range(U256::from(200)..U256::from(257)),
+		Err(ExitError::InvalidRange),
+	);
+	assert_eq!(machine.return_range(), &range);
+
+	// Empty ranges read nothing, wherever they start.
+	assert_eq!(machine.set_return_range(U256::MAX..U256::MAX), Ok(()));
+	assert_eq!(machine.return_value(), Vec::<u8>::new());
+}
+
+#[test]
+fn return_range_is_set_by_return() {
+	// PUSH1 0x20 PUSH1 0x40 RETURN
+	let mut machine = machine("60206040f3");
+	assert!(matches!(machine.run(), Capture::Exit(ExitReason::Succeed(ExitSucceed::Returned))));
+	assert_eq!(machine.return_range(), &(U256::from(0x40)..U256::from(0x60)));
+}
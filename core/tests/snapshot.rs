@@ -0,0 +1,31 @@
+use std::rc::Rc;
+use evm_core::{Machine, Capture, ExitSucceed};
+
+#[test]
+fn snapshot_and_restore() {
+	// PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x01 PUSH1 0x20 MSTORE PUSH1 0x03
+	let code = hex::decode("602a60005260016020526003").unwrap();
+	let mut vm = Machine::new(Rc::new(code), Rc::new(Vec::new()), 1024, 10000);
+
+	for _ in 0..3 {
+		vm.step().unwrap();
+	}
+	let snapshot = vm.snapshot();
+	let stack = vm.stack().clone();
+	let memory = vm.memory().get(0, 64);
+	let position = vm.inspect().map(|(opcode, _)| opcode);
+
+	assert_eq!(vm.run(), Capture::Exit(ExitSucceed::Stopped.into()));
+	assert_eq!(vm.stack().len(), 1);
+	assert_ne!(vm.memory().get(0, 64), memory);
+
+	vm.restore(snapshot);
+	assert_eq!(vm.stack().len(), stack.len());
+	assert_eq!(vm.memory().get(0, 64), memory);
+	assert_eq!(vm.memory().effective_len(), 32.into());
+	assert_eq!(vm.inspect().map(|(opcode, _)| opcode), position);
+
+	assert_eq!(vm.run(), Capture::Exit(ExitSucceed::Stopped.into()));
+	assert_eq!(vm.stack().peek(0).unwrap()[31], 3);
+	assert_eq!(vm.memory().get(32, 32)[31], 1);
+}
@@ -0,0 +1,39 @@
+use evm_core::{ExitError, Stack};
+use primitive_types::{H256, U256};
+
+#[test]
+fn u256_round_trips_in_big_endian() {
+	let value = U256::from_big_endian(&(1..=32).collect::<Vec<u8>>());
+	let mut stack = Stack::new(1024);
+
+	stack.push_u256(value).unwrap();
+	let mut word = H256::default();
+	value.to_big_endian(&mut word[..]);
+	assert_eq!(stack.peek(0), Ok(word));
+	assert_eq!(word[0], 1);
+	assert_eq!(word[31], 32);
+	assert_eq!(stack.pop_u256(), Ok(value));
+	assert_eq!(stack.len(), 0);
+}
+
+#[test]
+fn small_numbers_are_in_the_last_bytes() {
+	let mut stack = Stack::new(1024);
+
+	stack.push_u256(U256::from(0x1234)).unwrap();
+	assert_eq!(stack.pop_h256(), Ok(H256::from_low_u64_be(0x1234)));
+	stack.push_h256(H256::from_low_u64_be(0x1234)).unwrap();
+	assert_eq!(stack.pop_u256(), Ok(U256::from(0x1234)));
+}
+
+#[test]
+fn typed_push_and_pop_check_limits() {
+	let mut stack = Stack::new(1);
+
+	assert_eq!(stack.pop_u256(), Err(ExitError::StackUnderflow));
+	assert_eq!(stack.pop_h256(), Err(ExitError::StackUnderflow));
+	stack.push_u256(U256::one()).unwrap();
+	assert_eq!(stack.push_u256(U256::one()), Err(ExitError::StackOverflow));
+	assert_eq!(stack.push_h256(H256::zero()), Err(ExitError::StackOverflow));
+	assert_eq!(stack.len(), 1);
+}
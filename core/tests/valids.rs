@@ -0,0 +1,67 @@
+use evm_core::Valids;
+
+/// Jump destinations found by walking the code, one flag per byte.
+fn reference(code: &[u8]) -> Vec<bool> {
+	let mut valids = vec![false; code.len()];
+	let mut i = 0;
+	while i < code.len() {
+		match code[i] {
+			0x5b => valids[i] = true,
+			0x60..=0x7f => i += (code[i] - 0x5f) as usize,
+			_ => (),
+		}
+		i += 1;
+	}
+	valids
+}
+
+fn assert_matches_reference(code: &[u8]) {
+	let valids = Valids::new(code);
+	let expected = reference(code);
+
+	assert_eq!(valids.len(), code.len());
+	for position in 0..(code.len() + 9) {
+		assert_eq!(
+			valids.is_valid(position),
+			expected.get(position).copied().unwrap_or(false),
+			"code {:?} position {}", code, position,
+		);
+	}
+}
+
+#[test]
+fn valids_skip_push_data() {
+	// JUMPDEST at odd positions, and JUMPDEST bytes inside PUSH data,
+	// including a PUSH truncated by the end of the code.
+	let codes: [&[u8]; 5] = [
+		&[],
+		&[0x00, 0x5b, 0x00, 0x5b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5b],
+		&[0x60, 0x5b, 0x5b, 0x61, 0x5b, 0x5b, 0x5b],
+		&[0x7f, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b, 0x5b],
+		&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5b, 0x5b, 0x62, 0x5b],
+	];
+
+	for code in &codes {
+		assert_matches_reference(code);
+	}
+}
+
+#[test]
+fn valids_agree_with_reference_on_generated_code() {
+	let mut seed = 0x2545f4914f6cdd1du64;
+	for len in 0..300 {
+		let code = (0..len).map(|_| {
+			seed ^= seed << 13;
+			seed ^= seed >> 7;
+			seed ^= seed << 17;
+			// Favour JUMPDESTs and PUSHes to exercise both paths.
+			match seed % 4 {
+				0 => 0x5b,
+				1 => 0x60 + (seed >> 8) as u8 % 32,
+				_ => (seed >> 16) as u8,
+			}
+		}).collect::<Vec<u8>>();
+
+		assert_matches_reference(&code);
+	}
+}
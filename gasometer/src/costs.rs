@@ -11,11 +11,11 @@ pub fn call_extra_check(gas: U256, after_gas: u64, config: &Config) -> Result<()
 	}
 }
 
-pub fn suicide_refund(already_removed: bool) -> i64 {
+pub fn suicide_refund(already_removed: bool, config: &Config) -> i64 {
 	if already_removed {
 		0
 	} else {
-		R_SUICIDE
+		config.refund_suicide
 	}
 }
 
@@ -113,11 +113,11 @@ pub fn verylowcopy_cost(len: U256) -> Result<u64, ExitError> {
 	Ok(gas.as_u64())
 }
 
-pub fn extcodecopy_cost(len: U256, config: &Config) -> Result<u64, ExitError> {
+pub fn extcodecopy_cost(len: U256, is_cold: bool, config: &Config) -> Result<u64, ExitError> {
 	let wordd = len / U256::from(32);
 	let wordr = len % U256::from(32);
 
-	let gas = U256::from(config.gas_ext_code).checked_add(
+	let gas = U256::from(address_access_cost(is_cold, config.gas_ext_code, config)).checked_add(
 		U256::from(G_COPY).checked_mul(
 			if wordr == U256::zero() {
 				wordd
@@ -134,11 +134,11 @@ pub fn extcodecopy_cost(len: U256, config: &Config) -> Result<u64, ExitError> {
 	Ok(gas.as_u64())
 }
 
-pub fn log_cost(n: u8, len: U256) -> Result<u64, ExitError> {
-	let gas = U256::from(G_LOG)
-		.checked_add(U256::from(G_LOGDATA).checked_mul(len).ok_or(ExitError::OutOfGas)?)
+pub fn log_cost(n: u8, len: U256, config: &Config) -> Result<u64, ExitError> {
+	let gas = U256::from(config.gas_log)
+		.checked_add(U256::from(config.gas_log_data).checked_mul(len).ok_or(ExitError::OutOfGas)?)
 		.ok_or(ExitError::OutOfGas)?
-		.checked_add(U256::from(G_LOGTOPIC * n as u64))
+		.checked_add(U256::from(config.gas_log_topic * n as u64))
 		.ok_or(ExitError::OutOfGas)?;
 
 	if gas > U256::from(u64::max_value()) {
@@ -169,10 +169,51 @@ pub fn sha3_cost(len: U256) -> Result<u64, ExitError> {
 	Ok(gas.as_u64())
 }
 
-pub fn sstore_cost(original: H256, current: H256, new: H256, gas: u64, config: &Config) -> Result<u64, ExitError> {
+pub fn sload_cost(is_cold: bool, config: &Config) -> u64 {
+	if config.increase_state_access_gas {
+		if is_cold {
+			config.gas_sload_cold
+		} else {
+			config.gas_storage_read_warm
+		}
+	} else {
+		config.gas_sload
+	}
+}
+
+pub fn address_access_cost(is_cold: bool, regular_value: u64, config: &Config) -> u64 {
+	if config.increase_state_access_gas {
+		if is_cold {
+			config.gas_account_access_cold
+		} else {
+			config.gas_storage_read_warm
+		}
+	} else {
+		regular_value
+	}
+}
+
+pub fn sstore_cost(
+	original: H256,
+	current: H256,
+	new: H256,
+	gas: u64,
+	is_cold: bool,
+	config: &Config,
+) -> Result<u64, ExitError> {
+	let gas_cost = sstore_warm_cost(original, current, new, gas, config)?;
+
+	Ok(if config.increase_state_access_gas && is_cold {
+		gas_cost + config.gas_sload_cold
+	} else {
+		gas_cost
+	})
+}
+
+fn sstore_warm_cost(original: H256, current: H256, new: H256, gas: u64, config: &Config) -> Result<u64, ExitError> {
 	if config.sstore_gas_metering {
 		if config.sstore_revert_under_stipend {
-			if gas < config.call_stipend {
+			if gas <= config.call_stipend {
 				return Err(ExitError::OutOfGas)
 			}
 		}
@@ -199,7 +240,7 @@ pub fn sstore_cost(original: H256, current: H256, new: H256, gas: u64, config: &
 	}
 }
 
-pub fn suicide_cost(value: U256, target_exists: bool, config: &Config) -> u64 {
+pub fn suicide_cost(value: U256, is_cold: bool, target_exists: bool, config: &Config) -> u64 {
 	let eip161 = !config.empty_considered_exists;
 	let should_charge_topup = if eip161 {
 		value != U256::zero() && !target_exists
@@ -213,18 +254,25 @@ pub fn suicide_cost(value: U256, target_exists: bool, config: &Config) -> u64 {
 		0
 	};
 
-	config.gas_suicide + suicide_gas_topup
+	let cold_target_cost = if config.increase_state_access_gas && is_cold {
+		config.gas_account_access_cold
+	} else {
+		0
+	};
+
+	config.gas_suicide + suicide_gas_topup + cold_target_cost
 }
 
 pub fn call_cost(
 	value: U256,
+	is_cold: bool,
 	is_call_or_callcode: bool,
 	is_call_or_staticcall: bool,
 	new_account: bool,
 	config: &Config,
 ) -> u64 {
 	let transfers_value = value != U256::default();
-	config.gas_call +
+	address_access_cost(is_cold, config.gas_call, config) +
 		xfer_cost(is_call_or_callcode, transfers_value) +
 		new_cost(is_call_or_staticcall, new_account, transfers_value, config)
 }
@@ -250,12 +298,12 @@ fn new_cost(
 	if is_call_or_staticcall {
 		if eip161 {
 			if transfers_value && new_account {
-				G_NEWACCOUNT
+				config.gas_call_new_account
 			} else {
 				0
 			}
 		} else if new_account {
-			G_NEWACCOUNT
+			config.gas_call_new_account
 		} else {
 			0
 		}
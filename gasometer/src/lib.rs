@@ -5,15 +5,19 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
 mod consts;
 mod costs;
-mod memory;
+mod schedule;
 mod utils;
 
+pub use crate::schedule::{export_gas_schedule, GasSchedule};
+
 use core::cmp::max;
 use primitive_types::{H160, H256, U256};
 use evm_core::{Opcode, ExitError, Stack};
-use evm_runtime::{Handler, Config};
+use evm_runtime::{Handler, Config, INIT_CODE_WORD_COST};
 
 macro_rules! try_or_fail {
 	( $inner:expr, $e:expr ) => (
@@ -42,9 +46,11 @@ impl<'config> Gasometer<'config> {
 			gas_limit,
 			config,
 			inner: Ok(Inner {
+				memory_words: 0,
 				memory_gas: 0,
 				used_gas: 0,
 				refunded_gas: 0,
+				floor_gas: 0,
 				config,
 			}),
 		}
@@ -57,6 +63,12 @@ impl<'config> Gasometer<'config> {
 		self.inner.as_mut().map_err(|e| e.clone())
 	}
 
+	#[inline]
+	/// Gas limit of the gasometer.
+	pub fn gas_limit(&self) -> u64 {
+		self.gas_limit
+	}
+
 	#[inline]
 	/// Reference of the config.
 	pub fn config(&self) -> &'config Config {
@@ -90,6 +102,16 @@ impl<'config> Gasometer<'config> {
 		}
 	}
 
+	#[inline]
+	/// Minimum gas the transaction pays, regardless of refunds. Zero unless
+	/// the calldata floor is enabled.
+	pub fn floor_gas(&self) -> u64 {
+		match self.inner.as_ref() {
+			Ok(inner) => inner.floor_gas,
+			Err(_) => 0,
+		}
+	}
+
 	/// Explictly fail the gasometer with out of gas. Return `OutOfGas` error.
 	pub fn fail(&mut self) -> ExitError {
 		self.inner = Err(ExitError::OutOfGas);
@@ -102,6 +124,10 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		cost: u64,
 	) -> Result<(), ExitError> {
+		if !self.config.metering {
+			return Ok(())
+		}
+
 		let all_gas_cost = self.total_used_gas() + cost;
 		if self.gas_limit < all_gas_cost {
 			self.inner = Err(ExitError::OutOfGas);
@@ -118,6 +144,10 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		refund: i64,
 	) -> Result<(), ExitError> {
+		if !self.config.metering {
+			return Ok(())
+		}
+
 		self.inner_mut()?.refunded_gas += refund;
 		Ok(())
 	}
@@ -138,26 +168,58 @@ impl<'config> Gasometer<'config> {
 		cost: GasCost,
 		memory: Option<MemoryCost>,
 	) -> Result<(), ExitError> {
+		self.record_dynamic_cost_with(cost, memory, evm_core::memory_expansion_cost)
+	}
+
+	/// Memory size in words before and after expanding the memory to cover
+	/// the given range, as priced by `record_dynamic_cost_with`. Both are the
+	/// same if the memory already covers it.
+	pub fn memory_expansion(&self, memory: MemoryCost) -> Result<(usize, usize), ExitError> {
+		let inner = self.inner.as_ref().map_err(|e| e.clone())?;
+		Ok((inner.memory_words, inner.memory_words(memory)?))
+	}
+
+	/// Record dynamic cost as `record_dynamic_cost`, pricing the expansion of
+	/// the memory from its current to its new size in words with the given
+	/// function, as `Handler::memory_expansion_cost` does.
+	pub fn record_dynamic_cost_with<F: FnOnce(usize, usize) -> u64>(
+		&mut self,
+		cost: GasCost,
+		memory: Option<MemoryCost>,
+		memory_expansion_cost: F,
+	) -> Result<(), ExitError> {
+		if !self.config.metering {
+			return Ok(())
+		}
+
 		let gas = self.gas();
 
-		let memory_gas = match memory {
-			Some(memory) => try_or_fail!(self.inner, self.inner_mut()?.memory_gas(memory)),
-			None => self.inner_mut()?.memory_gas,
+		let (memory_words, memory_gas) = match memory {
+			Some(memory) => try_or_fail!(
+				self.inner,
+				self.inner_mut()?.memory_gas(memory, memory_expansion_cost)
+			),
+			None => (self.inner_mut()?.memory_words, self.inner_mut()?.memory_gas),
 		};
 		let gas_cost = try_or_fail!(self.inner, self.inner_mut()?.gas_cost(cost, gas));
 		let gas_refund = self.inner_mut()?.gas_refund(cost);
 		let used_gas = self.inner_mut()?.used_gas;
 
-		let all_gas_cost = memory_gas + used_gas + gas_cost;
-		if self.gas_limit < all_gas_cost {
-			self.inner = Err(ExitError::OutOfGas);
-			return Err(ExitError::OutOfGas)
-		}
+		let all_gas_cost = memory_gas.checked_add(used_gas)
+			.and_then(|all_gas_cost| all_gas_cost.checked_add(gas_cost));
+		let all_gas_cost = match all_gas_cost {
+			Some(all_gas_cost) if all_gas_cost <= self.gas_limit => all_gas_cost,
+			_ => {
+				self.inner = Err(ExitError::OutOfGas);
+				return Err(ExitError::OutOfGas)
+			},
+		};
 
 		let after_gas = self.gas_limit - all_gas_cost;
 		try_or_fail!(self.inner, self.inner_mut()?.extra_check(cost, after_gas));
 
 		self.inner_mut()?.used_gas += gas_cost;
+		self.inner_mut()?.memory_words = memory_words;
 		self.inner_mut()?.memory_gas = memory_gas;
 		self.inner_mut()?.refunded_gas += gas_refund;
 
@@ -170,6 +232,10 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		stipend: u64,
 	) -> Result<(), ExitError> {
+		if !self.config.metering {
+			return Ok(())
+		}
+
 		self.inner_mut()?.used_gas -= stipend;
 		Ok(())
 	}
@@ -179,29 +245,60 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		cost: TransactionCost,
 	) -> Result<(), ExitError> {
+		if !self.config.metering {
+			return Ok(())
+		}
+
+		let (zero_data_len, non_zero_data_len) = match cost {
+			TransactionCost::Call { zero_data_len, non_zero_data_len } |
+			TransactionCost::Create { zero_data_len, non_zero_data_len } =>
+				(zero_data_len as u64, non_zero_data_len as u64),
+		};
 		let gas_cost = match cost {
-			TransactionCost::Call { zero_data_len, non_zero_data_len } => {
+			TransactionCost::Call { .. } => {
 				self.config.gas_transaction_call +
-					zero_data_len as u64 * self.config.gas_transaction_zero_data +
-					non_zero_data_len as u64 * self.config.gas_transaction_non_zero_data
+					zero_data_len * self.config.gas_transaction_zero_data +
+					non_zero_data_len * self.config.gas_transaction_non_zero_data
 			},
-			TransactionCost::Create { zero_data_len, non_zero_data_len } => {
+			TransactionCost::Create { .. } => {
+				let init_code_cost = if self.config.max_initcode_size.is_some() {
+					(zero_data_len + non_zero_data_len).div_ceil(32) * INIT_CODE_WORD_COST
+				} else {
+					0
+				};
+
 				self.config.gas_transaction_create +
-					zero_data_len as u64 * self.config.gas_transaction_zero_data +
-					non_zero_data_len as u64 * self.config.gas_transaction_non_zero_data
+					zero_data_len * self.config.gas_transaction_zero_data +
+					non_zero_data_len * self.config.gas_transaction_non_zero_data +
+					init_code_cost
 			},
 		};
 
-		if self.gas() < gas_cost {
+		let floor_gas = if self.config.has_calldata_floor {
+			self.config.gas_transaction_call +
+				(zero_data_len + non_zero_data_len * 4) * self.config.gas_transaction_floor_per_token
+		} else {
+			0
+		};
+
+		if self.gas() < max(gas_cost, floor_gas) {
 			self.inner = Err(ExitError::OutOfGas);
 			return Err(ExitError::OutOfGas);
 		}
 
-		self.inner_mut()?.used_gas += gas_cost;
+		let inner = self.inner_mut()?;
+		inner.used_gas += gas_cost;
+		inner.floor_gas = floor_gas;
 		Ok(())
 	}
 }
 
+/// Gas left after retaining one 64th of the given gas, the most a sub-call
+/// or sub-create can be given (EIP-150).
+pub fn all_but_one_64th(gas: u64) -> u64 {
+	gas - gas / 64
+}
+
 /// Calculate the call transaction cost.
 pub fn call_transaction_cost(
 	data: &[u8]
@@ -347,7 +444,36 @@ pub fn static_opcode_cost(
 	TABLE[opcode.as_usize()]
 }
 
-/// Calculate the opcode cost.
+/// Fixed gas cost of an opcode under the config, for static analysis.
+/// `None` for opcodes not available under the config, and for opcodes whose
+/// cost depends on their operands, memory or state, such as CALL, SSTORE,
+/// and account and storage accesses with EIP-2929.
+pub fn base_opcode_cost(opcode: Opcode, config: &Config) -> Option<u64> {
+	if let Some(cost) = static_opcode_cost(opcode) {
+		return Some(cost)
+	}
+
+	match opcode {
+		Opcode::CHAINID if config.has_chain_id => Some(consts::G_BASE),
+		Opcode::SHL | Opcode::SHR | Opcode::SAR if config.has_bitwise_shifting =>
+			Some(consts::G_VERYLOW),
+		Opcode::SELFBALANCE if config.has_self_balance => Some(consts::G_LOW),
+		Opcode::RETURNDATASIZE if config.has_return_data => Some(consts::G_BASE),
+		Opcode::BLOCKHASH => Some(consts::G_BLOCKHASH),
+		Opcode::TLOAD | Opcode::TSTORE if config.has_transient_storage =>
+			Some(config.gas_storage_read_warm),
+
+		_ if config.increase_state_access_gas => None,
+		Opcode::SLOAD => Some(config.gas_sload),
+		Opcode::BALANCE => Some(config.gas_balance),
+		Opcode::EXTCODESIZE => Some(config.gas_ext_code),
+		Opcode::EXTCODEHASH if config.has_ext_code_hash => Some(config.gas_ext_code_hash),
+		_ => None,
+	}
+}
+
+/// Calculate the opcode cost. All stack operands are read here, so a stack
+/// underflow fails with `StackUnderflow` before any gas is recorded.
 pub fn dynamic_opcode_cost<H: Handler>(
 	address: H160,
 	opcode: Opcode,
@@ -355,7 +481,8 @@ pub fn dynamic_opcode_cost<H: Handler>(
 	is_static: bool,
 	config: &Config,
 	handler: &H
-) -> Result<(GasCost, Option<MemoryCost>), ExitError> {
+) -> Result<(GasCost, StorageTarget, Option<MemoryCost>), ExitError> {
+	let mut storage_target = StorageTarget::None;
 	let gas_cost = match opcode {
 		Opcode::RETURN => GasCost::Zero,
 
@@ -374,27 +501,54 @@ pub fn dynamic_opcode_cost<H: Handler>(
 		Opcode::SELFBALANCE if config.has_self_balance => GasCost::Low,
 		Opcode::SELFBALANCE => GasCost::Invalid,
 
-		Opcode::EXTCODESIZE => GasCost::ExtCodeSize,
-		Opcode::BALANCE => GasCost::Balance,
+		Opcode::EXTCODESIZE => {
+			let target = stack.peek(0)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::ExtCodeSize { target_is_cold: handler.is_cold(target, None) }
+		},
+		Opcode::BALANCE => {
+			let target = stack.peek(0)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::Balance { target_is_cold: handler.is_cold(target, None) }
+		},
 		Opcode::BLOCKHASH => GasCost::BlockHash,
 
-		Opcode::EXTCODEHASH if config.has_ext_code_hash => GasCost::ExtCodeHash,
+		Opcode::EXTCODEHASH if config.has_ext_code_hash => {
+			let target = stack.peek(0)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::ExtCodeHash { target_is_cold: handler.is_cold(target, None) }
+		},
 		Opcode::EXTCODEHASH => GasCost::Invalid,
 
-		Opcode::CALLCODE => GasCost::CallCode {
-			value: U256::from_big_endian(&stack.peek(2)?[..]),
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()),
+		Opcode::CALLCODE => {
+			let target = stack.peek(1)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::CallCode {
+				value: U256::from_big_endian(&stack.peek(2)?[..]),
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold: handler.is_cold(target, None),
+				target_exists: handler.exists(target),
+			}
 		},
-		Opcode::STATICCALL => GasCost::StaticCall {
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()),
+		Opcode::STATICCALL => {
+			let target = stack.peek(1)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::StaticCall {
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold: handler.is_cold(target, None),
+				target_exists: handler.exists(target),
+			}
 		},
 		Opcode::SHA3 => GasCost::Sha3 {
 			len: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
-		Opcode::EXTCODECOPY => GasCost::ExtCodeCopy {
-			len: U256::from_big_endian(&stack.peek(3)?[..]),
+		Opcode::EXTCODECOPY => {
+			let target = stack.peek(0)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::ExtCodeCopy {
+				target_is_cold: handler.is_cold(target, None),
+				len: U256::from_big_endian(&stack.peek(3)?[..]),
+			}
 		},
 		Opcode::CALLDATACOPY | Opcode::CODECOPY => GasCost::VeryLowCopy {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
@@ -402,11 +556,24 @@ pub fn dynamic_opcode_cost<H: Handler>(
 		Opcode::EXP => GasCost::Exp {
 			power: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
-		Opcode::SLOAD => GasCost::SLoad,
+		Opcode::SLOAD => {
+			let index = stack.peek(0)?;
+			storage_target = StorageTarget::Slot(address, index);
+			GasCost::SLoad { target_is_cold: handler.is_cold(address, Some(index)) }
+		},
 
-		Opcode::DELEGATECALL if config.has_delegate_call => GasCost::DelegateCall {
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()),
+		Opcode::TLOAD if config.has_transient_storage => GasCost::WarmStorageRead,
+		Opcode::TSTORE if !is_static && config.has_transient_storage =>
+			GasCost::WarmStorageRead,
+
+		Opcode::DELEGATECALL if config.has_delegate_call => {
+			let target = stack.peek(1)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::DelegateCall {
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold: handler.is_cold(target, None),
+				target_exists: handler.exists(target),
+			}
 		},
 		Opcode::DELEGATECALL => GasCost::Invalid,
 
@@ -419,11 +586,13 @@ pub fn dynamic_opcode_cost<H: Handler>(
 		Opcode::SSTORE if !is_static => {
 			let index = stack.peek(0)?;
 			let value = stack.peek(1)?;
+			storage_target = StorageTarget::Slot(address, index);
 
 			GasCost::SStore {
 				original: handler.original_storage(address, index),
 				current: handler.storage(address, index),
 				new: value,
+				target_is_cold: handler.is_cold(address, Some(index)),
 			}
 		},
 		Opcode::LOG0 if !is_static => GasCost::Log {
@@ -450,19 +619,37 @@ pub fn dynamic_opcode_cost<H: Handler>(
 		Opcode::CREATE2 if !is_static && config.has_create2 => GasCost::Create2 {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		},
-		Opcode::SUICIDE if !is_static => GasCost::Suicide {
-			value: handler.balance(address),
-			target_exists: handler.exists(stack.peek(0)?.into()),
-			already_removed: handler.deleted(address),
+		Opcode::SUICIDE if !is_static => {
+			let target = stack.peek(0)?.into();
+			storage_target = StorageTarget::Address(target);
+			GasCost::Suicide {
+				value: handler.balance(address),
+				target_is_cold: handler.is_cold(target, None),
+				target_exists: handler.exists(target),
+				already_removed: handler.deleted(address),
+			}
 		},
 		Opcode::CALL
 			if !is_static ||
 			(is_static && U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero()) =>
+		{
+			let target = stack.peek(1)?.into();
+			storage_target = StorageTarget::Address(target);
 			GasCost::Call {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
-				target_exists: handler.exists(stack.peek(1)?.into()),
-			},
+				target_is_cold: handler.is_cold(target, None),
+				target_exists: handler.exists(target),
+			}
+		},
+
+		Opcode::SSTORE | Opcode::LOG0 | Opcode::LOG1 | Opcode::LOG2 | Opcode::LOG3 |
+		Opcode::LOG4 | Opcode::CREATE | Opcode::SUICIDE | Opcode::CALL if is_static =>
+			return Err(ExitError::StaticModeViolation),
+		Opcode::CREATE2 if is_static && config.has_create2 =>
+			return Err(ExitError::StaticModeViolation),
+		Opcode::TSTORE if is_static && config.has_transient_storage =>
+			return Err(ExitError::StaticModeViolation),
 
 		_ => GasCost::Invalid,
 	};
@@ -521,27 +708,28 @@ pub fn dynamic_opcode_cost<H: Handler>(
 		_ => None,
 	};
 
-	Ok((gas_cost, memory_cost))
+	Ok((gas_cost, storage_target, memory_cost))
 }
 
 #[derive(Clone)]
 struct Inner<'config> {
+	memory_words: usize,
 	memory_gas: u64,
 	used_gas: u64,
 	refunded_gas: i64,
+	floor_gas: u64,
 	config: &'config Config,
 }
 
 impl<'config> Inner<'config> {
-	fn memory_gas(
-		&self,
-		memory: MemoryCost,
-	) -> Result<u64, ExitError> {
+	/// Memory size in words after expanding the memory to cover the given
+	/// range, if it is not already.
+	fn memory_words(&self, memory: MemoryCost) -> Result<usize, ExitError> {
 		let from = memory.offset;
 		let len = memory.len;
 
 		if len == U256::zero() {
-			return Ok(self.memory_gas)
+			return Ok(self.memory_words)
 		}
 
 		let end = from.checked_add(len).ok_or(ExitError::OutOfGas)?;
@@ -558,7 +746,24 @@ impl<'config> Inner<'config> {
 			end / 32 + 1
 		};
 
-		Ok(max(self.memory_gas, memory::memory_gas(new)?))
+		Ok(max(new, self.memory_words))
+	}
+
+	/// Memory size in words and total memory gas after expanding the memory
+	/// to cover the given range, if it is not already.
+	fn memory_gas<F: FnOnce(usize, usize) -> u64>(
+		&self,
+		memory: MemoryCost,
+		memory_expansion_cost: F,
+	) -> Result<(usize, u64), ExitError> {
+		let new = self.memory_words(memory)?;
+		if new <= self.memory_words {
+			return Ok((self.memory_words, self.memory_gas))
+		}
+
+		let cost = memory_expansion_cost(self.memory_words, new);
+		let memory_gas = self.memory_gas.checked_add(cost).ok_or(ExitError::OutOfGas)?;
+		Ok((new, memory_gas))
 	}
 
 	fn extra_check(
@@ -581,39 +786,44 @@ impl<'config> Inner<'config> {
 		gas: u64,
 	) -> Result<u64, ExitError> {
 		Ok(match cost {
-			GasCost::Call { value, target_exists, .. } =>
-				costs::call_cost(value, true, true, !target_exists, self.config),
-			GasCost::CallCode { value, target_exists, .. } =>
-				costs::call_cost(value, true, false, !target_exists, self.config),
-			GasCost::DelegateCall { target_exists, .. } =>
-				costs::call_cost(U256::zero(), false, false, !target_exists, self.config),
-			GasCost::StaticCall { target_exists, .. } =>
-				costs::call_cost(U256::zero(), false, true, !target_exists, self.config),
-			GasCost::Suicide { value, target_exists, .. } =>
-				costs::suicide_cost(value, target_exists, self.config),
+			GasCost::Call { value, target_is_cold, target_exists, .. } =>
+				costs::call_cost(value, target_is_cold, true, true, !target_exists, self.config),
+			GasCost::CallCode { value, target_is_cold, target_exists, .. } =>
+				costs::call_cost(value, target_is_cold, true, false, !target_exists, self.config),
+			GasCost::DelegateCall { target_is_cold, target_exists, .. } =>
+				costs::call_cost(U256::zero(), target_is_cold, false, false, !target_exists, self.config),
+			GasCost::StaticCall { target_is_cold, target_exists, .. } =>
+				costs::call_cost(U256::zero(), target_is_cold, false, true, !target_exists, self.config),
+			GasCost::Suicide { value, target_is_cold, target_exists, .. } =>
+				costs::suicide_cost(value, target_is_cold, target_exists, self.config),
 			GasCost::SStore { .. } if self.config.estimate => self.config.gas_sstore_set,
-			GasCost::SStore { original, current, new } =>
-				costs::sstore_cost(original, current, new, gas, self.config)?,
+			GasCost::SStore { original, current, new, target_is_cold } =>
+				costs::sstore_cost(original, current, new, gas, target_is_cold, self.config)?,
 
 			GasCost::Sha3 { len } => costs::sha3_cost(len)?,
-			GasCost::Log { n, len } => costs::log_cost(n, len)?,
-			GasCost::ExtCodeCopy { len } => costs::extcodecopy_cost(len, self.config)?,
+			GasCost::Log { n, len } => costs::log_cost(n, len, self.config)?,
+			GasCost::ExtCodeCopy { target_is_cold, len } =>
+				costs::extcodecopy_cost(len, target_is_cold, self.config)?,
 			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len)?,
 			GasCost::Exp { power } => costs::exp_cost(power, self.config)?,
 			GasCost::Create => consts::G_CREATE,
 			GasCost::Create2 { len } => costs::create2_cost(len)?,
-			GasCost::SLoad => self.config.gas_sload,
+			GasCost::SLoad { target_is_cold } => costs::sload_cost(target_is_cold, self.config),
 
 			GasCost::Zero => consts::G_ZERO,
 			GasCost::Base => consts::G_BASE,
 			GasCost::VeryLow => consts::G_VERYLOW,
 			GasCost::Low => consts::G_LOW,
+			GasCost::WarmStorageRead => self.config.gas_storage_read_warm,
 			GasCost::Invalid => return Err(ExitError::OutOfGas),
 
-			GasCost::ExtCodeSize => self.config.gas_ext_code,
-			GasCost::Balance => self.config.gas_balance,
+			GasCost::ExtCodeSize { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_ext_code, self.config),
+			GasCost::Balance { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_balance, self.config),
 			GasCost::BlockHash => consts::G_BLOCKHASH,
-			GasCost::ExtCodeHash => self.config.gas_ext_code_hash,
+			GasCost::ExtCodeHash { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_ext_code_hash, self.config),
 		})
 	}
 
@@ -624,10 +834,10 @@ impl<'config> Inner<'config> {
 		match cost {
 			_ if self.config.estimate => 0,
 
-			GasCost::SStore { original, current, new } =>
+			GasCost::SStore { original, current, new, .. } =>
 				costs::sstore_refund(original, current, new, self.config),
 			GasCost::Suicide { already_removed, .. } =>
-				costs::suicide_refund(already_removed),
+				costs::suicide_refund(already_removed, self.config),
 			_ => 0,
 		}
 	}
@@ -644,17 +854,28 @@ pub enum GasCost {
 	VeryLow,
 	/// Low gas cost.
 	Low,
+	/// Warm storage read gas cost, also charged by TLOAD and TSTORE.
+	WarmStorageRead,
 	/// Fail the gasometer.
 	Invalid,
 
 	/// Gas cost for `EXTCODESIZE`.
-	ExtCodeSize,
+	ExtCodeSize {
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool
+	},
 	/// Gas cost for `BALANCE`.
-	Balance,
+	Balance {
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool
+	},
 	/// Gas cost for `BLOCKHASH`.
 	BlockHash,
 	/// Gas cost for `EXTBLOCKHASH`.
-	ExtCodeHash,
+	ExtCodeHash {
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool
+	},
 
 	/// Gas cost for `CALL`.
 	Call {
@@ -662,6 +883,8 @@ pub enum GasCost {
 		value: U256,
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -671,6 +894,8 @@ pub enum GasCost {
 		value: U256,
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -678,6 +903,8 @@ pub enum GasCost {
 	DelegateCall {
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -685,6 +912,8 @@ pub enum GasCost {
 	StaticCall {
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -692,6 +921,8 @@ pub enum GasCost {
 	Suicide {
 		/// Value.
 		value: U256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool,
 		/// Whether the target has already been removed.
@@ -704,7 +935,9 @@ pub enum GasCost {
 		/// Current value.
 		current: H256,
 		/// New value.
-		new: H256
+		new: H256,
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool
 	},
 	/// Gas cost for `SHA3`.
 	Sha3 {
@@ -720,6 +953,8 @@ pub enum GasCost {
 	},
 	/// Gas cost for `EXTCODECOPY`.
 	ExtCodeCopy {
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool,
 		/// Length.
 		len: U256
 	},
@@ -741,7 +976,21 @@ pub enum GasCost {
 		len: U256
 	},
 	/// Gas cost for `SLOAD`.
-	SLoad,
+	SLoad {
+		/// Whether the target is cold (EIP-2929).
+		target_is_cold: bool
+	},
+}
+
+/// Storage opcode will access. Used for tracking accessed storage (EIP-2929).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StorageTarget {
+	/// No storage access.
+	None,
+	/// Accessing an address.
+	Address(H160),
+	/// Accessing a storage slot of an address.
+	Slot(H160, H256),
 }
 
 /// Memory cost.
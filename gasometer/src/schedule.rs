@@ -0,0 +1,167 @@
+use alloc::collections::BTreeMap;
+use primitive_types::U256;
+use evm_core::Opcode;
+use evm_runtime::Config;
+use crate::{consts, static_opcode_cost, GasCost, Inner};
+
+/// Gas schedule of a config, for documentation and tooling.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasSchedule {
+	/// Gas paid for each opcode available under the config, keyed by its
+	/// byte. Dynamic costs are listed for a warm target, no value and empty
+	/// operands. `SSTORE` is left out, as its cost depends on the storage.
+	pub opcodes: BTreeMap<u8, u64>,
+	/// Gas paid for SLOAD of a cold slot (EIP-2929).
+	pub gas_sload_cold: u64,
+	/// Gas paid for accessing a warm address or storage slot (EIP-2929).
+	pub gas_storage_read_warm: u64,
+	/// Gas paid for accessing a cold address (EIP-2929).
+	pub gas_account_access_cold: u64,
+	/// Gas paid for sstore set.
+	pub gas_sstore_set: u64,
+	/// Gas paid for sstore reset.
+	pub gas_sstore_reset: u64,
+	/// Gas refunded for sstore clears.
+	pub refund_sstore_clears: i64,
+	/// Gas paid for SUICIDE when it hits a new account.
+	pub gas_suicide_new_account: u64,
+	/// Gas refunded for SUICIDE.
+	pub refund_suicide: i64,
+	/// Gas paid for EXP for every byte of the exponent.
+	pub gas_expbyte: u64,
+	/// Gas paid for calls transferring value.
+	pub gas_call_value: u64,
+	/// Gas paid for calls creating a new account.
+	pub gas_new_account: u64,
+	/// Gas given to the callee of calls transferring value.
+	pub call_stipend: u64,
+	/// Gas paid for every word of memory.
+	pub gas_memory: u64,
+	/// Gas paid for every word copied.
+	pub gas_copy: u64,
+	/// Gas paid for every word hashed by SHA3 and CREATE2.
+	pub gas_sha3_word: u64,
+	/// Gas paid for every byte of log data.
+	pub gas_log_data: u64,
+	/// Gas paid for every log topic.
+	pub gas_log_topic: u64,
+	/// Gas paid for every byte of deployed code.
+	pub gas_code_deposit: u64,
+	/// Gas paid for a contract creation transaction.
+	pub gas_transaction_create: u64,
+	/// Gas paid for a message call transaction.
+	pub gas_transaction_call: u64,
+	/// Gas paid for zero data in a transaction.
+	pub gas_transaction_zero_data: u64,
+	/// Gas paid for non-zero data in a transaction.
+	pub gas_transaction_non_zero_data: u64,
+	/// Refunds are capped to the used gas divided by this quotient.
+	pub max_refund_quotient: u64,
+}
+
+/// Export the gas schedule of the given config.
+pub fn export_gas_schedule(config: &Config) -> GasSchedule {
+	let inner = Inner {
+		memory_words: 0,
+		memory_gas: 0,
+		used_gas: 0,
+		refunded_gas: 0,
+		floor_gas: 0,
+		config,
+	};
+
+	let mut opcodes = BTreeMap::new();
+	for byte in 0..=u8::MAX {
+		let opcode = Opcode(byte);
+		let cost = match static_opcode_cost(opcode) {
+			Some(cost) => Some(cost),
+			None => base_cost(opcode, config).and_then(|cost| inner.gas_cost(cost, 0).ok()),
+		};
+
+		if let Some(cost) = cost {
+			opcodes.insert(byte, cost);
+		}
+	}
+
+	GasSchedule {
+		opcodes,
+		gas_sload_cold: config.gas_sload_cold,
+		gas_storage_read_warm: config.gas_storage_read_warm,
+		gas_account_access_cold: config.gas_account_access_cold,
+		gas_sstore_set: config.gas_sstore_set,
+		gas_sstore_reset: config.gas_sstore_reset,
+		refund_sstore_clears: config.refund_sstore_clears,
+		gas_suicide_new_account: config.gas_suicide_new_account,
+		refund_suicide: config.refund_suicide,
+		gas_expbyte: config.gas_expbyte,
+		gas_call_value: consts::G_CALLVALUE,
+		gas_new_account: config.gas_call_new_account,
+		call_stipend: config.call_stipend,
+		gas_memory: consts::G_MEMORY,
+		gas_copy: consts::G_COPY,
+		gas_sha3_word: consts::G_SHA3WORD,
+		gas_log_data: config.gas_log_data,
+		gas_log_topic: config.gas_log_topic,
+		gas_code_deposit: consts::G_CODEDEPOSIT,
+		gas_transaction_create: config.gas_transaction_create,
+		gas_transaction_call: config.gas_transaction_call,
+		gas_transaction_zero_data: config.gas_transaction_zero_data,
+		gas_transaction_non_zero_data: config.gas_transaction_non_zero_data,
+		max_refund_quotient: config.max_refund_quotient,
+	}
+}
+
+/// Cost of an opcode without a static cost, for a warm and existing target,
+/// no value and empty operands.
+fn base_cost(opcode: Opcode, config: &Config) -> Option<GasCost> {
+	let zero = U256::zero();
+
+	Some(match opcode {
+		Opcode::RETURN => GasCost::Zero,
+		Opcode::MLOAD | Opcode::MSTORE | Opcode::MSTORE8 => GasCost::VeryLow,
+		Opcode::REVERT if config.has_revert => GasCost::Zero,
+		Opcode::CHAINID if config.has_chain_id => GasCost::Base,
+		Opcode::SHL | Opcode::SHR | Opcode::SAR if config.has_bitwise_shifting =>
+			GasCost::VeryLow,
+		Opcode::SELFBALANCE if config.has_self_balance => GasCost::Low,
+		Opcode::EXTCODESIZE => GasCost::ExtCodeSize { target_is_cold: false },
+		Opcode::BALANCE => GasCost::Balance { target_is_cold: false },
+		Opcode::BLOCKHASH => GasCost::BlockHash,
+		Opcode::EXTCODEHASH if config.has_ext_code_hash =>
+			GasCost::ExtCodeHash { target_is_cold: false },
+		Opcode::CALLCODE => GasCost::CallCode {
+			value: zero, gas: zero, target_is_cold: false, target_exists: true,
+		},
+		Opcode::STATICCALL => GasCost::StaticCall {
+			gas: zero, target_is_cold: false, target_exists: true,
+		},
+		Opcode::SHA3 => GasCost::Sha3 { len: zero },
+		Opcode::EXTCODECOPY => GasCost::ExtCodeCopy { target_is_cold: false, len: zero },
+		Opcode::CALLDATACOPY | Opcode::CODECOPY => GasCost::VeryLowCopy { len: zero },
+		Opcode::EXP => GasCost::Exp { power: zero },
+		Opcode::SLOAD => GasCost::SLoad { target_is_cold: false },
+		Opcode::TLOAD | Opcode::TSTORE if config.has_transient_storage =>
+			GasCost::WarmStorageRead,
+		Opcode::DELEGATECALL if config.has_delegate_call => GasCost::DelegateCall {
+			gas: zero, target_is_cold: false, target_exists: true,
+		},
+		Opcode::RETURNDATASIZE if config.has_return_data => GasCost::Base,
+		Opcode::RETURNDATACOPY if config.has_return_data => GasCost::VeryLowCopy { len: zero },
+		Opcode::LOG0 => GasCost::Log { n: 0, len: zero },
+		Opcode::LOG1 => GasCost::Log { n: 1, len: zero },
+		Opcode::LOG2 => GasCost::Log { n: 2, len: zero },
+		Opcode::LOG3 => GasCost::Log { n: 3, len: zero },
+		Opcode::LOG4 => GasCost::Log { n: 4, len: zero },
+		Opcode::CREATE => GasCost::Create,
+		Opcode::CREATE2 if config.has_create2 => GasCost::Create2 { len: zero },
+		Opcode::SUICIDE => GasCost::Suicide {
+			value: zero, target_is_cold: false, target_exists: true, already_removed: false,
+		},
+		Opcode::CALL => GasCost::Call {
+			value: zero, gas: zero, target_is_cold: false, target_exists: true,
+		},
+		_ => return None,
+	})
+}
+
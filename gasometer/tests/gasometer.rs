@@ -0,0 +1,98 @@
+use evm_core::{ExitError, Opcode};
+use evm_gasometer::{static_opcode_cost, GasCost, Gasometer, MemoryCost};
+use evm_runtime::Config;
+use primitive_types::{H256, U256};
+
+#[test]
+fn record_cost_fails_at_exceeding_opcode() {
+	let config = Config::istanbul();
+	let mut gasometer = Gasometer::new(10, &config);
+	let opcodes = [Opcode::PUSH1, Opcode::PUSH1, Opcode::ADD, Opcode::PUSH1];
+
+	for opcode in &opcodes[..3] {
+		gasometer.record_cost(static_opcode_cost(*opcode).unwrap()).unwrap();
+	}
+	assert_eq!(gasometer.gas(), 1);
+
+	assert_eq!(
+		gasometer.record_cost(static_opcode_cost(opcodes[3]).unwrap()),
+		Err(ExitError::OutOfGas)
+	);
+	assert_eq!(gasometer.gas(), 0);
+	assert_eq!(gasometer.total_used_gas(), gasometer.gas_limit());
+}
+
+#[test]
+fn record_dynamic_cost_tracks_refund() {
+	let config = Config::istanbul();
+	let mut gasometer = Gasometer::new(10_000, &config);
+
+	gasometer.record_dynamic_cost(GasCost::SStore {
+		original: H256::from_low_u64_be(1),
+		current: H256::from_low_u64_be(1),
+		new: H256::zero(),
+		target_is_cold: false,
+	}, None).unwrap();
+
+	assert_eq!(gasometer.total_used_gas(), config.gas_sstore_reset);
+	assert_eq!(gasometer.gas(), 10_000 - config.gas_sstore_reset);
+	assert_eq!(gasometer.refunded_gas(), config.refund_sstore_clears);
+
+	gasometer.record_refund(-config.refund_sstore_clears).unwrap();
+	assert_eq!(gasometer.refunded_gas(), 0);
+	assert_eq!(gasometer.gas(), 5_000);
+}
+
+#[test]
+fn exp_cost_of_maximal_exponent() {
+	let config = Config::istanbul();
+	let cost = 10 + config.gas_expbyte * 32;
+
+	let mut gasometer = Gasometer::new(cost, &config);
+	gasometer.record_dynamic_cost(GasCost::Exp { power: U256::MAX }, None).unwrap();
+	assert_eq!(gasometer.gas(), 0);
+
+	let mut gasometer = Gasometer::new(cost - 1, &config);
+	assert_eq!(
+		gasometer.record_dynamic_cost(GasCost::Exp { power: U256::MAX }, None),
+		Err(ExitError::OutOfGas)
+	);
+	assert_eq!(gasometer.gas(), 0);
+}
+
+#[test]
+fn exp_cost_overflow_is_out_of_gas() {
+	let mut config = Config::istanbul();
+	config.gas_expbyte = u64::MAX;
+
+	let mut gasometer = Gasometer::new(u64::MAX, &config);
+	assert_eq!(
+		gasometer.record_dynamic_cost(GasCost::Exp { power: U256::MAX }, None),
+		Err(ExitError::OutOfGas)
+	);
+	assert_eq!(gasometer.gas(), 0);
+}
+
+#[test]
+fn memory_expansion_is_priced_by_the_given_cost() {
+	let config = Config::istanbul();
+	let mut gasometer = Gasometer::new(100_000, &config);
+	let memory = |offset: u64, len: u64| MemoryCost { offset: offset.into(), len: len.into() };
+
+	assert_eq!(gasometer.memory_expansion(memory(0, 0)), Ok((0, 0)));
+	assert_eq!(gasometer.memory_expansion(memory(0, 33)), Ok((0, 2)));
+	gasometer.record_dynamic_cost_with(GasCost::Zero, Some(memory(0, 33)), |current, new| {
+		assert_eq!((current, new), (0, 2));
+		1000
+	}).unwrap();
+	assert_eq!(gasometer.total_used_gas(), 1000);
+
+	// Memory already covered is not priced again.
+	assert_eq!(gasometer.memory_expansion(memory(32, 32)), Ok((2, 2)));
+	gasometer.record_dynamic_cost_with(GasCost::Zero, Some(memory(32, 32)), |_, _| unreachable!()).unwrap();
+	assert_eq!(gasometer.memory_expansion(memory(64, 1)), Ok((2, 3)));
+	assert_eq!(
+		gasometer.memory_expansion(MemoryCost { offset: U256::MAX, len: 1.into() }),
+		Err(ExitError::OutOfGas)
+	);
+}
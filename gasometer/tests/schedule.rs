@@ -0,0 +1,59 @@
+use evm_core::Opcode;
+use evm_gasometer::{base_opcode_cost, export_gas_schedule};
+use evm_runtime::Config;
+
+#[test]
+fn istanbul_schedule_lists_opcode_costs() {
+	let schedule = export_gas_schedule(&Config::istanbul());
+	let cost = |opcode: Opcode| schedule.opcodes.get(&opcode.as_u8()).copied();
+
+	assert_eq!(cost(Opcode::ADD), Some(3));
+	assert_eq!(cost(Opcode::SLOAD), Some(800));
+	assert_eq!(cost(Opcode::CALL), Some(700));
+	assert_eq!(cost(Opcode::BALANCE), Some(700));
+	assert_eq!(cost(Opcode::SHA3), Some(30));
+	assert_eq!(cost(Opcode::SSTORE), None);
+	assert_eq!(cost(Opcode::TLOAD), None);
+	assert_eq!(schedule.gas_sstore_set, 20000);
+	assert_eq!(schedule.call_stipend, 2300);
+}
+
+#[test]
+fn berlin_schedule_lists_warm_access_costs() {
+	let schedule = export_gas_schedule(&Config::berlin());
+
+	assert_eq!(schedule.opcodes.get(&Opcode::SLOAD.as_u8()), Some(&100));
+	assert_eq!(schedule.opcodes.get(&Opcode::CALL.as_u8()), Some(&100));
+	assert_eq!(schedule.gas_sload_cold, 2100);
+	assert_eq!(schedule.gas_account_access_cold, 2600);
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn istanbul_schedule_serializes_to_json() {
+	let schedule = export_gas_schedule(&Config::istanbul());
+	let json = serde_json::to_value(&schedule).unwrap();
+
+	assert_eq!(json["opcodes"][Opcode::SLOAD.as_u8().to_string()], 800);
+	assert_eq!(json["opcodes"][Opcode::CALL.as_u8().to_string()], 700);
+	assert_eq!(json["gas_expbyte"], 50);
+	assert_eq!(serde_json::from_value::<evm_gasometer::GasSchedule>(json).unwrap(), schedule);
+}
+
+#[test]
+fn base_opcode_cost_is_fixed_costs_only() {
+	let istanbul = Config::istanbul();
+	assert_eq!(base_opcode_cost(Opcode::ADD, &istanbul), Some(3));
+	assert_eq!(base_opcode_cost(Opcode::SLOAD, &istanbul), Some(istanbul.gas_sload));
+	assert_eq!(base_opcode_cost(Opcode::SELFBALANCE, &istanbul), Some(5));
+	assert_eq!(base_opcode_cost(Opcode::CALL, &istanbul), None);
+	assert_eq!(base_opcode_cost(Opcode::SSTORE, &istanbul), None);
+	assert_eq!(base_opcode_cost(Opcode::MLOAD, &istanbul), None);
+
+	let frontier = Config::frontier();
+	assert_eq!(base_opcode_cost(Opcode::SLOAD, &frontier), Some(frontier.gas_sload));
+	assert_eq!(base_opcode_cost(Opcode::SELFBALANCE, &frontier), None);
+
+	// Warm and cold accesses cost differently.
+	assert_eq!(base_opcode_cost(Opcode::SLOAD, &Config::berlin()), None);
+}
@@ -0,0 +1,28 @@
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+/// Address of a contract created by `CREATE`, which is
+/// `keccak256(rlp([sender, nonce]))[12:]`.
+pub fn create_address(sender: H160, nonce: U256) -> H160 {
+	let mut stream = rlp::RlpStream::new_list(2);
+	stream.append(&sender);
+	stream.append(&nonce);
+	H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
+}
+
+/// Address of a contract created by `CREATE2`, which is
+/// `keccak256(0xff ++ sender ++ salt ++ keccak256(init_code))[12:]`.
+pub fn create2_address(sender: H160, salt: H256, init_code: &[u8]) -> H160 {
+	let code_hash = H256::from_slice(Keccak256::digest(init_code).as_slice());
+	create2_address_from_code_hash(sender, salt, code_hash)
+}
+
+/// Same as `create2_address`, but with the init code hash already computed.
+pub fn create2_address_from_code_hash(sender: H160, salt: H256, code_hash: H256) -> H160 {
+	let mut hasher = Keccak256::new();
+	hasher.input(&[0xff]);
+	hasher.input(&sender[..]);
+	hasher.input(&salt[..]);
+	hasher.input(&code_hash[..]);
+	H256::from_slice(hasher.result().as_slice()).into()
+}
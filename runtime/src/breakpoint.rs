@@ -0,0 +1,47 @@
+use alloc::collections::BTreeSet;
+use crate::Opcode;
+
+/// Program counters and opcodes to pause at, for `Runtime::run_until`.
+#[derive(Clone, Debug, Default)]
+pub struct BreakpointSet {
+	positions: BTreeSet<usize>,
+	opcodes: BTreeSet<u8>,
+}
+
+impl BreakpointSet {
+	/// Create an empty breakpoint set.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Pause at the given program counter.
+	pub fn add_position(&mut self, position: usize) {
+		self.positions.insert(position);
+	}
+
+	/// Pause at every occurrence of the given opcode.
+	pub fn add_opcode(&mut self, opcode: Opcode) {
+		self.opcodes.insert(opcode.as_u8());
+	}
+
+	/// Stop pausing at the given program counter.
+	pub fn remove_position(&mut self, position: usize) {
+		self.positions.remove(&position);
+	}
+
+	/// Stop pausing at the given opcode.
+	pub fn remove_opcode(&mut self, opcode: Opcode) {
+		self.opcodes.remove(&opcode.as_u8());
+	}
+
+	/// Whether the set has no breakpoint.
+	pub fn is_empty(&self) -> bool {
+		self.positions.is_empty() && self.opcodes.is_empty()
+	}
+
+	/// Whether the runtime should pause before running the given opcode at the
+	/// given program counter.
+	pub fn contains(&self, position: usize, opcode: Opcode) -> bool {
+		self.positions.contains(&position) || self.opcodes.contains(&opcode.as_u8())
+	}
+}
@@ -1,4 +1,5 @@
 use primitive_types::{H160, U256, H256};
+use crate::Opcode;
 
 /// Create scheme.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
@@ -34,6 +35,19 @@ pub enum CallScheme {
 	StaticCall,
 }
 
+impl CallScheme {
+	/// Scheme of a call opcode, or `None` if the opcode is not a call.
+	pub fn from_opcode(opcode: Opcode) -> Option<Self> {
+		match opcode {
+			Opcode::CALL => Some(Self::Call),
+			Opcode::CALLCODE => Some(Self::CallCode),
+			Opcode::DELEGATECALL => Some(Self::DelegateCall),
+			Opcode::STATICCALL => Some(Self::StaticCall),
+			_ => None,
+		}
+	}
+}
+
 /// Context of the runtime.
 #[derive(Clone, Debug)]
 pub struct Context {
@@ -41,6 +55,34 @@ pub struct Context {
 	pub address: H160,
 	/// Caller of the EVM.
 	pub caller: H160,
+	/// Sender of the transaction, the same in every frame of the call tree,
+	/// as returned by ORIGIN.
+	pub tx_origin: H160,
 	/// Apparent value of the EVM.
 	pub apparent_value: U256,
+	/// Whether the execution is static, forbidding state modifications.
+	pub is_static: bool,
+	/// Depth of the call frame, `0` for the frame of the transaction and one
+	/// more for each nested call or create.
+	pub depth: usize,
+}
+
+/// Block the runtime executes in, read by the block opcodes in place of the
+/// handler.
+#[derive(Clone, Debug, Default)]
+pub struct BlockContext {
+	/// Block number.
+	pub number: U256,
+	/// Block timestamp.
+	pub timestamp: U256,
+	/// Block gas limit.
+	pub gas_limit: U256,
+	/// Block coinbase.
+	pub coinbase: H160,
+	/// Block difficulty.
+	pub difficulty: U256,
+	/// Block randomness (PREVRANDAO), if known.
+	pub randomness: Option<H256>,
+	/// Chain ID (EIP-1344).
+	pub chain_id: U256,
 }
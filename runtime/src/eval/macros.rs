@@ -21,8 +21,8 @@ macro_rules! pop {
 macro_rules! pop_u256 {
 	( $machine:expr, $( $x:ident ),* ) => (
 		$(
-			let $x = match $machine.machine.stack_mut().pop() {
-				Ok(value) => U256::from_big_endian(&value[..]),
+			let $x = match $machine.machine.stack_mut().pop_u256() {
+				Ok(value) => value,
 				Err(e) => return Control::Exit(e.into()),
 			};
 		)*
@@ -43,9 +43,7 @@ macro_rules! push {
 macro_rules! push_u256 {
 	( $machine:expr, $( $x:expr ),* ) => (
 		$(
-			let mut value = H256::default();
-			$x.to_big_endian(&mut value[..]);
-			match $machine.machine.stack_mut().push(value) {
+			match $machine.machine.stack_mut().push_u256($x) {
 				Ok(()) => (),
 				Err(e) => return Control::Exit(e.into()),
 			}
@@ -2,11 +2,16 @@
 mod macros;
 mod system;
 
+pub use self::system::{finish_call, finish_create};
+
+use primitive_types::U256;
 use crate::{Handler, Runtime, ExitReason, CallScheme, Opcode};
 
 pub enum Control<H: Handler> {
 	Continue,
-	CallInterrupt(H::CallInterrupt),
+	/// Call interrupt, with the scheme of the call and the offset and length
+	/// of the output memory range.
+	CallInterrupt(H::CallInterrupt, CallScheme, U256, U256),
 	CreateInterrupt(H::CreateInterrupt),
 	Exit(ExitReason)
 }
@@ -27,7 +32,7 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: Opcode, handler: &mut H) ->
 		Opcode::ADDRESS => system::address(state),
 		Opcode::BALANCE => system::balance(state, handler),
 		Opcode::SELFBALANCE => system::selfbalance(state, handler),
-		Opcode::ORIGIN => system::origin(state, handler),
+		Opcode::ORIGIN => system::origin(state),
 		Opcode::CALLER => system::caller(state),
 		Opcode::CALLVALUE => system::callvalue(state),
 		Opcode::GASPRICE => system::gasprice(state, handler),
@@ -44,6 +49,8 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: Opcode, handler: &mut H) ->
 		Opcode::GASLIMIT => system::gaslimit(state, handler),
 		Opcode::SLOAD => system::sload(state, handler),
 		Opcode::SSTORE => system::sstore(state, handler),
+		Opcode::TLOAD => system::tload(state, handler),
+		Opcode::TSTORE => system::tstore(state, handler),
 		Opcode::GAS => system::gas(state, handler),
 		Opcode::LOG0 => system::log(state, 0, handler),
 		Opcode::LOG1 => system::log(state, 1, handler),
@@ -57,7 +64,7 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: Opcode, handler: &mut H) ->
 		Opcode::CALLCODE => system::call(state, CallScheme::CallCode, handler),
 		Opcode::DELEGATECALL => system::call(state, CallScheme::DelegateCall, handler),
 		Opcode::STATICCALL => system::call(state, CallScheme::StaticCall, handler),
-		Opcode::CHAINID => system::chainid(state, handler),
+		Opcode::CHAINID if state.config.has_chain_id => system::chainid(state, handler),
 		_ => handle_other(state, opcode, handler),
 	}
 }
@@ -1,9 +1,10 @@
 use core::cmp::min;
 use alloc::vec::Vec;
-use primitive_types::{H256, U256};
+use primitive_types::{H160, H256, U256};
 use sha3::{Keccak256, Digest};
 use crate::{Runtime, ExitError, Handler, Capture, Transfer, ExitReason,
-			CreateScheme, CallScheme, Context, ExitSucceed, ExitFatal};
+			CreateScheme, CallScheme, Context, ExitSucceed, ExitFatal, Log,
+			ExternalOperation};
 use super::Control;
 
 pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
@@ -26,7 +27,11 @@ pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 }
 
 pub fn chainid<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.chain_id());
+	let chain_id = match &runtime.block {
+		Some(block) => block.chain_id,
+		None => handler.chain_id(),
+	};
+	push_u256!(runtime, chain_id);
 
 	Control::Continue
 }
@@ -38,8 +43,9 @@ pub fn address<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	Control::Continue
 }
 
-pub fn balance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+pub fn balance<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, address);
+	handler.record_external_operation(ExternalOperation::AccountAccess(address.into()));
 	push_u256!(runtime, handler.balance(address.into()));
 
 	Control::Continue
@@ -51,8 +57,8 @@ pub fn selfbalance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H>
 	Control::Continue
 }
 
-pub fn origin<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	let ret = H256::from(handler.origin());
+pub fn origin<H: Handler>(runtime: &mut Runtime) -> Control<H> {
+	let ret = H256::from(runtime.context.tx_origin);
 	push!(runtime, ret);
 
 	Control::Continue
@@ -81,22 +87,25 @@ pub fn gasprice<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	Control::Continue
 }
 
-pub fn extcodesize<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+pub fn extcodesize<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, address);
+	handler.record_external_operation(ExternalOperation::AccountAccess(address.into()));
 	push_u256!(runtime, handler.code_size(address.into()));
 
 	Control::Continue
 }
 
-pub fn extcodehash<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+pub fn extcodehash<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, address);
+	handler.record_external_operation(ExternalOperation::AccountAccess(address.into()));
 	push!(runtime, handler.code_hash(address.into()));
 
 	Control::Continue
 }
 
-pub fn extcodecopy<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+pub fn extcodecopy<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, address);
+	handler.record_external_operation(ExternalOperation::AccountAccess(address.into()));
 	pop_u256!(runtime, memory_offset, code_offset, len);
 
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(memory_offset, len));
@@ -120,6 +129,9 @@ pub fn returndatasize<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	Control::Continue
 }
 
+/// Copy the return data of the last sub-call into memory. A range past the
+/// end of the return data fails the frame with `OutOfOffset` rather than
+/// being padded with zeros (EIP-211), even if it is empty.
 pub fn returndatacopy<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	pop_u256!(runtime, memory_offset, data_offset, len);
 
@@ -137,53 +149,122 @@ pub fn returndatacopy<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	}
 }
 
+/// Push the hash of the block of the given number if it is one of the 256
+/// blocks before the current one, and zero otherwise, without asking the
+/// handler for blocks out of that window.
 pub fn blockhash<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	pop_u256!(runtime, number);
-	push!(runtime, handler.block_hash(number));
+
+	let current = match &runtime.block {
+		Some(block) => block.number,
+		None => handler.block_number(),
+	};
+	if number < current && current - number <= U256::from(256) {
+		push!(runtime, handler.block_hash(number));
+	} else {
+		push!(runtime, H256::default());
+	}
 
 	Control::Continue
 }
 
 pub fn coinbase<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push!(runtime, handler.block_coinbase().into());
+	let coinbase = match &runtime.block {
+		Some(block) => block.coinbase,
+		None => handler.block_coinbase(),
+	};
+	push!(runtime, coinbase.into());
 	Control::Continue
 }
 
 pub fn timestamp<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.block_timestamp());
+	let timestamp = match &runtime.block {
+		Some(block) => block.timestamp,
+		None => handler.block_timestamp(),
+	};
+	push_u256!(runtime, timestamp);
 	Control::Continue
 }
 
 pub fn number<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.block_number());
+	let number = match &runtime.block {
+		Some(block) => block.number,
+		None => handler.block_number(),
+	};
+	push_u256!(runtime, number);
 	Control::Continue
 }
 
 pub fn difficulty<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.block_difficulty());
+	let (randomness, difficulty) = match &runtime.block {
+		Some(block) => (block.randomness, block.difficulty),
+		None => (handler.block_randomness(), handler.block_difficulty()),
+	};
+
+	if runtime.config.has_prevrandao {
+		if let Some(randomness) = randomness {
+			push!(runtime, randomness);
+			return Control::Continue
+		}
+	}
+
+	push_u256!(runtime, difficulty);
 	Control::Continue
 }
 
 pub fn gaslimit<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.block_gas_limit());
+	let gas_limit = match &runtime.block {
+		Some(block) => block.gas_limit,
+		None => handler.block_gas_limit(),
+	};
+	push_u256!(runtime, gas_limit);
 	Control::Continue
 }
 
-pub fn sload<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+pub fn sload<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, index);
+	handler.record_external_operation(
+		ExternalOperation::StorageAccess(runtime.context.address, index)
+	);
 	push!(runtime, handler.storage(runtime.context.address, index));
 
 	Control::Continue
 }
 
 pub fn sstore<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	if runtime.context.is_static {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
 	pop!(runtime, index, value);
+	handler.record_external_operation(
+		ExternalOperation::StorageAccess(runtime.context.address, index)
+	);
 	match handler.set_storage(runtime.context.address, index, value) {
 		Ok(()) => Control::Continue,
 		Err(e) => Control::Exit(e.into()),
 	}
 }
 
+pub fn tload<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	pop!(runtime, index);
+	push!(runtime, handler.transient_storage(runtime.context.address, index));
+
+	Control::Continue
+}
+
+pub fn tstore<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	if runtime.context.is_static {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
+	pop!(runtime, index, value);
+	match handler.set_transient_storage(runtime.context.address, index, value) {
+		Ok(()) => Control::Continue,
+		Err(e) => Control::Exit(e.into()),
+	}
+}
+
 pub fn gas<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	push_u256!(runtime, handler.gas_left());
 
@@ -191,6 +272,10 @@ pub fn gas<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 }
 
 pub fn log<H: Handler>(runtime: &mut Runtime, n: u8, handler: &mut H) -> Control<H> {
+	if runtime.context.is_static {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
 	pop_u256!(runtime, offset, len);
 
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(offset, len));
@@ -211,16 +296,41 @@ pub fn log<H: Handler>(runtime: &mut Runtime, n: u8, handler: &mut H) -> Control
 		}
 	}
 
+	let log = runtime.logs.as_ref().map(|_| Log {
+		address: runtime.context.address,
+		topics: topics.clone(),
+		data: data.clone(),
+	});
+
 	match handler.log(runtime.context.address, topics, data) {
-		Ok(()) => Control::Continue,
+		Ok(()) => {
+			if let (Some(logs), Some(log)) = (runtime.logs.as_mut(), log) {
+				logs.push(log);
+			}
+			Control::Continue
+		},
 		Err(e) => Control::Exit(e.into()),
 	}
 }
 
 pub fn suicide<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	if runtime.context.is_static {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
 	pop!(runtime, target);
+	handler.record_external_operation(ExternalOperation::AccountAccess(target.into()));
 
-	match handler.mark_delete(runtime.context.address, target.into()) {
+	let address = runtime.context.address;
+	let result = if runtime.config.suicide_only_if_created_same_tx &&
+		!handler.created_in_current_transaction(address)
+	{
+		handler.transfer_balance(address, target.into())
+	} else {
+		handler.mark_delete(address, target.into())
+	};
+
+	match result {
 		Ok(()) => (),
 		Err(e) => return Control::Exit(e.into()),
 	}
@@ -233,10 +343,20 @@ pub fn create<H: Handler>(
 	is_create2: bool,
 	handler: &mut H,
 ) -> Control<H> {
+	if runtime.context.is_static {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
 	runtime.return_data_buffer = Vec::new();
 
 	pop_u256!(runtime, value, code_offset, len);
 
+	if let Some(limit) = runtime.config.max_initcode_size {
+		if len > U256::from(limit) {
+			return Control::Exit(ExitError::InitCodeLimit.into())
+		}
+	}
+
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(code_offset, len));
 	let code = if len == U256::zero() {
 		Vec::new()
@@ -263,26 +383,9 @@ pub fn create<H: Handler>(
 
 	match handler.create(runtime.context.address, scheme, value, code, None) {
 		Capture::Exit((reason, address, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
-
-			match reason {
-				ExitReason::Succeed(_) => {
-					push!(runtime, create_address.into());
-					Control::Continue
-				},
-				ExitReason::Revert(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push!(runtime, H256::default());
-					Control::Exit(e.into())
-				},
+			match finish_create(runtime, reason, address, return_data) {
+				Ok(()) => Control::Continue,
+				Err(e) => Control::Exit(e),
 			}
 		},
 		Capture::Trap(interrupt) => {
@@ -317,6 +420,10 @@ pub fn call<'config, H: Handler>(
 		},
 	};
 
+	if scheme == CallScheme::Call && runtime.context.is_static && value != U256::zero() {
+		return Control::Exit(ExitError::StaticModeViolation.into())
+	}
+
 	pop_u256!(runtime, in_offset, in_len, out_offset, out_len);
 
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(in_offset, in_len));
@@ -335,17 +442,26 @@ pub fn call<'config, H: Handler>(
 		CallScheme::Call | CallScheme::StaticCall => Context {
 			address: to.into(),
 			caller: runtime.context.address,
+			tx_origin: runtime.context.tx_origin,
 			apparent_value: value,
+			is_static: scheme == CallScheme::StaticCall || runtime.context.is_static,
+			depth: runtime.context.depth + 1,
 		},
 		CallScheme::CallCode => Context {
 			address: runtime.context.address,
 			caller: runtime.context.address,
+			tx_origin: runtime.context.tx_origin,
 			apparent_value: value,
+			is_static: runtime.context.is_static,
+			depth: runtime.context.depth + 1,
 		},
 		CallScheme::DelegateCall => Context {
 			address: runtime.context.address,
 			caller: runtime.context.caller,
+			tx_origin: runtime.context.tx_origin,
 			apparent_value: runtime.context.apparent_value,
+			is_static: runtime.context.is_static,
+			depth: runtime.context.depth + 1,
 		},
 	};
 
@@ -365,56 +481,100 @@ pub fn call<'config, H: Handler>(
 		None
 	};
 
-	match handler.call(to.into(), transfer, input, gas, scheme == CallScheme::StaticCall, context) {
+	handler.record_external_operation(ExternalOperation::AccountAccess(to.into()));
+	match handler.call(to.into(), transfer, input, gas, scheme, context) {
 		Capture::Exit((reason, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
-
-			match reason {
-				ExitReason::Succeed(_) => {
-					match runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					) {
-						Ok(()) => {
-							push_u256!(runtime, U256::one());
-							Control::Continue
-						},
-						Err(_) => {
-							push_u256!(runtime, U256::zero());
-							Control::Continue
-						},
-					}
-				},
-				ExitReason::Revert(_) => {
-					push_u256!(runtime, U256::zero());
-
-					let _ = runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					);
-
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Exit(e.into())
-				},
+			match finish_call(runtime, out_offset, out_len, reason, return_data) {
+				Ok(()) => Control::Continue,
+				Err(e) => Control::Exit(e),
 			}
 		},
 		Capture::Trap(interrupt) => {
 			push!(runtime, H256::default());
-			Control::CallInterrupt(interrupt)
+			Control::CallInterrupt(interrupt, scheme, out_offset, out_len)
+		},
+	}
+}
+
+fn check_return_data_size(runtime: &Runtime, return_data: &[u8]) -> Result<(), ExitError> {
+	match runtime.config.max_return_data_size {
+		Some(max) if return_data.len() > max => Err(ExitError::OutOfGas),
+		_ => Ok(()),
+	}
+}
+
+/// Feed the outcome of a create into the runtime: set the return data buffer
+/// and push the created address, or zero if it did not succeed.
+pub fn finish_create(
+	runtime: &mut Runtime,
+	reason: ExitReason,
+	address: Option<H160>,
+	return_data: Vec<u8>,
+) -> Result<(), ExitReason> {
+	check_return_data_size(runtime, &return_data)?;
+	runtime.return_data_buffer = return_data;
+	let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
+
+	match reason {
+		ExitReason::Succeed(_) => {
+			runtime.machine.stack_mut().push(create_address)?;
+			Ok(())
+		},
+		ExitReason::Revert(_) | ExitReason::Error(_) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Ok(())
+		},
+		ExitReason::Fatal(e) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Err(e.into())
+		},
+	}
+}
+
+/// Feed the outcome of a call into the runtime: set the return data buffer,
+/// copy it to the output memory range on success or revert (EIP-211), and
+/// push one on success or zero otherwise.
+pub fn finish_call(
+	runtime: &mut Runtime,
+	out_offset: U256,
+	out_len: U256,
+	reason: ExitReason,
+	return_data: Vec<u8>,
+) -> Result<(), ExitReason> {
+	check_return_data_size(runtime, &return_data)?;
+	runtime.return_data_buffer = return_data;
+	let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
+
+	match reason {
+		ExitReason::Succeed(_) => {
+			let copied = runtime.machine.memory_mut().copy_large(
+				out_offset,
+				U256::zero(),
+				target_len,
+				&runtime.return_data_buffer[..],
+			);
+			runtime.machine.stack_mut().push(H256::from_low_u64_be(copied.is_ok() as u64))?;
+			Ok(())
+		},
+		ExitReason::Revert(_) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+
+			let _ = runtime.machine.memory_mut().copy_large(
+				out_offset,
+				U256::zero(),
+				target_len,
+				&runtime.return_data_buffer[..],
+			);
+
+			Ok(())
+		},
+		ExitReason::Error(_) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Ok(())
+		},
+		ExitReason::Fatal(e) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Err(e.into())
 		},
 	}
 }
@@ -0,0 +1,52 @@
+use alloc::vec::Vec;
+use crate::Config;
+
+/// Configs to use by block number, each active from its activation block
+/// until the next one.
+#[derive(Clone, Debug)]
+pub struct ForkSchedule {
+	/// Activation blocks with their config, in ascending order of block. The
+	/// first one is always activated at block 0.
+	forks: Vec<(u64, Config)>,
+}
+
+impl ForkSchedule {
+	/// Schedule using the given config from the genesis block on.
+	pub fn new(genesis: Config) -> Self {
+		Self {
+			forks: alloc::vec![(0, genesis)],
+		}
+	}
+
+	/// Add a config activated at the given block, replacing the one activated
+	/// at the same block if any. Forks can be added in any order.
+	pub fn with_fork(mut self, block: u64, config: Config) -> Self {
+		match self.forks.binary_search_by_key(&block, |(activation, _)| *activation) {
+			Ok(index) => self.forks[index].1 = config,
+			Err(index) => self.forks.insert(index, (block, config)),
+		}
+		self
+	}
+
+	/// Ethereum mainnet schedule. Forks without a preset keep the config of
+	/// the previous one, so blocks before Istanbul use the Frontier config.
+	pub fn mainnet() -> Self {
+		Self::new(Config::frontier())
+			.with_fork(9_069_000, Config::istanbul())
+			.with_fork(12_244_000, Config::berlin())
+			.with_fork(12_965_000, Config::london())
+			.with_fork(15_537_394, Config::paris())
+	}
+
+	/// Config active at the given block.
+	pub fn config_for(&self, block: u64) -> &Config {
+		let index = self.forks.partition_point(|(activation, _)| *activation <= block);
+		// The genesis fork is activated at block 0, so `index` is at least 1.
+		&self.forks[index - 1].1
+	}
+
+	/// Activation blocks with their config, in ascending order of block.
+	pub fn forks(&self) -> &[(u64, Config)] {
+		&self.forks
+	}
+}
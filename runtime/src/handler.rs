@@ -1,7 +1,7 @@
 use alloc::vec::Vec;
 use primitive_types::{H160, H256, U256};
-use crate::{Capture, Stack, ExitError, Opcode,
-			CreateScheme, Context, Machine, ExitReason};
+use crate::{Capture, Stack, ExitError, Opcode, memory_expansion_cost,
+			CallScheme, CreateScheme, Context, Machine, ExitReason};
 
 /// Transfer from source to target, with given value.
 #[derive(Clone, Debug)]
@@ -14,6 +14,30 @@ pub struct Transfer {
 	pub value: U256,
 }
 
+/// Log emitted by `LOG0` to `LOG4`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Log {
+	/// Address of the emitting contract.
+	pub address: H160,
+	/// Log topics.
+	pub topics: Vec<H256>,
+	/// Log data.
+	pub data: Vec<u8>,
+}
+
+/// Operation of the machine on the state or on a precompile, recorded through
+/// `Handler::record_external_operation` as it is performed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExternalOperation {
+	/// Access of an account by `BALANCE`, `EXTCODESIZE`, `EXTCODEHASH`,
+	/// `EXTCODECOPY`, or as the target of a call or of `SUICIDE`.
+	AccountAccess(H160),
+	/// Access of a storage slot of an account by `SLOAD` or `SSTORE`.
+	StorageAccess(H160, H256),
+	/// Invocation of the precompile at the given address.
+	PrecompileInvocation(H160),
+}
+
 /// EVM context handler.
 pub trait Handler {
 	/// Type of `CREATE` interrupt.
@@ -35,11 +59,26 @@ pub trait Handler {
 	fn code(&self, address: H160) -> Vec<u8>;
 	/// Get storage value of address at index.
 	fn storage(&self, address: H160, index: H256) -> H256;
+	/// Get transient storage value of address at index (EIP-1153). Zero
+	/// unless the handler keeps transient storage.
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
 	/// Get original storage value of address at index.
 	fn original_storage(&self, address: H160, index: H256) -> H256;
 
 	/// Get the gas left value.
 	fn gas_left(&self) -> U256;
+	/// Get the gas spent so far, before applying any refund. Zero unless the
+	/// handler meters gas.
+	fn gas_spent(&self) -> U256 {
+		U256::zero()
+	}
+	/// Get the gas refund accumulated so far. Zero unless the handler meters
+	/// gas.
+	fn gas_refund(&self) -> i64 {
+		0
+	}
 	/// Get the gas price value.
 	fn gas_price(&self) -> U256;
 	/// Get execution origin.
@@ -54,6 +93,10 @@ pub trait Handler {
 	fn block_timestamp(&self) -> U256;
 	/// Get environmental block difficulty.
 	fn block_difficulty(&self) -> U256;
+	/// Get environmental block randomness (PREVRANDAO), if known.
+	fn block_randomness(&self) -> Option<H256> {
+		None
+	}
 	/// Get environmental gas limit.
 	fn block_gas_limit(&self) -> U256;
 	/// Get environmental chain ID.
@@ -61,15 +104,46 @@ pub trait Handler {
 
 	/// Check whether an address exists.
 	fn exists(&self, address: H160) -> bool;
+	/// Check whether an address is a contract, that is, has non-empty code.
+	fn is_contract(&self, address: H160) -> bool {
+		self.code_size(address) != U256::zero()
+	}
 	/// Check whether an address has already been deleted.
 	fn deleted(&self, address: H160) -> bool;
+	/// Check whether an address, or a storage slot of it if `index` is given,
+	/// has not been accessed yet in the current transaction (EIP-2929).
+	/// Handlers not tracking accesses charge every access as cold.
+	fn is_cold(&self, _address: H160, _index: Option<H256>) -> bool {
+		true
+	}
+	/// Check whether an address was created in the current transaction.
+	fn created_in_current_transaction(&self, _address: H160) -> bool {
+		false
+	}
+	/// Addresses marked for self-destruct in the current transaction, to be
+	/// deleted when it is finalized.
+	fn pending_selfdestructs(&self) -> Vec<H160> {
+		Vec::new()
+	}
 
 	/// Set storage value of address at index.
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError>;
+	/// Set transient storage value of address at index (EIP-1153). Does
+	/// nothing unless the handler keeps transient storage.
+	fn set_transient_storage(&mut self, _address: H160, _index: H256, _value: H256) -> Result<(), ExitError> {
+		Ok(())
+	}
+	/// Clear all transient storage, as done at the end of a transaction.
+	fn clear_transient_storage(&mut self) { }
 	/// Create a log owned by address with given topics and data.
 	fn log(&mut self, address: H160, topcis: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError>;
 	/// Mark an address to be deleted, with funds transferred to target.
 	fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError>;
+	/// Transfer all funds of an address to target, without deleting it. Fails
+	/// unless the handler supports it.
+	fn transfer_balance(&mut self, _address: H160, _target: H160) -> Result<(), ExitError> {
+		Err(ExitError::Other("balance transfer not supported".into()))
+	}
 	/// Invoke a create operation.
 	fn create(
 		&mut self,
@@ -86,14 +160,17 @@ pub trait Handler {
 	) -> Result<(), ExitError> {
 		Ok(())
 	}
-	/// Invoke a call operation.
+	/// Invoke a call operation, made with the given scheme. The call is
+	/// static if the scheme is `STATICCALL`. The context is the one of the
+	/// callee: `DELEGATECALL` keeps the address, caller and apparent value of
+	/// the caller frame, and `CALLCODE` only its address.
 	fn call(
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
 		input: Vec<u8>,
 		target_gas: Option<u64>,
-		is_static: bool,
+		scheme: CallScheme,
 		context: Context,
 	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt>;
 	/// Feed in call feedback.
@@ -104,6 +181,24 @@ pub trait Handler {
 		Ok(())
 	}
 
+	/// Called before each opcode is pre-validated and run, with a reference
+	/// of the machine about to run it. Nothing is copied for the call.
+	fn step(
+		&mut self,
+		_context: &Context,
+		_opcode: Opcode,
+		_machine: &Machine
+	) { }
+	/// Record an external operation. Account and storage accesses are
+	/// recorded by the runtime, precompile invocations by the handler running
+	/// them.
+	fn record_external_operation(&mut self, _op: ExternalOperation) { }
+	/// Gas cost of expanding the memory of a frame from `current_words` to
+	/// `new_words` words, charged by the handler metering the opcodes in
+	/// `pre_validate`. Chains pricing memory differently can override it.
+	fn memory_expansion_cost(&self, current_words: usize, new_words: usize) -> u64 {
+		memory_expansion_cost(current_words, new_words)
+	}
 	/// Pre-validation step for the runtime.
 	fn pre_validate(
 		&mut self,
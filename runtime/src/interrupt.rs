@@ -1,4 +1,8 @@
-use crate::{Runtime, Handler, ExitFatal};
+use core::mem::ManuallyDrop;
+use alloc::vec::Vec;
+use primitive_types::{H160, U256};
+use crate::{Runtime, Handler, CallScheme, ExitFatal, ExitReason};
+use crate::eval::{finish_call, finish_create};
 
 /// Interrupt resolution.
 pub enum Resolve<'a, 'config, H: Handler> {
@@ -17,6 +21,21 @@ impl<'a, 'config> ResolveCreate<'a, 'config> {
 	pub(crate) fn new(runtime: &'a mut Runtime<'config>) -> Self {
 		Self { runtime }
 	}
+
+	/// Resume the runtime with the outcome of the interrupted create, as if
+	/// the handler had returned it. The created address is pushed on success,
+	/// and zero otherwise. `return_data` becomes the return data buffer, and
+	/// should be the revert data if the create reverted, or empty.
+	pub fn resume(self, reason: ExitReason, address: Option<H160>, return_data: Vec<u8>) {
+		let mut this = ManuallyDrop::new(self);
+		let runtime = &mut *this.runtime;
+
+		// Drop the placeholder pushed when the create was interrupted.
+		let _ = runtime.machine.stack_mut().pop();
+		if let Err(e) = finish_create(runtime, reason, address, return_data) {
+			runtime.exit(e);
+		}
+	}
 }
 
 impl<'a, 'config> Drop for ResolveCreate<'a, 'config> {
@@ -29,11 +48,40 @@ impl<'a, 'config> Drop for ResolveCreate<'a, 'config> {
 /// Call interrupt resolution.
 pub struct ResolveCall<'a, 'config> {
 	runtime: &'a mut Runtime<'config>,
+	scheme: CallScheme,
+	out_offset: U256,
+	out_len: U256,
 }
 
 impl<'a, 'config> ResolveCall<'a, 'config> {
-	pub(crate) fn new(runtime: &'a mut Runtime<'config>) -> Self {
-		Self { runtime }
+	pub(crate) fn new(
+		runtime: &'a mut Runtime<'config>,
+		scheme: CallScheme,
+		out_offset: U256,
+		out_len: U256,
+	) -> Self {
+		Self { runtime, scheme, out_offset, out_len }
+	}
+
+	/// Scheme of the interrupted call.
+	pub fn scheme(&self) -> CallScheme {
+		self.scheme
+	}
+
+	/// Resume the runtime with the outcome of the interrupted call, as if the
+	/// handler had returned it. One is pushed on success and zero otherwise.
+	/// `return_data` becomes the return data buffer and, if the call succeeded
+	/// or reverted, is copied to the output memory range of the call.
+	pub fn resume(self, reason: ExitReason, return_data: Vec<u8>) {
+		let mut this = ManuallyDrop::new(self);
+		let (out_offset, out_len) = (this.out_offset, this.out_len);
+		let runtime = &mut *this.runtime;
+
+		// Drop the placeholder pushed when the call was interrupted.
+		let _ = runtime.machine.stack_mut().pop();
+		if let Err(e) = finish_call(runtime, out_offset, out_len, reason, return_data) {
+			runtime.exit(e);
+		}
 	}
 }
 
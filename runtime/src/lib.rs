@@ -11,19 +11,36 @@ mod eval;
 mod context;
 mod interrupt;
 mod handler;
+mod revert;
+mod address;
+mod recorder;
+mod fork;
+mod breakpoint;
 
 pub use evm_core::*;
 
-pub use crate::context::{CreateScheme, CallScheme, Context};
+pub use crate::context::{CreateScheme, CallScheme, Context, BlockContext};
 pub use crate::interrupt::{Resolve, ResolveCall, ResolveCreate};
-pub use crate::handler::{Transfer, Handler};
+pub use crate::handler::{Transfer, Handler, Log, ExternalOperation};
+pub use crate::recorder::AccessListRecorder;
+pub use crate::fork::ForkSchedule;
+pub use crate::breakpoint::BreakpointSet;
+#[cfg(feature = "hex")]
+pub use hex::FromHexError;
+pub use crate::address::{create_address, create2_address, create2_address_from_code_hash};
+pub use crate::revert::{
+	decode_revert, decode_revert_reason, RevertReason, ERROR_SELECTOR, PANIC_SELECTOR,
+};
 
 use alloc::vec::Vec;
 use alloc::rc::Rc;
+use core::cmp::{min, max};
+use primitive_types::{H160, H256, U256};
 
 macro_rules! step {
 	( $self:expr, $handler:expr, $return:tt $($err:path)?; $($ok:path)? ) => ({
 		if let Some((opcode, stack)) = $self.machine.inspect() {
+			$handler.step(&$self.context, opcode, &$self.machine);
 			match $handler.pre_validate(&$self.context, opcode, stack) {
 				Ok(()) => (),
 				Err(e) => {
@@ -41,6 +58,7 @@ macro_rules! step {
 			},
 		}
 
+		$self.breakpoint = None;
 		match $self.machine.step() {
 			Ok(()) => $($ok)?(()),
 			Err(Capture::Exit(e)) => {
@@ -51,8 +69,8 @@ macro_rules! step {
 			Err(Capture::Trap(opcode)) => {
 				match eval::eval($self, opcode, $handler) {
 					eval::Control::Continue => $($ok)?(()),
-					eval::Control::CallInterrupt(interrupt) => {
-						let resolve = ResolveCall::new($self);
+					eval::Control::CallInterrupt(interrupt, scheme, out_offset, out_len) => {
+						let resolve = ResolveCall::new($self, scheme, out_offset, out_len);
 						#[allow(unused_parens)]
 						$return $($err)*(Capture::Trap(Resolve::Call(interrupt, resolve)))
 					},
@@ -81,7 +99,10 @@ pub struct Runtime<'config> {
 	status: Result<(), ExitReason>,
 	return_data_buffer: Vec<u8>,
 	context: Context,
-	_config: &'config Config,
+	block: Option<BlockContext>,
+	logs: Option<Vec<Log>>,
+	breakpoint: Option<usize>,
+	config: &'config Config,
 }
 
 impl<'config> Runtime<'config> {
@@ -97,15 +118,87 @@ impl<'config> Runtime<'config> {
 			status: Ok(()),
 			return_data_buffer: Vec::new(),
 			context,
-			_config: config,
+			block: None,
+			logs: None,
+			breakpoint: None,
+			config,
 		}
 	}
 
+	/// Create a new runtime with given code and data, executing in the given
+	/// block. `NUMBER`, `TIMESTAMP`, `GASLIMIT`, `COINBASE`, `DIFFICULTY` and
+	/// `CHAINID` read the block instead of the handler. Sub-calls run by the
+	/// handler read the handler.
+	pub fn new_with_block(
+		code: Rc<Vec<u8>>,
+		data: Rc<Vec<u8>>,
+		context: Context,
+		block: BlockContext,
+		config: &'config Config,
+	) -> Self {
+		Self {
+			block: Some(block),
+			..Self::new(code, data, context, config)
+		}
+	}
+
+	/// Create a new runtime with the given hex encoded code, with or without
+	/// a `0x` prefix. Fails if the code is not valid hex, or of odd length.
+	#[cfg(feature = "hex")]
+	pub fn from_hex_code(
+		code_hex: &str,
+		data: &[u8],
+		context: Context,
+		config: &'config Config,
+	) -> Result<Self, FromHexError> {
+		let code_hex = code_hex.strip_prefix("0x").unwrap_or(code_hex);
+		let code = hex::decode(code_hex)?;
+		Ok(Self::new(Rc::new(code), Rc::new(data.to_vec()), context, config))
+	}
+
+	/// Keep the logs emitted from now on in a buffer of the runtime, in
+	/// addition to passing them to the handler.
+	pub fn enable_log_buffer(&mut self) {
+		if self.logs.is_none() {
+			self.logs = Some(Vec::new());
+		}
+	}
+
+	/// Logs emitted since the log buffer was enabled. Empty if it is not.
+	pub fn logs(&self) -> &[Log] {
+		self.logs.as_deref().unwrap_or(&[])
+	}
+
 	/// Get a reference to the machine.
 	pub fn machine(&self) -> &Machine {
 		&self.machine
 	}
 
+	/// Get a mutable reference to the machine.
+	pub fn machine_mut(&mut self) -> &mut Machine {
+		&mut self.machine
+	}
+
+	/// Get a reference to the execution config.
+	pub fn config(&self) -> &'config Config {
+		self.config
+	}
+
+	/// Exit the runtime with the given reason. Further step will return it.
+	fn exit(&mut self, reason: ExitReason) {
+		self.machine.exit(reason.clone());
+		self.status = Err(reason);
+	}
+
+	/// Gas used by the execution, with the refund applied. Refunds are only
+	/// credited here, so execution itself is charged the gross amount.
+	pub fn gas_used<H: Handler>(&self, handler: &H) -> U256 {
+		let spent = handler.gas_spent();
+		let refund = U256::from(max(handler.gas_refund(), 0) as u64);
+
+		spent - min(refund, spent / U256::from(self.config.max_refund_quotient))
+	}
+
 	/// Step the runtime.
 	pub fn step<'a, H: Handler>(
 		&'a mut self,
@@ -114,6 +207,52 @@ impl<'config> Runtime<'config> {
 		step!(self, handler, return Err; Ok)
 	}
 
+	/// Loop stepping the runtime until it stops, as a read-only call. The
+	/// call and all its sub-calls run as static, so any state write fails
+	/// with `StaticModeViolation`, even at the top level.
+	pub fn run_read_only<'a, H: Handler>(
+		&'a mut self,
+		handler: &mut H,
+	) -> Capture<ExitReason, Resolve<'a, 'config, H>> {
+		self.context.is_static = true;
+		self.run(handler)
+	}
+
+	/// Step the runtime at most `n` times, returning the number of steps
+	/// executed. Stops early if the runtime exits or traps.
+	pub fn step_n<'a, H: Handler>(
+		&'a mut self,
+		handler: &mut H,
+		n: usize,
+	) -> Result<usize, Capture<ExitReason, Resolve<'a, 'config, H>>> {
+		for _ in 0..n {
+			step!(self, handler, return Err;)
+		}
+		Ok(n)
+	}
+
+	/// Loop stepping the runtime until it stops or reaches a breakpoint,
+	/// returning the program counter of the breakpoint, whose opcode is not
+	/// run yet. The current position is checked before the first step, unless
+	/// it is the breakpoint just returned, so calling it again resumes past
+	/// the breakpoint reached.
+	pub fn run_until<'a, H: Handler>(
+		&'a mut self,
+		handler: &mut H,
+		breakpoints: &BreakpointSet,
+	) -> Result<usize, Capture<ExitReason, Resolve<'a, 'config, H>>> {
+		loop {
+			if let (Ok(position), Some((opcode, _))) = (self.machine.position(), self.machine.inspect()) {
+				if self.breakpoint != Some(position) && breakpoints.contains(position, opcode) {
+					self.breakpoint = Some(position);
+					return Ok(position)
+				}
+			}
+
+			step!(self, handler, return Err;);
+		}
+	}
+
 	/// Loop stepping the runtime until it stops.
 	pub fn run<'a, H: Handler>(
 		&'a mut self,
@@ -125,6 +264,9 @@ impl<'config> Runtime<'config> {
 	}
 }
 
+/// Gas paid for every word of the init code of creates (EIP-3860).
+pub const INIT_CODE_WORD_COST: u64 = 2;
+
 /// Runtime configuration.
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -138,18 +280,38 @@ pub struct Config {
 	pub gas_sstore_reset: u64,
 	/// Gas paid for sstore refund.
 	pub refund_sstore_clears: i64,
+	/// Refunds are capped to the used gas divided by this quotient.
+	pub max_refund_quotient: u64,
+	/// Gas refunded for SUICIDE, removed by EIP-3529.
+	pub refund_suicide: i64,
 	/// Gas paid for BALANCE opcode.
 	pub gas_balance: u64,
 	/// Gas paid for SLOAD opcode.
 	pub gas_sload: u64,
+	/// Gas paid for SLOAD opcode of a cold slot (EIP-2929).
+	pub gas_sload_cold: u64,
+	/// Gas paid for accessing a warm address or storage slot (EIP-2929).
+	pub gas_storage_read_warm: u64,
+	/// Gas paid for accessing a cold address (EIP-2929).
+	pub gas_account_access_cold: u64,
 	/// Gas paid for SUICIDE opcode.
 	pub gas_suicide: u64,
 	/// Gas paid for SUICIDE opcode when it hits a new account.
 	pub gas_suicide_new_account: u64,
 	/// Gas paid for CALL opcode.
 	pub gas_call: u64,
+	/// Gas paid for CALL opcode when it creates the target account, that is,
+	/// when the target does not exist, or is empty and transferred value
+	/// unless `empty_considered_exists`.
+	pub gas_call_new_account: u64,
 	/// Gas paid for EXP opcode for every byte.
 	pub gas_expbyte: u64,
+	/// Gas paid for LOG opcodes.
+	pub gas_log: u64,
+	/// Gas paid for LOG opcodes for every topic.
+	pub gas_log_topic: u64,
+	/// Gas paid for LOG opcodes for every byte of data.
+	pub gas_log_data: u64,
 	/// Gas paid for a contract creation transaction.
 	pub gas_transaction_create: u64,
 	/// Gas paid for a message call transaction.
@@ -158,10 +320,23 @@ pub struct Config {
 	pub gas_transaction_zero_data: u64,
 	/// Gas paid for non-zero data in a transaction.
 	pub gas_transaction_non_zero_data: u64,
-	/// EIP-1283.
+	/// Floor gas paid per calldata token in a transaction.
+	pub gas_transaction_floor_per_token: u64,
+	/// Gas paid for every address of the access list of a transaction
+	/// (EIP-2930).
+	pub gas_access_list_address: u64,
+	/// Gas paid for every storage key of the access list of a transaction
+	/// (EIP-2930).
+	pub gas_access_list_storage_key: u64,
+	/// EIP-1283. Net gas metering of SSTORE, as EIP-2200 with
+	/// `sstore_revert_under_stipend`.
 	pub sstore_gas_metering: bool,
-	/// EIP-1706.
+	/// EIP-1706. Whether SSTORE fails when the gas left does not exceed the
+	/// call stipend.
 	pub sstore_revert_under_stipend: bool,
+	/// EIP-2929. Whether accesses to cold addresses and storage slots cost
+	/// more than warm ones.
+	pub increase_state_access_gas: bool,
 	/// Whether to throw out of gas error when
 	/// CALL/CALLCODE/DELEGATECALL requires more than maximum amount
 	/// of gas.
@@ -172,14 +347,35 @@ pub struct Config {
 	pub empty_considered_exists: bool,
 	/// Whether create transactions and create opcode increases nonce by one.
 	pub create_increase_nonce: bool,
+	/// Whether transactions check that the sender balance covers the value
+	/// plus the gas limit at the gas price before executing.
+	pub validate_upfront_cost: bool,
+	/// EIP-3607. Whether transactions from senders with deployed code are
+	/// rejected.
+	pub reject_sender_with_code: bool,
 	/// Stack limit.
 	pub stack_limit: usize,
 	/// Memory limit.
 	pub memory_limit: usize,
+	/// Maximum cumulative size of the memory of the machines of the call
+	/// stack. The frame expanding its memory past it fails with out of gas.
+	pub max_total_memory: Option<usize>,
 	/// Call limit.
 	pub call_stack_limit: usize,
 	/// Create contract limit.
 	pub create_contract_limit: Option<usize>,
+	/// EIP-3860. Maximum size of the init code of create transactions and
+	/// opcodes.
+	pub max_initcode_size: Option<usize>,
+	/// Maximum size of the return data of a sub-call. A larger return data
+	/// fails the calling frame with out of gas instead of being buffered.
+	pub max_return_data_size: Option<usize>,
+	/// Maximum cumulative size of the return data of all sub-calls of a
+	/// transaction. The sub-call exceeding it fails with out of gas.
+	pub max_total_return_data_size: Option<usize>,
+	/// Maximum number of sub-calls and sub-creates of a transaction, whatever
+	/// their depth. The sub-call exceeding it fails with out of gas.
+	pub max_call_count: Option<u64>,
 	/// Call stipend.
 	pub call_stipend: u64,
 	/// Has delegate call.
@@ -198,11 +394,136 @@ pub struct Config {
 	pub has_self_balance: bool,
 	/// Has ext code hash.
 	pub has_ext_code_hash: bool,
+	/// EIP-7623. Whether transactions pay at least the calldata floor.
+	pub has_calldata_floor: bool,
+	/// EIP-6780. Whether SUICIDE only deletes contracts created in the same
+	/// transaction, and otherwise only transfers the balance.
+	pub suicide_only_if_created_same_tx: bool,
+	/// EIP-3651. Whether the block coinbase is warm from the start of
+	/// transactions.
+	pub warm_coinbase: bool,
+	/// EIP-1153. Has transient storage, TLOAD and TSTORE.
+	pub has_transient_storage: bool,
+	/// EIP-4399. Whether DIFFICULTY pushes the block randomness (PREVRANDAO)
+	/// instead of the block difficulty.
+	pub has_prevrandao: bool,
+	/// Whether gas is metered. Without metering, no gas is charged or
+	/// refunded, for testing opcode semantics in isolation.
+	pub metering: bool,
 	/// Whether the gasometer is running in estimate mode.
 	pub estimate: bool,
 }
 
 impl Config {
+	/// List of the EIPs and features enabled by this configuration, derived
+	/// from its flags.
+	pub fn active_features(&self) -> Vec<&'static str> {
+		let mut features = Vec::new();
+
+		if self.call_l64_after_gas {
+			features.push("EIP-150");
+		}
+		if !self.empty_considered_exists {
+			features.push("EIP-161");
+		}
+		if self.create_contract_limit.is_some() {
+			features.push("EIP-170");
+		}
+		if self.has_delegate_call {
+			features.push("EIP-7");
+		}
+		if self.has_revert {
+			features.push("EIP-140");
+		}
+		if self.has_return_data {
+			features.push("EIP-211");
+		}
+		if self.has_bitwise_shifting {
+			features.push("EIP-145");
+		}
+		if self.has_create2 {
+			features.push("EIP-1014");
+		}
+		if self.has_ext_code_hash {
+			features.push("EIP-1052");
+		}
+		if self.sstore_gas_metering && self.sstore_revert_under_stipend {
+			features.push("EIP-2200");
+		} else if self.sstore_gas_metering {
+			features.push("EIP-1283");
+		} else if self.sstore_revert_under_stipend {
+			features.push("EIP-1706");
+		}
+		if self.has_chain_id {
+			features.push("EIP-1344");
+		}
+		if self.has_self_balance {
+			features.push("EIP-1884");
+		}
+		if self.increase_state_access_gas {
+			features.push("EIP-2929");
+		}
+		if self.refund_suicide == 0 {
+			features.push("EIP-3529");
+		}
+		if self.reject_sender_with_code {
+			features.push("EIP-3607");
+		}
+		if self.has_transient_storage {
+			features.push("EIP-1153");
+		}
+		if self.max_initcode_size.is_some() {
+			features.push("EIP-3860");
+		}
+		if self.has_prevrandao {
+			features.push("EIP-4399");
+		}
+		if self.warm_coinbase {
+			features.push("EIP-3651");
+		}
+		if self.has_calldata_floor {
+			features.push("EIP-7623");
+		}
+		if self.suicide_only_if_created_same_tx {
+			features.push("EIP-6780");
+		}
+
+		features
+	}
+
+	/// Intrinsic gas of a transaction with the given data, or init code for
+	/// creates, and access list: the base cost, the cost of the data, the
+	/// cost of the access list, and for creates under EIP-3860 the cost of
+	/// every word of init code.
+	pub fn intrinsic_gas(
+		&self,
+		is_create: bool,
+		data: &[u8],
+		access_list: &[(H160, Vec<H256>)],
+	) -> u64 {
+		let zero_data_len = data.iter().filter(|v| **v == 0).count() as u64;
+		let non_zero_data_len = data.len() as u64 - zero_data_len;
+		let storage_keys: u64 = access_list.iter().map(|(_, keys)| keys.len() as u64).sum();
+
+		let base = if is_create {
+			self.gas_transaction_create
+		} else {
+			self.gas_transaction_call
+		};
+		let init_code_cost = if is_create && self.max_initcode_size.is_some() {
+			(data.len() as u64).div_ceil(32) * INIT_CODE_WORD_COST
+		} else {
+			0
+		};
+
+		base +
+			zero_data_len * self.gas_transaction_zero_data +
+			non_zero_data_len * self.gas_transaction_non_zero_data +
+			access_list.len() as u64 * self.gas_access_list_address +
+			storage_keys * self.gas_access_list_storage_key +
+			init_code_cost
+	}
+
 	/// Frontier hard fork configuration.
 	pub const fn frontier() -> Config {
 		Config {
@@ -210,27 +531,47 @@ impl Config {
 			gas_ext_code_hash: 20,
 			gas_balance: 20,
 			gas_sload: 50,
+			gas_sload_cold: 0,
+			gas_storage_read_warm: 0,
+			gas_account_access_cold: 0,
 			gas_sstore_set: 20000,
 			gas_sstore_reset: 5000,
 			refund_sstore_clears: 15000,
+			max_refund_quotient: 2,
+			refund_suicide: 24000,
 			gas_suicide: 0,
 			gas_suicide_new_account: 0,
 			gas_call: 40,
+			gas_call_new_account: 25000,
 			gas_expbyte: 10,
+			gas_log: 375,
+			gas_log_topic: 375,
+			gas_log_data: 8,
 			gas_transaction_create: 21000,
 			gas_transaction_call: 21000,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 68,
+			gas_transaction_floor_per_token: 10,
+			gas_access_list_address: 0,
+			gas_access_list_storage_key: 0,
 			sstore_gas_metering: false,
 			sstore_revert_under_stipend: false,
+			increase_state_access_gas: false,
 			err_on_call_with_more_gas: true,
 			empty_considered_exists: true,
 			create_increase_nonce: false,
+			validate_upfront_cost: false,
+			reject_sender_with_code: false,
 			call_l64_after_gas: false,
 			stack_limit: 1024,
 			memory_limit: usize::max_value(),
+			max_total_memory: None,
 			call_stack_limit: 1024,
 			create_contract_limit: None,
+			max_initcode_size: None,
+			max_return_data_size: None,
+			max_total_return_data_size: None,
+			max_call_count: None,
 			call_stipend: 2300,
 			has_delegate_call: false,
 			has_create2: false,
@@ -240,6 +581,12 @@ impl Config {
 			has_chain_id: false,
 			has_self_balance: false,
 			has_ext_code_hash: false,
+			has_calldata_floor: false,
+			suicide_only_if_created_same_tx: false,
+			has_transient_storage: false,
+			has_prevrandao: false,
+			warm_coinbase: false,
+			metering: true,
 			estimate: false,
 		}
 	}
@@ -251,27 +598,114 @@ impl Config {
 			gas_ext_code_hash: 700,
 			gas_balance: 700,
 			gas_sload: 800,
+			gas_sload_cold: 0,
+			gas_storage_read_warm: 0,
+			gas_account_access_cold: 0,
 			gas_sstore_set: 20000,
 			gas_sstore_reset: 5000,
 			refund_sstore_clears: 15000,
+			max_refund_quotient: 2,
+			refund_suicide: 24000,
 			gas_suicide: 5000,
 			gas_suicide_new_account: 25000,
 			gas_call: 700,
+			gas_call_new_account: 25000,
 			gas_expbyte: 50,
+			gas_log: 375,
+			gas_log_topic: 375,
+			gas_log_data: 8,
 			gas_transaction_create: 53000,
 			gas_transaction_call: 21000,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 16,
+			gas_transaction_floor_per_token: 10,
+			gas_access_list_address: 0,
+			gas_access_list_storage_key: 0,
 			sstore_gas_metering: true,
 			sstore_revert_under_stipend: true,
+			increase_state_access_gas: false,
 			err_on_call_with_more_gas: false,
 			empty_considered_exists: false,
 			create_increase_nonce: true,
+			validate_upfront_cost: false,
+			reject_sender_with_code: false,
 			call_l64_after_gas: true,
 			stack_limit: 1024,
 			memory_limit: usize::max_value(),
+			max_total_memory: None,
+			call_stack_limit: 1024,
+			create_contract_limit: Some(0x6000),
+			max_initcode_size: None,
+			max_return_data_size: None,
+			max_total_return_data_size: None,
+			max_call_count: None,
+			call_stipend: 2300,
+			has_delegate_call: true,
+			has_create2: true,
+			has_revert: true,
+			has_return_data: true,
+			has_bitwise_shifting: true,
+			has_chain_id: true,
+			has_self_balance: true,
+			has_ext_code_hash: true,
+			has_calldata_floor: false,
+			suicide_only_if_created_same_tx: false,
+			has_transient_storage: false,
+			has_prevrandao: false,
+			warm_coinbase: false,
+			metering: true,
+			estimate: false,
+		}
+	}
+
+	/// Berlin hard fork configuration.
+	pub const fn berlin() -> Config {
+		Config {
+			gas_ext_code: 100,
+			gas_ext_code_hash: 100,
+			gas_balance: 100,
+			gas_sload: 100,
+			gas_sload_cold: 2100,
+			gas_storage_read_warm: 100,
+			gas_account_access_cold: 2600,
+			gas_sstore_set: 20000,
+			gas_sstore_reset: 2900,
+			refund_sstore_clears: 15000,
+			max_refund_quotient: 2,
+			refund_suicide: 24000,
+			gas_suicide: 5000,
+			gas_suicide_new_account: 25000,
+			gas_call: 100,
+			gas_call_new_account: 25000,
+			gas_expbyte: 50,
+			gas_log: 375,
+			gas_log_topic: 375,
+			gas_log_data: 8,
+			gas_transaction_create: 53000,
+			gas_transaction_call: 21000,
+			gas_transaction_zero_data: 4,
+			gas_transaction_non_zero_data: 16,
+			gas_transaction_floor_per_token: 10,
+			gas_access_list_address: 2400,
+			gas_access_list_storage_key: 1900,
+			sstore_gas_metering: true,
+			sstore_revert_under_stipend: true,
+			increase_state_access_gas: true,
+			err_on_call_with_more_gas: false,
+			empty_considered_exists: false,
+			create_increase_nonce: true,
+			validate_upfront_cost: false,
+			reject_sender_with_code: false,
+			call_l64_after_gas: true,
+			stack_limit: 1024,
+			memory_limit: usize::MAX,
+			max_total_memory: None,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
+			max_initcode_size: None,
+			max_return_data_size: None,
+			max_total_return_data_size: None,
+			max_call_count: None,
 			call_stipend: 2300,
 			has_delegate_call: true,
 			has_create2: true,
@@ -281,7 +715,33 @@ impl Config {
 			has_chain_id: true,
 			has_self_balance: true,
 			has_ext_code_hash: true,
+			has_calldata_floor: false,
+			suicide_only_if_created_same_tx: false,
+			has_transient_storage: false,
+			has_prevrandao: false,
+			warm_coinbase: false,
+			metering: true,
 			estimate: false,
 		}
 	}
+
+	/// London hard fork configuration. The BASEFEE opcode (EIP-3198) is not
+	/// supported.
+	pub const fn london() -> Config {
+		Config {
+			refund_sstore_clears: 4800,
+			max_refund_quotient: 5,
+			refund_suicide: 0,
+			..Self::berlin()
+		}
+	}
+
+	/// Paris (the Merge) hard fork configuration, with DIFFICULTY returning
+	/// the block randomness.
+	pub const fn paris() -> Config {
+		Config {
+			has_prevrandao: true,
+			..Self::london()
+		}
+	}
 }
@@ -0,0 +1,162 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use primitive_types::{H160, H256, U256};
+use crate::{CallScheme, Capture, Context, CreateScheme, ExitError, ExitReason, ExternalOperation, Handler,
+			Machine, Opcode, Stack, Transfer};
+
+/// Handler delegating to an inner handler, and recording the addresses and
+/// storage slots accessed, to build an EIP-2930 access list from a trial run.
+///
+/// Accesses are recorded through `Handler::record_external_operation`, so
+/// only the ones of runtimes run with the recorder as handler are seen.
+/// Sub-calls run by the inner handler itself are not.
+pub struct AccessListRecorder<H> {
+	inner: H,
+	accessed: BTreeMap<H160, BTreeSet<H256>>,
+}
+
+impl<H> AccessListRecorder<H> {
+	/// Wrap the given handler.
+	pub fn new(inner: H) -> Self {
+		Self {
+			inner,
+			accessed: BTreeMap::new(),
+		}
+	}
+
+	/// Get a reference to the inner handler.
+	pub fn inner(&self) -> &H {
+		&self.inner
+	}
+
+	/// Get a mutable reference to the inner handler.
+	pub fn inner_mut(&mut self) -> &mut H {
+		&mut self.inner
+	}
+
+	/// Unwrap the inner handler.
+	pub fn into_inner(self) -> H {
+		self.inner
+	}
+
+	/// Addresses accessed, with the storage slots accessed of each, both in
+	/// ascending order.
+	pub fn access_list(&self) -> Vec<(H160, Vec<H256>)> {
+		self.accessed.iter()
+			.map(|(address, slots)| (*address, slots.iter().cloned().collect()))
+			.collect()
+	}
+}
+
+impl<H: Handler> Handler for AccessListRecorder<H> {
+	type CreateInterrupt = H::CreateInterrupt;
+	type CreateFeedback = H::CreateFeedback;
+	type CallInterrupt = H::CallInterrupt;
+	type CallFeedback = H::CallFeedback;
+
+	fn balance(&self, address: H160) -> U256 { self.inner.balance(address) }
+	fn code_size(&self, address: H160) -> U256 { self.inner.code_size(address) }
+	fn code_hash(&self, address: H160) -> H256 { self.inner.code_hash(address) }
+	fn code(&self, address: H160) -> Vec<u8> { self.inner.code(address) }
+	fn storage(&self, address: H160, index: H256) -> H256 { self.inner.storage(address, index) }
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.inner.transient_storage(address, index)
+	}
+	fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.inner.original_storage(address, index)
+	}
+
+	fn gas_left(&self) -> U256 { self.inner.gas_left() }
+	fn gas_spent(&self) -> U256 { self.inner.gas_spent() }
+	fn gas_refund(&self) -> i64 { self.inner.gas_refund() }
+	fn gas_price(&self) -> U256 { self.inner.gas_price() }
+	fn origin(&self) -> H160 { self.inner.origin() }
+	fn block_hash(&self, number: U256) -> H256 { self.inner.block_hash(number) }
+	fn block_number(&self) -> U256 { self.inner.block_number() }
+	fn block_coinbase(&self) -> H160 { self.inner.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.inner.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.inner.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.inner.block_randomness() }
+	fn block_gas_limit(&self) -> U256 { self.inner.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.inner.chain_id() }
+
+	fn exists(&self, address: H160) -> bool { self.inner.exists(address) }
+	fn is_contract(&self, address: H160) -> bool { self.inner.is_contract(address) }
+	fn deleted(&self, address: H160) -> bool { self.inner.deleted(address) }
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+		self.inner.is_cold(address, index)
+	}
+	fn created_in_current_transaction(&self, address: H160) -> bool {
+		self.inner.created_in_current_transaction(address)
+	}
+	fn pending_selfdestructs(&self) -> Vec<H160> { self.inner.pending_selfdestructs() }
+
+	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.inner.set_storage(address, index, value)
+	}
+	fn set_transient_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.inner.set_transient_storage(address, index, value)
+	}
+	fn clear_transient_storage(&mut self) { self.inner.clear_transient_storage() }
+	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
+		self.inner.log(address, topics, data)
+	}
+	fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+		self.inner.mark_delete(address, target)
+	}
+	fn transfer_balance(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+		self.inner.transfer_balance(address, target)
+	}
+	fn create(
+		&mut self,
+		caller: H160,
+		scheme: CreateScheme,
+		value: U256,
+		init_code: Vec<u8>,
+		target_gas: Option<u64>,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
+		self.inner.create(caller, scheme, value, init_code, target_gas)
+	}
+	fn create_feedback(&mut self, feedback: Self::CreateFeedback) -> Result<(), ExitError> {
+		self.inner.create_feedback(feedback)
+	}
+	fn call(
+		&mut self,
+		code_address: H160,
+		transfer: Option<Transfer>,
+		input: Vec<u8>,
+		target_gas: Option<u64>,
+		scheme: CallScheme,
+		context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
+		self.inner.call(code_address, transfer, input, target_gas, scheme, context)
+	}
+	fn call_feedback(&mut self, feedback: Self::CallFeedback) -> Result<(), ExitError> {
+		self.inner.call_feedback(feedback)
+	}
+
+	fn step(&mut self, context: &Context, opcode: Opcode, machine: &Machine) {
+		self.inner.step(context, opcode, machine)
+	}
+	fn record_external_operation(&mut self, op: ExternalOperation) {
+		match op {
+			ExternalOperation::AccountAccess(address) |
+			ExternalOperation::PrecompileInvocation(address) => {
+				self.accessed.entry(address).or_default();
+			},
+			ExternalOperation::StorageAccess(address, index) => {
+				self.accessed.entry(address).or_default().insert(index);
+			},
+		}
+		self.inner.record_external_operation(op)
+	}
+	fn memory_expansion_cost(&self, current_words: usize, new_words: usize) -> u64 {
+		self.inner.memory_expansion_cost(current_words, new_words)
+	}
+	fn pre_validate(&mut self, context: &Context, opcode: Opcode, stack: &Stack) -> Result<(), ExitError> {
+		self.inner.pre_validate(context, opcode, stack)
+	}
+	fn other(&mut self, opcode: Opcode, machine: &mut Machine) -> Result<(), ExitError> {
+		self.inner.other(opcode, machine)
+	}
+}
@@ -0,0 +1,57 @@
+use alloc::string::String;
+use primitive_types::U256;
+
+/// Selector of the `Error(string)` revert data.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the `Panic(uint256)` revert data.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decoded revert data.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RevertReason {
+	/// `Error(string)`, as produced by `require` and `revert` with a message.
+	Error(String),
+	/// `Panic(uint256)`, as produced by failed assertions and checked
+	/// arithmetic, with its panic code.
+	Panic(U256),
+}
+
+/// Decode the ABI-encoded `Error(string)` or `Panic(uint256)` revert data.
+/// Return `None` for any other or malformed data.
+pub fn decode_revert(data: &[u8]) -> Option<RevertReason> {
+	if data.len() < 4 {
+		return None
+	}
+	let (selector, args) = data.split_at(4);
+
+	if selector == ERROR_SELECTOR {
+		let offset = read_usize(args, 0)?;
+		let len = read_usize(args, offset)?;
+		let start = offset.checked_add(32)?;
+		let end = start.checked_add(len)?;
+		let bytes = args.get(start..end)?;
+
+		core::str::from_utf8(bytes).ok().map(|s| RevertReason::Error(s.into()))
+	} else if selector == PANIC_SELECTOR {
+		args.get(0..32).map(|code| RevertReason::Panic(U256::from_big_endian(code)))
+	} else {
+		None
+	}
+}
+
+/// Decode the message of the ABI-encoded `Error(string)` revert data.
+/// Return `None` for panics or malformed data.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+	match decode_revert(data)? {
+		RevertReason::Error(reason) => Some(reason),
+		RevertReason::Panic(_) => None,
+	}
+}
+
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+	let word = U256::from_big_endian(data.get(offset..offset.checked_add(32)?)?);
+	if word > U256::from(usize::MAX) {
+		return None
+	}
+	Some(word.as_usize())
+}
@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use evm_runtime::{create_address, create2_address};
+use primitive_types::{H160, H256, U256};
+
+fn h160(s: &str) -> H160 {
+	H160::from_str(s).unwrap()
+}
+
+fn h256(s: &str) -> H256 {
+	H256::from_str(s).unwrap()
+}
+
+fn bytes(s: &str) -> Vec<u8> {
+	(0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn legacy_address() {
+	let sender = h160("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+
+	assert_eq!(
+		create_address(sender, U256::zero()),
+		h160("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"),
+	);
+	assert_eq!(
+		create_address(sender, U256::one()),
+		h160("343c43a37d37dff08ae8c4a11544c718abb4fcf8"),
+	);
+}
+
+/// Examples from EIP-1014.
+#[test]
+fn create2_address_examples() {
+	assert_eq!(
+		create2_address(H160::zero(), H256::zero(), &bytes("00")),
+		h160("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"),
+	);
+	assert_eq!(
+		create2_address(
+			h160("00000000000000000000000000000000deadbeef"),
+			h256("00000000000000000000000000000000000000000000000000000000cafebabe"),
+			&bytes("deadbeef"),
+		),
+		h160("60f3f640a8508fc6a86d45df051962668e1e8ac7"),
+	);
+	assert_eq!(
+		create2_address(
+			h160("00000000000000000000000000000000deadbeef"),
+			h256("00000000000000000000000000000000000000000000000000000000cafebabe"),
+			&bytes("deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"),
+		),
+		h160("1d8bfdc5d46dc4f61d6b6115972536ebe6a8854c"),
+	);
+	assert_eq!(
+		create2_address(H160::zero(), H256::zero(), &[]),
+		h160("e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0"),
+	);
+}
@@ -0,0 +1,91 @@
+use evm_runtime::Config;
+use primitive_types::{H160, H256};
+
+#[test]
+fn frontier_has_no_features() {
+	assert!(Config::frontier().active_features().is_empty());
+}
+
+#[test]
+fn istanbul_features() {
+	assert_eq!(Config::istanbul().active_features(), vec![
+		"EIP-150", "EIP-161", "EIP-170", "EIP-7", "EIP-140", "EIP-211",
+		"EIP-145", "EIP-1014", "EIP-1052", "EIP-2200", "EIP-1344", "EIP-1884",
+	]);
+}
+
+#[test]
+fn sstore_gas_metering_features() {
+	let mut config = Config::istanbul();
+	config.sstore_revert_under_stipend = false;
+	assert!(config.active_features().contains(&"EIP-1283"));
+	assert!(!config.active_features().contains(&"EIP-2200"));
+
+	config.sstore_gas_metering = false;
+	config.sstore_revert_under_stipend = true;
+	assert!(config.active_features().contains(&"EIP-1706"));
+	assert!(!config.active_features().contains(&"EIP-1283"));
+}
+
+#[test]
+fn calldata_floor_feature() {
+	let mut config = Config::istanbul();
+	config.has_calldata_floor = true;
+	assert_eq!(config.active_features().last(), Some(&"EIP-7623"));
+}
+
+#[test]
+fn berlin_features() {
+	assert_eq!(Config::berlin().active_features().last(), Some(&"EIP-2929"));
+}
+
+#[test]
+fn london_features() {
+	assert_eq!(Config::london().active_features().last(), Some(&"EIP-3529"));
+}
+
+#[test]
+fn paris_differs_from_london_only_by_prevrandao() {
+	let paris = Config::paris();
+	assert!(paris.has_prevrandao);
+	assert_eq!(paris.max_refund_quotient, 5);
+
+	let mut london = Config::london();
+	assert!(!london.has_prevrandao);
+	london.has_prevrandao = true;
+	assert_eq!(format!("{:?}", london), format!("{:?}", paris));
+}
+
+#[test]
+fn intrinsic_gas_of_token_transfer() {
+	// transfer(address, uint256) of 1e18 to an address without zero bytes
+	let mut data = vec![0xa9, 0x05, 0x9c, 0xbb];
+	data.extend_from_slice(&[0; 12]);
+	data.extend_from_slice(&[0x11; 20]);
+	data.extend_from_slice(&[0; 24]);
+	data.extend_from_slice(&[0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00]);
+
+	// 38 zero and 30 non-zero bytes
+	assert_eq!(Config::istanbul().intrinsic_gas(false, &data, &[]), 21000 + 38 * 4 + 30 * 16);
+	assert_eq!(Config::frontier().intrinsic_gas(false, &data, &[]), 21000 + 38 * 4 + 30 * 68);
+}
+
+#[test]
+fn intrinsic_gas_of_access_list() {
+	let access_list = vec![
+		(H160::repeat_byte(1), vec![H256::zero(), H256::repeat_byte(1)]),
+		(H160::repeat_byte(2), Vec::new()),
+	];
+
+	assert_eq!(Config::berlin().intrinsic_gas(false, &[], &access_list), 21000 + 2 * 2400 + 2 * 1900);
+}
+
+#[test]
+fn intrinsic_gas_of_init_code_words() {
+	let init_code = vec![0xff; 33];
+	assert_eq!(Config::istanbul().intrinsic_gas(true, &init_code, &[]), 53000 + 33 * 16);
+
+	let config = Config { max_initcode_size: Some(0xc000), ..Config::istanbul() };
+	assert_eq!(config.intrinsic_gas(true, &init_code, &[]), 53000 + 33 * 16 + 2 * 2);
+	assert_eq!(config.intrinsic_gas(false, &init_code, &[]), 21000 + 33 * 16);
+}
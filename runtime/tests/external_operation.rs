@@ -0,0 +1,138 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use evm_runtime::{
+	CallScheme, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitSucceed,
+	ExternalOperation, Handler, Opcode, Runtime, Stack, Transfer,
+};
+use primitive_types::{H160, H256, U256};
+
+/// Handler of an empty world recording the external operations, and
+/// counting the accounts accessed for the first time as cold.
+#[derive(Default)]
+struct Recording {
+	operations: Vec<ExternalOperation>,
+	accessed: BTreeSet<H160>,
+	cold_accounts: usize,
+}
+
+impl Handler for Recording {
+	type CreateInterrupt = ();
+	type CreateFeedback = ();
+	type CallInterrupt = ();
+	type CallFeedback = ();
+
+	fn balance(&self, _address: H160) -> U256 { U256::zero() }
+	fn code_size(&self, _address: H160) -> U256 { U256::zero() }
+	fn code_hash(&self, _address: H160) -> H256 { H256::zero() }
+	fn code(&self, _address: H160) -> Vec<u8> { Vec::new() }
+	fn storage(&self, _address: H160, _index: H256) -> H256 { H256::zero() }
+	fn original_storage(&self, _address: H160, _index: H256) -> H256 { H256::zero() }
+	fn gas_left(&self) -> U256 { U256::zero() }
+	fn gas_price(&self) -> U256 { U256::zero() }
+	fn origin(&self) -> H160 { H160::zero() }
+	fn block_hash(&self, _number: U256) -> H256 { H256::zero() }
+	fn block_number(&self) -> U256 { U256::zero() }
+	fn block_coinbase(&self) -> H160 { H160::zero() }
+	fn block_timestamp(&self) -> U256 { U256::zero() }
+	fn block_difficulty(&self) -> U256 { U256::zero() }
+	fn block_gas_limit(&self) -> U256 { U256::zero() }
+	fn chain_id(&self) -> U256 { U256::zero() }
+	fn exists(&self, _address: H160) -> bool { false }
+	fn deleted(&self, _address: H160) -> bool { false }
+	fn is_cold(&self, address: H160, _index: Option<H256>) -> bool {
+		!self.accessed.contains(&address)
+	}
+
+	fn set_storage(&mut self, _address: H160, _index: H256, _value: H256) -> Result<(), ExitError> {
+		Ok(())
+	}
+	fn log(&mut self, _address: H160, _topics: Vec<H256>, _data: Vec<u8>) -> Result<(), ExitError> {
+		Ok(())
+	}
+	fn mark_delete(&mut self, _address: H160, _target: H160) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn create(
+		&mut self,
+		_caller: H160,
+		_scheme: CreateScheme,
+		_value: U256,
+		_init_code: Vec<u8>,
+		_target_gas: Option<u64>,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), ()> {
+		Capture::Trap(())
+	}
+
+	fn call(
+		&mut self,
+		_code_address: H160,
+		_transfer: Option<Transfer>,
+		_input: Vec<u8>,
+		_target_gas: Option<u64>,
+		_scheme: CallScheme,
+		_context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), ()> {
+		Capture::Trap(())
+	}
+
+	fn record_external_operation(&mut self, op: ExternalOperation) {
+		if let ExternalOperation::AccountAccess(address) = op {
+			if self.is_cold(address, None) {
+				self.accessed.insert(address);
+				self.cold_accounts += 1;
+			}
+		}
+		self.operations.push(op);
+	}
+
+	fn pre_validate(&mut self, _context: &Context, _opcode: Opcode, _stack: &Stack) -> Result<(), ExitError> {
+		Ok(())
+	}
+}
+
+fn run(code: &str) -> Recording {
+	let config = Config::istanbul();
+	let context = Context {
+		address: H160::from_low_u64_be(1),
+		caller: H160::from_low_u64_be(2),
+		tx_origin: H160::from_low_u64_be(2),
+		apparent_value: U256::zero(),
+		is_static: false,
+		depth: 0,
+	};
+	let code = (0..code.len()).step_by(2)
+		.map(|i| u8::from_str_radix(&code[i..i + 2], 16).unwrap())
+		.collect();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+	let mut handler = Recording::default();
+
+	let reason = match runtime.run(&mut handler) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	handler
+}
+
+#[test]
+fn balance_records_account_accesses() {
+	// BALANCE of 0x0a, 0x0b, 0x0a and 0x0c
+	let handler = run("600a3150600b3150600a3150600c315000");
+
+	assert_eq!(handler.operations.len(), 4);
+	assert_eq!(handler.cold_accounts, 3);
+}
+
+#[test]
+fn storage_opcodes_record_storage_accesses() {
+	// SLOAD 1, SSTORE 2 <- 3
+	let handler = run("600154506003600255");
+
+	let address = H160::from_low_u64_be(1);
+	assert_eq!(handler.operations, vec![
+		ExternalOperation::StorageAccess(address, H256::from_low_u64_be(1)),
+		ExternalOperation::StorageAccess(address, H256::from_low_u64_be(2)),
+	]);
+	assert_eq!(handler.cold_accounts, 0);
+}
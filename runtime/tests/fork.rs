@@ -0,0 +1,46 @@
+use evm_runtime::{Config, ForkSchedule};
+
+fn features(config: &Config) -> Vec<&'static str> {
+	config.active_features()
+}
+
+#[test]
+fn mainnet_uses_frontier_before_istanbul_and_istanbul_after() {
+	let schedule = ForkSchedule::mainnet();
+
+	assert_eq!(features(schedule.config_for(0)), features(&Config::frontier()));
+	assert_eq!(features(schedule.config_for(9_068_999)), features(&Config::frontier()));
+	assert_eq!(features(schedule.config_for(9_069_000)), features(&Config::istanbul()));
+	assert_eq!(features(schedule.config_for(12_243_999)), features(&Config::istanbul()));
+}
+
+#[test]
+fn mainnet_later_forks() {
+	let schedule = ForkSchedule::mainnet();
+
+	assert_eq!(features(schedule.config_for(12_244_000)), features(&Config::berlin()));
+	assert_eq!(features(schedule.config_for(12_965_000)), features(&Config::london()));
+	assert!(!schedule.config_for(15_537_393).has_prevrandao);
+	assert!(schedule.config_for(15_537_394).has_prevrandao);
+	assert!(schedule.config_for(u64::MAX).has_prevrandao);
+}
+
+#[test]
+fn forks_are_ordered_regardless_of_insertion_order() {
+	let schedule = ForkSchedule::new(Config::frontier())
+		.with_fork(20, Config::berlin())
+		.with_fork(10, Config::istanbul())
+		.with_fork(20, Config::london());
+
+	let blocks: Vec<u64> = schedule.forks().iter().map(|(block, _)| *block).collect();
+	assert_eq!(blocks, vec![0, 10, 20]);
+	assert_eq!(features(schedule.config_for(15)), features(&Config::istanbul()));
+	assert_eq!(features(schedule.config_for(20)), features(&Config::london()));
+}
+
+#[test]
+fn genesis_fork_can_be_replaced() {
+	let schedule = ForkSchedule::new(Config::frontier()).with_fork(0, Config::istanbul());
+	assert_eq!(schedule.forks().len(), 1);
+	assert_eq!(features(schedule.config_for(0)), features(&Config::istanbul()));
+}
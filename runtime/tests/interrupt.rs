@@ -0,0 +1,232 @@
+use std::rc::Rc;
+use evm_runtime::{
+	CallScheme, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitRevert, ExitSucceed,
+	Handler, Opcode, Resolve, Runtime, Stack, Transfer,
+};
+use primitive_types::{H160, H256, U256};
+
+/// Handler interrupting every call and create, with an empty world.
+struct Interrupting;
+
+impl Handler for Interrupting {
+	type CreateInterrupt = ();
+	type CreateFeedback = ();
+	type CallInterrupt = ();
+	type CallFeedback = ();
+
+	fn balance(&self, _address: H160) -> U256 { U256::zero() }
+	fn code_size(&self, _address: H160) -> U256 { U256::zero() }
+	fn code_hash(&self, _address: H160) -> H256 { H256::zero() }
+	fn code(&self, _address: H160) -> Vec<u8> { Vec::new() }
+	fn storage(&self, _address: H160, _index: H256) -> H256 { H256::zero() }
+	fn original_storage(&self, _address: H160, _index: H256) -> H256 { H256::zero() }
+	fn gas_left(&self) -> U256 { U256::zero() }
+	fn gas_price(&self) -> U256 { U256::zero() }
+	fn origin(&self) -> H160 { H160::zero() }
+	fn block_hash(&self, _number: U256) -> H256 { H256::zero() }
+	fn block_number(&self) -> U256 { U256::zero() }
+	fn block_coinbase(&self) -> H160 { H160::zero() }
+	fn block_timestamp(&self) -> U256 { U256::zero() }
+	fn block_difficulty(&self) -> U256 { U256::zero() }
+	fn block_gas_limit(&self) -> U256 { U256::zero() }
+	fn chain_id(&self) -> U256 { U256::zero() }
+	fn exists(&self, _address: H160) -> bool { false }
+	fn deleted(&self, _address: H160) -> bool { false }
+
+	fn set_storage(&mut self, _address: H160, _index: H256, _value: H256) -> Result<(), ExitError> {
+		Ok(())
+	}
+	fn log(&mut self, _address: H160, _topics: Vec<H256>, _data: Vec<u8>) -> Result<(), ExitError> {
+		Ok(())
+	}
+	fn mark_delete(&mut self, _address: H160, _target: H160) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn create(
+		&mut self,
+		_caller: H160,
+		_scheme: CreateScheme,
+		_value: U256,
+		_init_code: Vec<u8>,
+		_target_gas: Option<u64>,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), ()> {
+		Capture::Trap(())
+	}
+
+	fn call(
+		&mut self,
+		_code_address: H160,
+		_transfer: Option<Transfer>,
+		_input: Vec<u8>,
+		_target_gas: Option<u64>,
+		_scheme: CallScheme,
+		_context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), ()> {
+		Capture::Trap(())
+	}
+
+	fn pre_validate(&mut self, _context: &Context, _opcode: Opcode, _stack: &Stack) -> Result<(), ExitError> {
+		Ok(())
+	}
+}
+
+fn context() -> Context {
+	Context {
+		address: H160::from_low_u64_be(1),
+		caller: H160::from_low_u64_be(2),
+		tx_origin: H160::from_low_u64_be(2),
+		apparent_value: U256::zero(),
+		is_static: false,
+		depth: 0,
+	}
+}
+
+fn word(value: u64) -> Vec<u8> {
+	H256::from_low_u64_be(value).as_bytes().to_vec()
+}
+
+/// CALL with a 32 bytes output range at offset 0, then return the output
+/// range, the call result and RETURNDATASIZE.
+///
+/// PUSH1 0x20 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0x01 PUSH2 0xffff CALL
+/// PUSH1 0x20 MSTORE RETURNDATASIZE PUSH1 0x40 MSTORE PUSH1 0x60 PUSH1 0 RETURN
+const CALLER: &str = "60206000600060006000600161fffff16020523d60405260606000f3";
+
+fn run_resumed_call(
+	config: &Config,
+	reason: ExitReason,
+	return_data: Vec<u8>,
+) -> (ExitReason, Vec<u8>) {
+	let code = (0..CALLER.len()).step_by(2)
+		.map(|i| u8::from_str_radix(&CALLER[i..i + 2], 16).unwrap())
+		.collect();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context(), config);
+	let mut handler = Interrupting;
+
+	match runtime.run(&mut handler) {
+		Capture::Trap(Resolve::Call((), resolve)) => {
+			assert_eq!(resolve.scheme(), CallScheme::Call);
+			resolve.resume(reason, return_data)
+		},
+		_ => panic!("expected a call interrupt"),
+	}
+
+	let reason = match runtime.run(&mut handler) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	(reason, runtime.machine().return_value())
+}
+
+fn resume_call(reason: ExitReason, return_data: Vec<u8>) -> Vec<u8> {
+	let (reason, output) = run_resumed_call(&Config::istanbul(), reason, return_data);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	output
+}
+
+#[test]
+fn resume_succeeded_call() {
+	let output = resume_call(ExitReason::Succeed(ExitSucceed::Returned), word(0x2a));
+
+	assert_eq!(output, [word(0x2a), word(1), word(32)].concat());
+}
+
+#[test]
+fn resume_reverted_call() {
+	let output = resume_call(
+		ExitReason::Revert(ExitRevert::Reverted),
+		vec![0xde, 0xad, 0xbe, 0xef],
+	);
+
+	let mut revert_data = vec![0xde, 0xad, 0xbe, 0xef];
+	revert_data.resize(32, 0);
+	assert_eq!(output, [revert_data, word(0), word(4)].concat());
+}
+
+#[test]
+fn resume_failed_call() {
+	let output = resume_call(ExitReason::Error(ExitError::OutOfGas), Vec::new());
+
+	assert_eq!(output, [word(0), word(0), word(0)].concat());
+}
+
+#[test]
+fn call_interrupt_carries_the_scheme() {
+	let config = Config::istanbul();
+	for (opcode, scheme) in [
+		(0xf2, CallScheme::CallCode),
+		(0xf4, CallScheme::DelegateCall),
+		(0xfa, CallScheme::StaticCall),
+	] {
+		// PUSH1 0 (x4, plus one for the value of CALLCODE) PUSH1 0x01 PUSH2 0xffff
+		let mut code = vec![0x60, 0, 0x60, 0, 0x60, 0, 0x60, 0, 0x60, 0, 0x60, 1, 0x61, 0xff, 0xff];
+		code.push(opcode);
+		let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context(), &config);
+		let mut handler = Interrupting;
+
+		match runtime.run(&mut handler) {
+			Capture::Trap(Resolve::Call((), resolve)) => {
+				assert_eq!(resolve.scheme(), scheme);
+				resolve.resume(ExitReason::Succeed(ExitSucceed::Stopped), Vec::new())
+			},
+			_ => panic!("expected a call interrupt"),
+		};
+	}
+}
+
+#[test]
+fn resume_create() {
+	let config = Config::istanbul();
+	// PUSH1 0 PUSH1 0 PUSH1 0 CREATE PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN
+	let code = vec![0x60, 0, 0x60, 0, 0x60, 0, 0xf0, 0x60, 0, 0x52, 0x60, 0x20, 0x60, 0, 0xf3];
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context(), &config);
+	let mut handler = Interrupting;
+
+	let created = H160::from_low_u64_be(0xc4ea7ed);
+	match runtime.run(&mut handler) {
+		Capture::Trap(Resolve::Create((), resolve)) => {
+			resolve.resume(ExitReason::Succeed(ExitSucceed::Returned), Some(created), Vec::new())
+		},
+		_ => panic!("expected a create interrupt"),
+	}
+
+	match runtime.run(&mut handler) {
+		Capture::Exit(reason) => assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned)),
+		Capture::Trap(_) => panic!("unexpected trap"),
+	}
+	assert_eq!(runtime.machine().return_value(), H256::from(created).as_bytes());
+}
+
+#[test]
+fn resume_call_with_return_data_at_size_limit() {
+	let mut config = Config::istanbul();
+	config.max_return_data_size = Some(32);
+
+	let (reason, output) = run_resumed_call(
+		&config, ExitReason::Succeed(ExitSucceed::Returned), word(0x2a),
+	);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(output, [word(0x2a), word(1), word(32)].concat());
+
+	let (reason, _) = run_resumed_call(
+		&config, ExitReason::Succeed(ExitSucceed::Returned), vec![0; 33],
+	);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+}
+
+#[test]
+fn handler_defaults_are_conservative() {
+	let mut handler = Interrupting;
+
+	assert_eq!(handler.transient_storage(H160::zero(), H256::zero()), H256::zero());
+	assert_eq!(handler.set_transient_storage(H160::zero(), H256::zero(), H256::repeat_byte(1)), Ok(()));
+	assert_eq!(handler.transient_storage(H160::zero(), H256::zero()), H256::zero());
+	assert_eq!(handler.gas_spent(), U256::zero());
+	assert_eq!(handler.gas_refund(), 0);
+	assert_eq!(handler.block_randomness(), None);
+	assert!(handler.is_cold(H160::zero(), None));
+	assert!(!handler.created_in_current_transaction(H160::zero()));
+	assert!(handler.pending_selfdestructs().is_empty());
+	assert!(handler.transfer_balance(H160::zero(), H160::repeat_byte(1)).is_err());
+}
@@ -0,0 +1,52 @@
+use evm_runtime::{decode_revert, decode_revert_reason, RevertReason};
+use primitive_types::U256;
+
+fn word(value: u64) -> Vec<u8> {
+	let mut word = [0u8; 32];
+	U256::from(value).to_big_endian(&mut word);
+	word.to_vec()
+}
+
+/// Return data of `require(false, "msg")`.
+fn require_blob() -> Vec<u8> {
+	let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+	data.extend(word(0x20));
+	data.extend(word(3));
+	let mut message = b"msg".to_vec();
+	message.resize(32, 0);
+	data.extend(message);
+	data
+}
+
+#[test]
+fn require_message() {
+	assert_eq!(decode_revert_reason(&require_blob()), Some("msg".into()));
+	assert_eq!(decode_revert(&require_blob()), Some(RevertReason::Error("msg".into())));
+}
+
+#[test]
+fn panic_code() {
+	let mut data = vec![0x4e, 0x48, 0x7b, 0x71];
+	data.extend(word(0x11));
+
+	assert_eq!(decode_revert(&data), Some(RevertReason::Panic(0x11.into())));
+	assert_eq!(decode_revert_reason(&data), None);
+}
+
+#[test]
+fn malformed_data() {
+	assert_eq!(decode_revert(&[]), None);
+	assert_eq!(decode_revert(&[0x08, 0xc3, 0x79]), None);
+	// Unknown selector.
+	assert_eq!(decode_revert(&[0xde, 0xad, 0xbe, 0xef]), None);
+
+	// Length past the end of the data.
+	let mut data = require_blob();
+	data.truncate(4 + 64 + 2);
+	assert_eq!(decode_revert_reason(&data), None);
+
+	// Invalid UTF-8.
+	let mut data = require_blob();
+	data[4 + 64] = 0xff;
+	assert_eq!(decode_revert_reason(&data), None);
+}
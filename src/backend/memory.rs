@@ -24,6 +24,8 @@ pub struct MemoryVicinity {
 	pub block_timestamp: U256,
 	/// Environmental block difficulty.
 	pub block_difficulty: U256,
+	/// Environmental block randomness (PREVRANDAO), if known.
+	pub block_randomness: Option<H256>,
 	/// Environmental block gas limit.
 	pub block_gas_limit: U256,
 }
@@ -84,6 +86,7 @@ impl<'vicinity> Backend for MemoryBackend<'vicinity> {
 	fn block_coinbase(&self) -> H160 { self.vicinity.block_coinbase }
 	fn block_timestamp(&self) -> U256 { self.vicinity.block_timestamp }
 	fn block_difficulty(&self) -> U256 { self.vicinity.block_difficulty }
+	fn block_randomness(&self) -> Option<H256> { self.vicinity.block_randomness }
 	fn block_gas_limit(&self) -> U256 { self.vicinity.block_gas_limit }
 
 	fn chain_id(&self) -> U256 { self.vicinity.chain_id }
@@ -62,6 +62,10 @@ pub trait Backend {
 	fn block_timestamp(&self) -> U256;
 	/// Environmental block difficulty.
 	fn block_difficulty(&self) -> U256;
+	/// Environmental block randomness (PREVRANDAO), if known.
+	fn block_randomness(&self) -> Option<H256> {
+		None
+	}
 	/// Environmental block gas limit.
 	fn block_gas_limit(&self) -> U256;
 	/// Environmental chain ID.
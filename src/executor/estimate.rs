@@ -0,0 +1,40 @@
+//! Gas estimation of transactions.
+
+use core::cmp::min;
+use crate::{ExitError, ExitReason};
+use crate::executor::EstimateOutcome;
+
+/// Binary search the minimal gas limit in `lo..=hi` for which a transaction
+/// succeeds, as `eth_estimateGas` does.
+///
+/// `run` runs the transaction with the given gas limit, on a fresh state and
+/// an executor whose config is in estimate mode, returning its exit reason
+/// and `StackExecutor::estimate_outcome`. The estimate mode charges the gas
+/// reserved for callers by the 63/64 rule and ignores refunds, so that the
+/// limit found is enough without them.
+///
+/// The transaction is first run with `hi`. If it does not succeed, its exit
+/// reason is returned, or `OutOfGas` if `estimate_outcome` tells a higher
+/// limit may help, and no search is done. Any failure below `hi` is taken as a too low
+/// limit.
+pub fn estimate_gas<F>(mut run: F, lo: u64, hi: u64) -> Result<u64, ExitReason>
+where
+	F: FnMut(u64) -> (ExitReason, EstimateOutcome),
+{
+	match run(hi) {
+		(_, EstimateOutcome::Succeed) => (),
+		(_, EstimateOutcome::OutOfGas) => return Err(ExitError::OutOfGas.into()),
+		(reason, _) => return Err(reason),
+	}
+
+	let (mut lo, mut hi) = (min(lo, hi), hi);
+	while lo < hi {
+		let mid = lo + (hi - lo) / 2;
+		match run(mid) {
+			(_, EstimateOutcome::Succeed) => hi = mid,
+			_ => lo = mid + 1,
+		}
+	}
+
+	Ok(hi)
+}
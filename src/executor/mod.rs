@@ -3,6 +3,18 @@
 //! Executors are structs that hook gasometer and the EVM core together. It
 //! also handles the call stacks in EVM.
 
+pub mod precompile;
+
 mod stack;
+mod estimate;
+#[cfg(feature = "parallel")]
+mod parallel;
 
-pub use self::stack::{StackExecutor, MemoryStackState, StackState, StackSubstateMetadata};
+pub use self::stack::{
+	StackExecutor, MemoryStackState, MemoryStackSubstate, StackState, StackSubstateMetadata,
+	Inspector, Step, Frame, StorageStats, CallTracer, CallNode, MemoryDiffTracer, EstimateOutcome,
+	OpcodeBan, ExecutionResult,
+};
+pub use self::estimate::estimate_gas;
+#[cfg(feature = "parallel")]
+pub use self::parallel::{CallTransaction, transact_calls_parallel};
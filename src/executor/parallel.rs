@@ -0,0 +1,51 @@
+//! Parallel execution of independent transactions.
+
+use alloc::vec::Vec;
+use primitive_types::{H160, U256};
+use rayon::prelude::*;
+use crate::{Config, ExitReason};
+use crate::backend::Backend;
+use crate::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+
+/// Call transaction run by `transact_calls_parallel`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallTransaction {
+	/// Caller of the transaction.
+	pub caller: H160,
+	/// Address called.
+	pub address: H160,
+	/// Value transferred.
+	pub value: U256,
+	/// Input data.
+	pub data: Vec<u8>,
+	/// Gas limit.
+	pub gas_limit: u64,
+}
+
+/// Run call transactions across threads, each on its own executor on top of
+/// the backend, returning their exit reason, return data and state in order.
+///
+/// This is only meaningful for independent transactions: none of them sees
+/// the changes of the others, which are all made against the backend state
+/// as it is. Running transactions touching the same accounts in parallel
+/// gives different results than running them one after the other.
+pub fn transact_calls_parallel<'backend, 'config, B: Backend + Sync>(
+	backend: &'backend B,
+	config: &'config Config,
+	transactions: &[CallTransaction],
+) -> Vec<(ExitReason, Vec<u8>, MemoryStackState<'backend, 'config, B>)> {
+	transactions.par_iter().map(|transaction| {
+		let metadata = StackSubstateMetadata::new(transaction.gas_limit, config);
+		let state = MemoryStackState::new(metadata, backend);
+		let mut executor = StackExecutor::new(state, config);
+
+		let (reason, out) = executor.transact_call(
+			transaction.caller,
+			transaction.address,
+			transaction.value,
+			transaction.data.clone(),
+			transaction.gas_limit,
+		);
+		(reason, out, executor.into_state())
+	}).collect()
+}
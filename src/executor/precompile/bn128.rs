@@ -0,0 +1,114 @@
+//! The alt_bn128 precompiles of EIP-196 and EIP-197, on top of the pairing
+//! implementation of `substrate-bn`.
+
+use core::cmp::min;
+use alloc::vec::Vec;
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use crate::{Context, ExitError, ExitSucceed};
+use super::{fixed_cost, PrecompileResult};
+
+/// Input padded with zeros, or truncated, to `N` bytes.
+fn padded<const N: usize>(input: &[u8]) -> [u8; N] {
+	let mut out = [0u8; N];
+	let len = min(input.len(), N);
+	out[..len].copy_from_slice(&input[..len]);
+	out
+}
+
+/// Error of alt_bn128 input that is not a point of the curve.
+fn invalid_point() -> ExitError {
+	ExitError::Other("invalid alt_bn128 point".into())
+}
+
+/// Element of the base field of alt_bn128, failing if not below the modulus.
+fn read_fq(input: &[u8]) -> Result<Fq, ExitError> {
+	Fq::from_slice(&input[..32]).map_err(|_| invalid_point())
+}
+
+/// Point of alt_bn128, with the point at infinity encoded as zeros.
+fn read_g1(input: &[u8]) -> Result<G1, ExitError> {
+	let x = read_fq(&input[..32])?;
+	let y = read_fq(&input[32..64])?;
+	if x == Fq::zero() && y == Fq::zero() {
+		Ok(G1::zero())
+	} else {
+		Ok(AffineG1::new(x, y).map_err(|_| invalid_point())?.into())
+	}
+}
+
+/// Point of the twist of alt_bn128, with the imaginary part of each
+/// coordinate first, and the point at infinity encoded as zeros. Fails on
+/// points not of the subgroup of order r.
+fn read_g2(input: &[u8]) -> Result<G2, ExitError> {
+	let x = Fq2::new(read_fq(&input[32..64])?, read_fq(&input[..32])?);
+	let y = Fq2::new(read_fq(&input[96..128])?, read_fq(&input[64..96])?);
+	if x == Fq2::zero() && y == Fq2::zero() {
+		Ok(G2::zero())
+	} else {
+		Ok(AffineG2::new(x, y).map_err(|_| invalid_point())?.into())
+	}
+}
+
+/// Encoding of a point of alt_bn128, with the point at infinity as zeros.
+fn write_g1(point: G1) -> Result<[u8; 64], ExitError> {
+	let mut output = [0u8; 64];
+	if let Some(point) = AffineG1::from_jacobian(point) {
+		point.x().to_big_endian(&mut output[..32]).map_err(|_| invalid_point())?;
+		point.y().to_big_endian(&mut output[32..]).map_err(|_| invalid_point())?;
+	}
+	Ok(output)
+}
+
+/// ECADD precompile, at address 0x06 (EIP-196), adding two points of the
+/// alt_bn128 curve. Costs 150 (EIP-1108). The input is padded with zeros, or
+/// truncated, to 128 bytes, and the point at infinity is encoded as zeros.
+/// Fails on coordinates that are not of a point of the curve.
+pub fn ec_add(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	let cost = fixed_cost(150, target_gas)?;
+	let input = padded::<128>(input);
+	let output = write_g1(read_g1(&input[..64])? + read_g1(&input[64..])?)?;
+
+	Ok((ExitSucceed::Returned, output.to_vec(), cost))
+}
+
+/// ECMUL precompile, at address 0x07 (EIP-196), multiplying a point of the
+/// alt_bn128 curve by a scalar. Costs 6000 (EIP-1108). The input is padded
+/// with zeros, or truncated, to 96 bytes. Fails on coordinates that are not
+/// of a point of the curve.
+pub fn ec_mul(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	let cost = fixed_cost(6000, target_gas)?;
+	let input = padded::<96>(input);
+	let point = read_g1(&input[..64])?;
+	let scalar = Fr::from_slice(&input[64..]).map_err(|_| invalid_point())?;
+	let output = write_g1(point * scalar)?;
+
+	Ok((ExitSucceed::Returned, output.to_vec(), cost))
+}
+
+/// ECPAIRING precompile, at address 0x08 (EIP-197), checking that the product
+/// of the optimal ate pairings of pairs of points of the alt_bn128 curve and
+/// its twist is one, returning the result as a word. Costs `45000 + 34000`
+/// per pair (EIP-1108). Fails on inputs whose length is not a multiple of
+/// 192, and on points not of the curve, or not of the subgroup of the twist.
+pub fn ec_pairing(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	let pairs = input.len() / 192;
+	if pairs * 192 != input.len() {
+		return Err(ExitError::Other("invalid ecpairing input length".into()))
+	}
+
+	let pairs = pairs as u64;
+	let cost = pairs.checked_mul(34000)
+		.and_then(|cost| cost.checked_add(45000))
+		.ok_or(ExitError::OutOfGas)?;
+	let cost = fixed_cost(cost, target_gas)?;
+
+	let points = input.chunks(192)
+		.map(|pair| Ok((read_g1(&pair[..64])?, read_g2(&pair[64..])?)))
+		.collect::<Result<Vec<_>, ExitError>>()?;
+
+	let mut output = [0u8; 32];
+	if bn::pairing_batch(&points) == Gt::one() {
+		output[31] = 1;
+	}
+	Ok((ExitSucceed::Returned, output.to_vec(), cost))
+}
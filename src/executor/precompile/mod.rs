@@ -0,0 +1,245 @@
+//! Standard precompiles, to be dispatched from the precompile callback of
+//! `StackExecutor::new_with_precompile`.
+
+#[cfg(feature = "bn128")]
+mod bn128;
+
+#[cfg(feature = "bn128")]
+pub use self::bn128::{ec_add, ec_mul, ec_pairing};
+
+use alloc::{vec, vec::Vec};
+use primitive_types::H160;
+use sha2::Digest;
+use crate::{Context, ExitError, ExitSucceed};
+
+/// Output and gas cost of a precompile, as returned by the precompile
+/// callback of the stack executor.
+pub type PrecompileResult = Result<(ExitSucceed, Vec<u8>, u64), ExitError>;
+
+/// Address of the SHA256 precompile.
+pub const SHA256_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
+]);
+/// Address of the IDENTITY precompile.
+pub const IDENTITY_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4,
+]);
+/// Address of the ECADD precompile (EIP-196).
+pub const ECADD_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
+]);
+/// Address of the ECMUL precompile (EIP-196).
+pub const ECMUL_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7,
+]);
+/// Address of the ECPAIRING precompile (EIP-197).
+pub const ECPAIRING_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8,
+]);
+/// Address of the BLAKE2F precompile (EIP-152).
+pub const BLAKE2F_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9,
+]);
+
+/// Length of the input of the BLAKE2F precompile: rounds, state, message,
+/// offset counters and final block flag.
+pub const BLAKE2F_INPUT_LENGTH: usize = 4 + 64 + 128 + 16 + 1;
+
+const BLAKE2B_IV: [u64; 8] = [
+	0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+	0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+	[14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+	[11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+	[7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+	[9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+	[2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+	[12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+	[13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+	[6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+	[10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Cost of a precompile charging `base` plus `word` for every word of
+/// input, failing if it exceeds the target gas.
+fn linear_cost(input: &[u8], base: u64, word: u64, target_gas: Option<u64>) -> Result<u64, ExitError> {
+	let words = (input.len() as u64).div_ceil(32);
+	let cost = words.checked_mul(word)
+		.and_then(|cost| cost.checked_add(base))
+		.ok_or(ExitError::OutOfGas)?;
+
+	match target_gas {
+		Some(gas) if cost > gas => Err(ExitError::OutOfGas),
+		_ => Ok(cost),
+	}
+}
+
+/// Cost of a precompile charging `cost`, failing if it exceeds the target
+/// gas.
+fn fixed_cost(cost: u64, target_gas: Option<u64>) -> Result<u64, ExitError> {
+	match target_gas {
+		Some(gas) if cost > gas => Err(ExitError::OutOfGas),
+		_ => Ok(cost),
+	}
+}
+
+/// SHA256 precompile, at address 0x02. Costs `60 + 12` per word of input.
+pub fn sha256(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	let cost = linear_cost(input, 60, 12, target_gas)?;
+	let hash = sha2::Sha256::digest(input);
+
+	Ok((ExitSucceed::Returned, hash.to_vec(), cost))
+}
+
+/// IDENTITY precompile, at address 0x04, returning its input. Costs `15 + 3`
+/// per word of input.
+pub fn identity(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	let cost = linear_cost(input, 15, 3, target_gas)?;
+
+	Ok((ExitSucceed::Returned, input.to_vec(), cost))
+}
+
+/// BLAKE2F precompile, at address 0x09 (EIP-152), running the BLAKE2b
+/// compression function. Costs 1 per round. Fails on inputs that are not
+/// `BLAKE2F_INPUT_LENGTH` long or have a final block flag other than 0 or 1.
+pub fn blake2f(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+	if input.len() != BLAKE2F_INPUT_LENGTH {
+		return Err(ExitError::Other("invalid blake2f input length".into()))
+	}
+
+	let mut rounds = [0u8; 4];
+	rounds.copy_from_slice(&input[..4]);
+	let rounds = u32::from_be_bytes(rounds);
+	let cost = fixed_cost(u64::from(rounds), target_gas)?;
+
+	let read_u64 = |offset: usize| {
+		let mut word = [0u8; 8];
+		word.copy_from_slice(&input[offset..(offset + 8)]);
+		u64::from_le_bytes(word)
+	};
+	let mut h = [0u64; 8];
+	for (i, word) in h.iter_mut().enumerate() {
+		*word = read_u64(4 + i * 8);
+	}
+	let mut m = [0u64; 16];
+	for (i, word) in m.iter_mut().enumerate() {
+		*word = read_u64(68 + i * 8);
+	}
+	let t = [read_u64(196), read_u64(204)];
+	let f = match input[212] {
+		0 => false,
+		1 => true,
+		_ => return Err(ExitError::Other("invalid blake2f final block flag".into())),
+	};
+
+	blake2_compress(rounds, &mut h, &m, t, f);
+
+	let mut output = Vec::with_capacity(64);
+	for word in &h {
+		output.extend_from_slice(&word.to_le_bytes());
+	}
+	Ok((ExitSucceed::Returned, output, cost))
+}
+
+/// BLAKE2b mixing function.
+fn blake2_mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+	v[d] = (v[d] ^ v[a]).rotate_right(32);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(24);
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+	v[d] = (v[d] ^ v[a]).rotate_right(16);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// BLAKE2b compression function, with a configurable number of rounds.
+fn blake2_compress(rounds: u32, h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool) {
+	let mut v = [0u64; 16];
+	v[..8].copy_from_slice(h);
+	v[8..].copy_from_slice(&BLAKE2B_IV);
+	v[12] ^= t[0];
+	v[13] ^= t[1];
+	if f {
+		v[14] = !v[14];
+	}
+
+	for round in 0..rounds {
+		let s = &BLAKE2B_SIGMA[round as usize % 10];
+		blake2_mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+		blake2_mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+		blake2_mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+		blake2_mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+		blake2_mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+		blake2_mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+		blake2_mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+		blake2_mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+	}
+
+	for (i, word) in h.iter_mut().enumerate() {
+		*word ^= v[i] ^ v[i + 8];
+	}
+}
+
+/// Addresses of the standard precompiles implemented here, to be set with
+/// `StackExecutor::set_precompile_addresses`.
+pub fn standard_precompile_addresses() -> Vec<H160> {
+	let mut addresses = vec![SHA256_ADDRESS, IDENTITY_ADDRESS];
+	if cfg!(feature = "bn128") {
+		addresses.extend_from_slice(&[ECADD_ADDRESS, ECMUL_ADDRESS, ECPAIRING_ADDRESS]);
+	}
+	addresses.push(BLAKE2F_ADDRESS);
+	addresses
+}
+
+/// Dispatch a call to the standard precompiles implemented here, returning
+/// `None` for other addresses.
+pub fn standard_precompiles(
+	address: H160,
+	input: &[u8],
+	target_gas: Option<u64>,
+	context: &Context,
+) -> Option<PrecompileResult> {
+	if address == SHA256_ADDRESS {
+		Some(sha256(input, target_gas, context))
+	} else if address == IDENTITY_ADDRESS {
+		Some(identity(input, target_gas, context))
+	} else if address == BLAKE2F_ADDRESS {
+		Some(blake2f(input, target_gas, context))
+	} else {
+		bn128_precompiles(address, input, target_gas, context)
+	}
+}
+
+/// Dispatch a call to the alt_bn128 precompiles, only available with the
+/// `bn128` feature.
+#[cfg(feature = "bn128")]
+fn bn128_precompiles(
+	address: H160,
+	input: &[u8],
+	target_gas: Option<u64>,
+	context: &Context,
+) -> Option<PrecompileResult> {
+	if address == ECADD_ADDRESS {
+		Some(ec_add(input, target_gas, context))
+	} else if address == ECMUL_ADDRESS {
+		Some(ec_mul(input, target_gas, context))
+	} else if address == ECPAIRING_ADDRESS {
+		Some(ec_pairing(input, target_gas, context))
+	} else {
+		None
+	}
+}
+
+#[cfg(not(feature = "bn128"))]
+fn bn128_precompiles(
+	_address: H160,
+	_input: &[u8],
+	_target_gas: Option<u64>,
+	_context: &Context,
+) -> Option<PrecompileResult> {
+	None
+}
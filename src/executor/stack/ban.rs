@@ -0,0 +1,55 @@
+use crate::Opcode;
+use core::iter::FromIterator;
+
+/// Set of opcodes the executor refuses to run, failing the frame executing
+/// one of them with `ExitError::BannedOpcode`, as done by sandboxed or
+/// permissioned environments forbidding, for example, `SELFDESTRUCT`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpcodeBan {
+    banned: [bool; 256],
+}
+
+impl OpcodeBan {
+    /// Create an empty set, banning nothing.
+    pub fn new() -> Self {
+        Self {
+            banned: [false; 256],
+        }
+    }
+
+    /// Ban the given opcode.
+    pub fn ban(&mut self, opcode: Opcode) {
+        self.banned[opcode.as_usize()] = true;
+    }
+
+    /// Allow the given opcode again.
+    pub fn unban(&mut self, opcode: Opcode) {
+        self.banned[opcode.as_usize()] = false;
+    }
+
+    /// Whether the given opcode is banned.
+    pub fn is_banned(&self, opcode: Opcode) -> bool {
+        self.banned[opcode.as_usize()]
+    }
+
+    /// Whether no opcode is banned.
+    pub fn is_empty(&self) -> bool {
+        self.banned.iter().all(|banned| !banned)
+    }
+}
+
+impl Default for OpcodeBan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<Opcode> for OpcodeBan {
+    fn from_iter<I: IntoIterator<Item = Opcode>>(opcodes: I) -> Self {
+        let mut ban = Self::new();
+        for opcode in opcodes {
+            ban.ban(opcode);
+        }
+        ban
+    }
+}
@@ -0,0 +1,261 @@
+use crate::backend::Backend;
+use crate::gasometer::GasCost;
+use crate::{
+    CallScheme, Context, ExitReason, ExitSucceed, ExternalOperation, Machine, Memory, Opcode, Stack,
+};
+use alloc::vec::Vec;
+use core::cmp::min;
+use primitive_types::{H160, H256, U256};
+
+/// Opcode about to be executed, borrowing the machine running it.
+///
+/// Nothing is copied to build a step. Inspectors needing an owned copy of the
+/// stack and memory can take one with `machine.snapshot()`.
+pub struct Step<'a> {
+    /// Context of the call frame.
+    pub context: &'a Context,
+    /// Opcode about to be executed.
+    pub opcode: Opcode,
+    /// Machine of the call frame.
+    pub machine: &'a Machine,
+    /// Gas left in the call frame, before the opcode is charged, as in the
+    /// traces of geth. The gas given to a callee is the one of its first step.
+    pub gas: u64,
+}
+
+impl<'a> Step<'a> {
+    /// Program counter of the opcode.
+    pub fn position(&self) -> Result<usize, ExitReason> {
+        self.machine.position()
+    }
+
+    /// Stack before the opcode is executed.
+    pub fn stack(&self) -> &Stack {
+        self.machine.stack()
+    }
+
+    /// Memory before the opcode is executed.
+    pub fn memory(&self) -> &Memory {
+        self.machine.memory()
+    }
+
+    /// Scheme of the call made by the opcode, if it is a call.
+    pub fn call_scheme(&self) -> Option<CallScheme> {
+        CallScheme::from_opcode(self.opcode)
+    }
+
+    /// Value pushed by the opcode, if it is a PUSH. Immediates truncated by
+    /// the end of the code are read as the machine does.
+    pub fn push_value(&self) -> Option<H256> {
+        let n = self.opcode.is_push()? as usize;
+        let position = self.position().ok()?;
+        let code = self.machine.code();
+
+        let start = min(position + 1, code.len());
+        let slice = &code[start..min(position + 1 + n, code.len())];
+        let mut value = H256::default();
+        value[(32 - slice.len())..].copy_from_slice(slice);
+        Some(value)
+    }
+}
+
+/// Call or create frame about to be entered.
+pub struct Frame<'a> {
+    /// Caller of the frame.
+    pub caller: H160,
+    /// Address the frame runs as, or the address of the contract created.
+    pub address: H160,
+    /// Value transferred to the frame.
+    pub value: U256,
+    /// Input of a call, or init code of a create.
+    pub input: &'a [u8],
+    /// Whether the frame is a create.
+    pub is_create: bool,
+    /// Scheme of the call, or `None` for a create. The frame of a call
+    /// transaction is entered as a `CALL`.
+    pub scheme: Option<CallScheme>,
+}
+
+/// Observer of the opcodes run by a stack executor.
+pub trait Inspector {
+    /// Called before an opcode of any call frame is executed, with the state
+    /// it executes on.
+    fn step(&mut self, step: &Step, state: &dyn Backend);
+
+    /// Called when a call or create frame, including the one of the
+    /// transaction, is entered.
+    fn enter(&mut self, _frame: &Frame) {}
+
+    /// Called when the last entered frame exits, with its return data, or
+    /// the deployed code of a successful create, and the gas it used.
+    fn exit(&mut self, _reason: &ExitReason, _output: &[u8], _gas_used: u64) {}
+
+    /// Called when an external operation is recorded by the executor.
+    fn external_operation(&mut self, _op: &ExternalOperation) {}
+
+    /// Called when the dynamic gas cost of an opcode is charged, with the cost
+    /// as priced by the gasometer.
+    fn gas_cost(&mut self, _cost: &GasCost) {}
+}
+
+/// Call or create frame recorded by a `CallTracer`.
+#[derive(Clone, Debug)]
+pub struct CallNode {
+    /// Caller of the frame.
+    pub caller: H160,
+    /// Address the frame runs as, or the address of the contract created.
+    pub address: H160,
+    /// Value transferred to the frame.
+    pub value: U256,
+    /// Input of a call, or init code of a create.
+    pub input: Vec<u8>,
+    /// Whether the frame is a create.
+    pub is_create: bool,
+    /// Scheme of the call, or `None` for a create.
+    pub scheme: Option<CallScheme>,
+    /// Return data, or the deployed code of a successful create.
+    pub output: Vec<u8>,
+    /// Exit reason of the frame.
+    pub reason: ExitReason,
+    /// Gas used by the frame, including the gas of its sub-calls.
+    pub gas_used: u64,
+    /// Frames entered by this frame, in order.
+    pub calls: Vec<CallNode>,
+}
+
+/// Inspector recording the tree of call and create frames, with their
+/// inputs and outputs.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    calls: Vec<CallNode>,
+    entered: Vec<CallNode>,
+}
+
+impl CallTracer {
+    /// Frames recorded at the top level, normally the frame of the
+    /// transaction.
+    pub fn calls(&self) -> &[CallNode] {
+        &self.calls
+    }
+}
+
+impl Inspector for CallTracer {
+    fn step(&mut self, _step: &Step, _state: &dyn Backend) {}
+
+    fn enter(&mut self, frame: &Frame) {
+        self.entered.push(CallNode {
+            caller: frame.caller,
+            address: frame.address,
+            value: frame.value,
+            input: frame.input.to_vec(),
+            is_create: frame.is_create,
+            scheme: frame.scheme,
+            output: Vec::new(),
+            reason: ExitReason::Succeed(ExitSucceed::Stopped),
+            gas_used: 0,
+            calls: Vec::new(),
+        });
+    }
+
+    fn exit(&mut self, reason: &ExitReason, output: &[u8], gas_used: u64) {
+        if let Some(mut node) = self.entered.pop() {
+            node.reason = reason.clone();
+            node.output = output.to_vec();
+            node.gas_used = gas_used;
+
+            match self.entered.last_mut() {
+                Some(parent) => parent.calls.push(node),
+                None => self.calls.push(node),
+            }
+        }
+    }
+}
+
+/// Inspector recording, at each step, the words of memory changed since the
+/// previous step of the same frame, instead of the whole memory. Words added
+/// by memory growth are all reported, and the first step of a frame compares
+/// against an empty memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryDiffTracer {
+    deltas: Vec<Vec<(usize, U256)>>,
+    previous: Vec<Vec<u8>>,
+}
+
+impl MemoryDiffTracer {
+    /// Index and new value of the words changed before each step, in order
+    /// of the steps.
+    pub fn deltas(&self) -> &[Vec<(usize, U256)>] {
+        &self.deltas
+    }
+}
+
+impl Inspector for MemoryDiffTracer {
+    fn step(&mut self, step: &Step, _state: &dyn Backend) {
+        if self.previous.is_empty() {
+            self.previous.push(Vec::new());
+        }
+        let previous = match self.previous.last_mut() {
+            Some(previous) => previous,
+            None => return,
+        };
+
+        let memory = step.memory();
+        let data = memory.data();
+        let len = memory.effective_len().low_u64() as usize;
+        let mut delta = Vec::new();
+        let previous_len = previous.len();
+        previous.resize(len, 0);
+        for (index, word) in previous.chunks_mut(32).enumerate() {
+            let mut value = [0u8; 32];
+            let start = min(index * 32, data.len());
+            let end = min(index * 32 + 32, data.len());
+            value[..end - start].copy_from_slice(&data[start..end]);
+
+            if index * 32 >= previous_len || *word != value {
+                word.copy_from_slice(&value);
+                delta.push((index, U256::from_big_endian(&value)));
+            }
+        }
+        self.deltas.push(delta);
+    }
+
+    fn enter(&mut self, _frame: &Frame) {
+        self.previous.push(Vec::new());
+    }
+
+    fn exit(&mut self, _reason: &ExitReason, _output: &[u8], _gas_used: u64) {
+        self.previous.pop();
+    }
+}
+
+/// Inspector counting storage access patterns, for gas optimization analysis.
+///
+/// Slots are warm or cold as charged by the gasometer, that is, warm once
+/// accessed in the transaction under EIP-2929, and always cold without it.
+#[derive(Clone, Debug, Default)]
+pub struct StorageStats {
+    /// Number of SLOADs of a warm slot.
+    pub warm_loads: usize,
+    /// Number of SLOADs of a cold slot.
+    pub cold_loads: usize,
+    /// Number of SSTOREs writing the current value.
+    pub noop_stores: usize,
+}
+
+impl Inspector for StorageStats {
+    fn step(&mut self, _step: &Step, _state: &dyn Backend) {}
+
+    fn gas_cost(&mut self, cost: &GasCost) {
+        match *cost {
+            GasCost::SLoad { target_is_cold } => {
+                if target_is_cold {
+                    self.cold_loads += 1;
+                } else {
+                    self.warm_loads += 1;
+                }
+            }
+            GasCost::SStore { current, new, .. } if current == new => self.noop_stores += 1,
+            _ => (),
+        }
+    }
+}
@@ -1,14 +1,21 @@
+mod ban;
+mod inspector;
 mod state;
 
+pub use self::ban::OpcodeBan;
+pub use self::inspector::{
+    CallNode, CallTracer, Frame, Inspector, MemoryDiffTracer, Step, StorageStats,
+};
 pub use self::state::{MemoryStackState, MemoryStackSubstate, StackState};
 
-use crate::gasometer::{self, Gasometer};
+use crate::backend::{Backend, Log};
+use crate::gasometer::{self, Gasometer, MemoryCost, StorageTarget, TransactionCost};
 use crate::{
-    Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitSucceed, Handler, Opcode,
-    Runtime, Stack, Transfer,
+    is_eof, CallScheme, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitSucceed,
+    ExternalOperation, Handler, Machine, memory_expansion_cost, Opcode, Runtime, Stack, Transfer,
 };
-use alloc::{rc::Rc, vec::Vec};
-use core::{cmp::min, convert::Infallible};
+use alloc::{collections::BTreeSet, rc::Rc, vec::Vec};
+use core::{cmp::{min, max}, convert::Infallible, mem};
 use primitive_types::{H160, H256, U256};
 use sha3::{Digest, Keccak256};
 
@@ -18,18 +25,86 @@ pub enum StackExitKind {
     Failed,
 }
 
+/// Outcome of a transaction, as relevant for gas estimation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EstimateOutcome {
+    /// The transaction succeeded.
+    Succeed,
+    /// The transaction explicitly reverted, and would regardless of gas.
+    Revert,
+    /// The transaction ran out of gas, or failed after a sub-call given all
+    /// the gas it could forward ran out of it. Raising the gas limit may
+    /// help.
+    OutOfGas,
+    /// The transaction failed for other reasons.
+    Failed,
+}
+
+/// Summary of an executed transaction, as returned to clients.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-codec", derive(codec::Encode, codec::Decode))]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionResult {
+    /// Exit reason of the transaction.
+    pub exit_reason: ExitReason,
+    /// Return data of the transaction.
+    pub return_data: Vec<u8>,
+    /// Gas used by the transaction, after refunds.
+    pub gas_used: u64,
+    /// Gas refunded to the sender, already deducted from `gas_used`.
+    pub gas_refunded: u64,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+}
+
+/// Addresses and storage slots accessed in a substate (EIP-2929).
+#[derive(Clone, Debug, Default)]
+pub struct Accessed {
+    pub accessed_addresses: BTreeSet<H160>,
+    pub accessed_storage: BTreeSet<(H160, H256)>,
+}
+
+impl Accessed {
+    pub fn access_address(&mut self, address: H160) {
+        self.accessed_addresses.insert(address);
+    }
+
+    pub fn access_addresses<I>(&mut self, addresses: I)
+    where
+        I: Iterator<Item = H160>,
+    {
+        self.accessed_addresses.extend(addresses);
+    }
+
+    pub fn access_storages<I>(&mut self, storages: I)
+    where
+        I: Iterator<Item = (H160, H256)>,
+    {
+        self.accessed_storage.extend(storages);
+    }
+}
+
+#[derive(Clone)]
 pub struct StackSubstateMetadata<'config> {
     gasometer: Gasometer<'config>,
     is_static: bool,
     depth: Option<usize>,
+    accessed: Option<Accessed>,
 }
 
 impl<'config> StackSubstateMetadata<'config> {
     pub fn new(gas_limit: u64, config: &'config Config) -> Self {
+        let accessed = if config.increase_state_access_gas {
+            Some(Accessed::default())
+        } else {
+            None
+        };
+
         Self {
             gasometer: Gasometer::new(gas_limit, config),
             is_static: false,
             depth: None,
+            accessed,
         }
     }
 
@@ -38,6 +113,17 @@ impl<'config> StackSubstateMetadata<'config> {
         self.gasometer
             .record_refund(other.gasometer.refunded_gas())?;
 
+        if let (Some(mut other_accessed), Some(self_accessed)) =
+            (other.accessed, self.accessed.as_mut())
+        {
+            self_accessed
+                .accessed_addresses
+                .append(&mut other_accessed.accessed_addresses);
+            self_accessed
+                .accessed_storage
+                .append(&mut other_accessed.accessed_storage);
+        }
+
         Ok(())
     }
 
@@ -59,12 +145,53 @@ impl<'config> StackSubstateMetadata<'config> {
                 None => Some(0),
                 Some(n) => Some(n + 1),
             },
+            accessed: self.accessed.as_ref().map(|_| Accessed::default()),
+        }
+    }
+
+    /// Depth of the call frame, `Some(0)` for the frame of the transaction
+    /// and `None` outside of it.
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    pub fn accessed(&self) -> &Option<Accessed> {
+        &self.accessed
+    }
+
+    pub fn access_address(&mut self, address: H160) {
+        if let Some(accessed) = &mut self.accessed {
+            accessed.access_address(address)
+        }
+    }
+
+    pub fn access_addresses<I>(&mut self, addresses: I)
+    where
+        I: Iterator<Item = H160>,
+    {
+        if let Some(accessed) = &mut self.accessed {
+            accessed.access_addresses(addresses);
+        }
+    }
+
+    pub fn access_storage(&mut self, address: H160, key: H256) {
+        if let Some(accessed) = &mut self.accessed {
+            accessed.accessed_storage.insert((address, key));
+        }
+    }
+
+    pub fn access_storages<I>(&mut self, storages: I)
+    where
+        I: Iterator<Item = (H160, H256)>,
+    {
+        if let Some(accessed) = &mut self.accessed {
+            accessed.access_storages(storages);
         }
     }
 }
 
 /// Stack-based executor.
-pub struct StackExecutor<'config, 'precompile, S> {
+pub struct StackExecutor<'config, 'precompile, 'inspector, S> {
     config: &'config Config,
     precompile: Option<
         &'precompile mut dyn FnMut(
@@ -75,15 +202,39 @@ pub struct StackExecutor<'config, 'precompile, S> {
         )
             -> Option<Result<(ExitSucceed, Vec<u8>, u64), ExitError>>,
     >,
+    inspector: Option<&'inspector mut dyn Inspector>,
+    opcode_ban: OpcodeBan,
+    memory_expansion_cost: Option<fn(usize, usize) -> u64>,
+    precompile_addresses: Vec<H160>,
+    access_list: Accessed,
+    access_list_gas: u64,
+    transaction_logs: usize,
+    out_of_gas: bool,
+    return_data_size: usize,
+    call_count: u64,
+    memory_sizes: Vec<usize>,
+    memory_size: usize,
     state: S,
 }
 
-impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'precompile, S> {
+impl<'config, 'precompile, 'inspector, S: StackState<'config>> StackExecutor<'config, 'precompile, 'inspector, S> {
     /// Create a new stack-based executor.
     pub fn new(state: S, config: &'config Config) -> Self {
         Self {
             config,
             precompile: None,
+            inspector: None,
+            opcode_ban: OpcodeBan::new(),
+            memory_expansion_cost: None,
+            precompile_addresses: Vec::new(),
+            access_list: Accessed::default(),
+            access_list_gas: 0,
+            transaction_logs: 0,
+            out_of_gas: false,
+            return_data_size: 0,
+            call_count: 0,
+            memory_sizes: Vec::new(),
+            memory_size: 0,
             state,
         }
     }
@@ -103,10 +254,45 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         Self {
             config,
             precompile: Some(precompile),
+            inspector: None,
+            opcode_ban: OpcodeBan::new(),
+            memory_expansion_cost: None,
+            precompile_addresses: Vec::new(),
+            access_list: Accessed::default(),
+            access_list_gas: 0,
+            transaction_logs: 0,
+            out_of_gas: false,
+            return_data_size: 0,
+            call_count: 0,
+            memory_sizes: Vec::new(),
+            memory_size: 0,
             state,
         }
     }
 
+    /// Set an inspector observing every opcode run by the executor.
+    pub fn set_inspector(&mut self, inspector: &'inspector mut dyn Inspector) {
+        self.inspector = Some(inspector);
+    }
+
+    /// Set the opcodes the executor refuses to run.
+    pub fn set_opcode_ban(&mut self, ban: OpcodeBan) {
+        self.opcode_ban = ban;
+    }
+
+    /// Set the gas cost of expanding the memory from a size to another, in
+    /// words, instead of the standard one, as priced by the
+    /// `Handler::memory_expansion_cost` of the executor.
+    pub fn set_memory_expansion_cost(&mut self, cost: fn(usize, usize) -> u64) {
+        self.memory_expansion_cost = Some(cost);
+    }
+
+    /// Set the addresses of the precompiles, warm from the start of every
+    /// transaction (EIP-2929).
+    pub fn set_precompile_addresses(&mut self, addresses: &[H160]) {
+        self.precompile_addresses = addresses.to_vec();
+    }
+
     pub fn state(&self) -> &S {
         &self.state
     }
@@ -115,6 +301,30 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         &mut self.state
     }
 
+    /// Mark addresses as warm for the next transaction, as done for the
+    /// access list of an EIP-2930 transaction. The transaction is charged
+    /// `gas_access_list_address` for every address given, on top of its
+    /// intrinsic gas. Has no effect unless EIP-2929 is enabled.
+    pub fn warm_addresses(&mut self, addresses: &[H160]) {
+        self.access_list.access_addresses(addresses.iter().copied());
+        self.access_list_gas = self.access_list_gas.saturating_add(
+            (addresses.len() as u64).saturating_mul(self.config.gas_access_list_address),
+        );
+    }
+
+    /// Mark storage slots as warm for the next transaction, as done for the
+    /// access list of an EIP-2930 transaction. The transaction is charged
+    /// `gas_access_list_storage_key` for every slot given, on top of its
+    /// intrinsic gas. The addresses of the slots are not warmed, nor charged
+    /// for, they are given with `warm_addresses`. Has no effect unless
+    /// EIP-2929 is enabled.
+    pub fn warm_storage(&mut self, entries: &[(H160, H256)]) {
+        self.access_list.access_storages(entries.iter().copied());
+        self.access_list_gas = self.access_list_gas.saturating_add(
+            (entries.len() as u64).saturating_mul(self.config.gas_access_list_storage_key),
+        );
+    }
+
     pub fn into_state(self) -> S {
         self.state
     }
@@ -135,10 +345,20 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
 
     /// Execute the runtime until it returns.
     pub fn execute(&mut self, runtime: &mut Runtime) -> ExitReason {
-        match runtime.run(self) {
+        self.memory_sizes.push(0);
+        let reason = match runtime.run(self) {
             Capture::Exit(s) => s,
             Capture::Trap(_) => unreachable!("Trap is Infallible"),
+        };
+        if let Some(size) = self.memory_sizes.pop() {
+            self.memory_size -= size;
         }
+        reason
+    }
+
+    /// Number of sub-calls and sub-creates run by the transaction so far.
+    pub fn call_count(&self) -> u64 {
+        self.call_count
     }
 
     /// Get remaining gas.
@@ -154,17 +374,21 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         init_code: Vec<u8>,
         gas_limit: u64,
     ) -> ExitReason {
+        let access_list_gas = self.start_transaction();
+        if let Err(e) = self.validate_init_code(&init_code) {
+            return e.into();
+        }
+        if let Err(e) = self.validate_transaction(caller, value, gas_limit) {
+            return e.into();
+        }
+
         let transaction_cost = gasometer::create_transaction_cost(&init_code);
-        match self
-            .state
-            .metadata_mut()
-            .gasometer
-            .record_transaction(transaction_cost)
-        {
-            Ok(()) => (),
-            Err(e) => return e.into(),
+        if let Err(e) = self.record_transaction(transaction_cost, access_list_gas) {
+            return e.into();
         }
 
+        self.warm_transaction_addresses(&[caller]);
+
         match self.create_inner(
             caller,
             CreateScheme::Legacy { caller },
@@ -173,7 +397,7 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             Some(gas_limit),
             false,
         ) {
-            Capture::Exit((s, _, _)) => s,
+            Capture::Exit((s, _, _)) => self.finalize(s),
             Capture::Trap(_) => unreachable!(),
         }
     }
@@ -187,16 +411,21 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         salt: H256,
         gas_limit: u64,
     ) -> ExitReason {
+        let access_list_gas = self.start_transaction();
+        if let Err(e) = self.validate_init_code(&init_code) {
+            return e.into();
+        }
+        if let Err(e) = self.validate_transaction(caller, value, gas_limit) {
+            return e.into();
+        }
+
         let transaction_cost = gasometer::create_transaction_cost(&init_code);
-        match self
-            .state
-            .metadata_mut()
-            .gasometer
-            .record_transaction(transaction_cost)
-        {
-            Ok(()) => (),
-            Err(e) => return e.into(),
+        if let Err(e) = self.record_transaction(transaction_cost, access_list_gas) {
+            return e.into();
         }
+
+        self.warm_transaction_addresses(&[caller]);
+
         let code_hash = H256::from_slice(Keccak256::digest(&init_code).as_slice());
 
         match self.create_inner(
@@ -211,7 +440,7 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             Some(gas_limit),
             false,
         ) {
-            Capture::Exit((s, _, _)) => s,
+            Capture::Exit((s, _, _)) => self.finalize(s),
             Capture::Trap(_) => unreachable!(),
         }
     }
@@ -225,23 +454,27 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         data: Vec<u8>,
         gas_limit: u64,
     ) -> (ExitReason, Vec<u8>) {
+        let access_list_gas = self.start_transaction();
+        if let Err(e) = self.validate_transaction(caller, value, gas_limit) {
+            return (e.into(), Vec::new());
+        }
+
         let transaction_cost = gasometer::call_transaction_cost(&data);
-        match self
-            .state
-            .metadata_mut()
-            .gasometer
-            .record_transaction(transaction_cost)
-        {
-            Ok(()) => (),
-            Err(e) => return (e.into(), Vec::new()),
+        if let Err(e) = self.record_transaction(transaction_cost, access_list_gas) {
+            return (e.into(), Vec::new());
         }
 
+        self.warm_transaction_addresses(&[caller, address]);
+
         self.state.inc_nonce(caller);
 
         let context = Context {
             caller,
             address,
+            tx_origin: self.origin(),
             apparent_value: value,
+            is_static: false,
+            depth: 0,
         };
 
         match self.call_inner(
@@ -253,23 +486,164 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             }),
             data,
             Some(gas_limit),
-            false,
+            CallScheme::Call,
             false,
             false,
             context,
         ) {
-            Capture::Exit((s, v)) => (s, v),
+            Capture::Exit((s, v)) => (self.finalize(s), v),
             Capture::Trap(_) => unreachable!(),
         }
     }
 
-    /// Get used gas for the current executor, given the price.
+    /// Reset what the executor tracks for a single transaction, before
+    /// starting one, returning the gas of its access list. Addresses and
+    /// storage slots accessed by previous transactions are cold again, except
+    /// those of the access list and the precompiles.
+    fn start_transaction(&mut self) -> u64 {
+        let access_list = mem::take(&mut self.access_list);
+        if let Some(accessed) = self.state.metadata_mut().accessed.as_mut() {
+            *accessed = access_list;
+        }
+        self.state
+            .metadata_mut()
+            .access_addresses(self.precompile_addresses.iter().copied());
+
+        self.transaction_logs = self.state.logs().len();
+        self.out_of_gas = false;
+        self.call_count = 0;
+        self.return_data_size = 0;
+        mem::take(&mut self.access_list_gas)
+    }
+
+    /// Record the intrinsic gas of a transaction, with the gas of its access
+    /// list (EIP-2930).
+    fn record_transaction(
+        &mut self,
+        transaction_cost: TransactionCost,
+        access_list_gas: u64,
+    ) -> Result<(), ExitError> {
+        let gasometer = &mut self.state.metadata_mut().gasometer;
+        gasometer.record_transaction(transaction_cost)?;
+        gasometer.record_cost(access_list_gas)
+    }
+
+    /// Finish a transaction that was executed, passing its exit reason
+    /// through. Transient storage and the addresses created by the
+    /// transaction (EIP-6780) do not outlive it.
+    fn finalize(&mut self, reason: ExitReason) -> ExitReason {
+        self.clear_transient_storage();
+        self.state.clear_created();
+        reason
+    }
+
+    /// Mark the addresses accessed by a transaction from its start as warm,
+    /// with the coinbase if enabled by the config (EIP-3651).
+    fn warm_transaction_addresses(&mut self, addresses: &[H160]) {
+        let coinbase = if self.config.warm_coinbase {
+            Some(self.state.block_coinbase())
+        } else {
+            None
+        };
+
+        self.state
+            .metadata_mut()
+            .access_addresses(addresses.iter().copied().chain(coinbase));
+    }
+
+    /// Check that the init code of a create transaction is within the limit
+    /// of the config.
+    fn validate_init_code(&self, init_code: &[u8]) -> Result<(), ExitError> {
+        match self.config.max_initcode_size {
+            Some(limit) if init_code.len() > limit => Err(ExitError::InitCodeLimit),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the caller is allowed to send a transaction and can pay its
+    /// value and maximum gas fee, as enabled by the config.
+    fn validate_transaction(
+        &self,
+        caller: H160,
+        value: U256,
+        gas_limit: u64,
+    ) -> Result<(), ExitError> {
+        if self.config.reject_sender_with_code && self.is_contract(caller) {
+            return Err(ExitError::SenderHasCode);
+        }
+
+        if !self.config.validate_upfront_cost {
+            return Ok(());
+        }
+
+        let upfront_cost = U256::from(gas_limit)
+            .checked_mul(self.state.gas_price())
+            .and_then(|fee| fee.checked_add(value))
+            .ok_or(ExitError::OutOfUpfrontFund)?;
+
+        if self.state.basic(caller).balance < upfront_cost {
+            return Err(ExitError::OutOfUpfrontFund);
+        }
+
+        Ok(())
+    }
+
+    /// Classify the exit reason of the last transaction run by this executor
+    /// for gas estimation, telling deterministic reverts apart from out of gas
+    /// failures. A failure after a sub-call ran out of gas only counts as out
+    /// of gas if the sub-call was given all the gas its caller could forward,
+    /// so that a higher limit would have given it more.
+    pub fn estimate_outcome(&self, reason: &ExitReason) -> EstimateOutcome {
+        match reason {
+            ExitReason::Succeed(_) => EstimateOutcome::Succeed,
+            ExitReason::Error(ExitError::OutOfGas) => EstimateOutcome::OutOfGas,
+            _ if self.out_of_gas => EstimateOutcome::OutOfGas,
+            ExitReason::Revert(_) => EstimateOutcome::Revert,
+            ExitReason::Error(_) | ExitReason::Fatal(_) => EstimateOutcome::Failed,
+        }
+    }
+
+    /// Get used gas for the current executor, given the price. Refunds of a
+    /// reverted or failed transaction are discarded with its substate, so
+    /// its full execution gas is charged.
     pub fn used_gas(&self) -> u64 {
-        self.state.metadata().gasometer.total_used_gas()
+        let gasometer = &self.state.metadata().gasometer;
+        let execution_gas = gasometer.total_used_gas()
             - min(
-                self.state.metadata().gasometer.total_used_gas() / 2,
-                self.state.metadata().gasometer.refunded_gas() as u64,
-            )
+                gasometer.total_used_gas() / self.config.max_refund_quotient,
+                gasometer.refunded_gas() as u64,
+            );
+
+        max(execution_gas, gasometer.floor_gas())
+    }
+
+    /// Summarize the last transaction run by the executor, given the exit
+    /// reason and return data returned by the `transact_*` function running
+    /// it. The summary is built here rather than from the `Runtime` of the
+    /// transaction: a runtime only sees its own frame, while the gas used
+    /// after refunds and the logs kept from sub-calls are only known to the
+    /// executor.
+    pub fn finish(&self, exit_reason: ExitReason, return_data: Vec<u8>) -> ExecutionResult {
+        let gas_used = self.used_gas();
+        let gas_refunded = self
+            .state
+            .metadata()
+            .gasometer
+            .total_used_gas()
+            .saturating_sub(gas_used);
+
+        ExecutionResult {
+            exit_reason,
+            return_data,
+            gas_used,
+            gas_refunded,
+            logs: self
+                .state
+                .logs()
+                .get(self.transaction_logs..)
+                .unwrap_or_default()
+                .to_vec(),
+        }
     }
 
     /// Get fee needed for the current executor, given the price.
@@ -290,20 +664,9 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
                 caller,
                 code_hash,
                 salt,
-            } => {
-                let mut hasher = Keccak256::new();
-                hasher.input(&[0xff]);
-                hasher.input(&caller[..]);
-                hasher.input(&salt[..]);
-                hasher.input(&code_hash[..]);
-                H256::from_slice(hasher.result().as_slice()).into()
-            }
+            } => crate::create2_address_from_code_hash(caller, salt, code_hash),
             CreateScheme::Legacy { caller } => {
-                let nonce = self.nonce(caller);
-                let mut stream = rlp::RlpStream::new_list(2);
-                stream.append(&caller);
-                stream.append(&nonce);
-                H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
+                crate::create_address(caller, self.nonce(caller))
             }
             CreateScheme::Fixed(naddress) => naddress,
         }
@@ -317,6 +680,46 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         init_code: Vec<u8>,
         target_gas: Option<u64>,
         take_l64: bool,
+    ) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Infallible> {
+        if self.inspector.is_none() {
+            return self.create_frame(caller, scheme, value, init_code, target_gas, take_l64);
+        }
+
+        let frame = Frame {
+            caller,
+            address: self.create_address(scheme),
+            value,
+            input: &init_code,
+            is_create: true,
+            scheme: None,
+        };
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.enter(&frame);
+        }
+        let address = frame.address;
+        let gas = self.state.metadata().gasometer.gas();
+
+        let capture = self.create_frame(caller, scheme, value, init_code, target_gas, take_l64);
+        let (reason, output) = match &capture {
+            Capture::Exit((reason, _, _)) if reason.is_succeed() => (reason, self.code(address)),
+            Capture::Exit((reason, _, out)) => (reason, out.clone()),
+            Capture::Trap(_) => unreachable!(),
+        };
+        let gas_used = gas.saturating_sub(self.state.metadata().gasometer.gas());
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.exit(reason, &output, gas_used);
+        }
+        capture
+    }
+
+    fn create_frame(
+        &mut self,
+        caller: H160,
+        scheme: CreateScheme,
+        value: U256,
+        init_code: Vec<u8>,
+        target_gas: Option<u64>,
+        take_l64: bool,
     ) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Infallible> {
         macro_rules! try_or_fail {
             ( $e:expr ) => {
@@ -327,12 +730,10 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             };
         }
 
-        fn l64(gas: u64) -> u64 {
-            gas - gas / 64
-        }
-
+        // The created frame is one deeper than the current one, and must be
+        // within the limit as the frame of a call is.
         if let Some(depth) = self.state.metadata().depth {
-            if depth > self.config.call_stack_limit {
+            if depth >= self.config.call_stack_limit {
                 return Capture::Exit((ExitError::CallTooDeep.into(), None, Vec::new()));
             }
         }
@@ -341,25 +742,11 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             return Capture::Exit((ExitError::OutOfFund.into(), None, Vec::new()));
         }
 
-        let after_gas = if take_l64 && self.config.call_l64_after_gas {
-            if self.config.estimate {
-                let initial_after_gas = self.state.metadata().gasometer.gas();
-                let diff = initial_after_gas - l64(initial_after_gas);
-                try_or_fail!(self.state.metadata_mut().gasometer.record_cost(diff));
-                self.state.metadata().gasometer.gas()
-            } else {
-                l64(self.state.metadata().gasometer.gas())
-            }
-        } else {
-            self.state.metadata().gasometer.gas()
-        };
-
-        let target_gas = target_gas.unwrap_or(after_gas);
-
-        let gas_limit = min(after_gas, target_gas);
-        try_or_fail!(self.state.metadata_mut().gasometer.record_cost(gas_limit));
+        let gas_limit = try_or_fail!(self.forward_gas(target_gas, take_l64));
+        let all_gas = target_gas.is_none_or(|target_gas| target_gas > gas_limit);
 
         let address = self.create_address(scheme);
+        self.state.metadata_mut().access_address(address);
         self.state.inc_nonce(caller);
 
         self.enter_substate(gas_limit, false);
@@ -376,12 +763,16 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             }
 
             self.state.reset_storage(address);
+            self.state.set_created(address);
         }
 
         let context = Context {
             address,
             caller,
+            tx_origin: self.origin(),
             apparent_value: value,
+            is_static: false,
+            depth: self.state.metadata().depth.unwrap_or_default(),
         };
         let transfer = Transfer {
             source: caller,
@@ -400,6 +791,11 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             self.state.inc_nonce(address);
         }
 
+        if is_eof(&init_code) {
+            let _ = self.exit_substate(StackExitKind::Failed);
+            return Capture::Exit((ExitError::InvalidEofCode.into(), None, Vec::new()));
+        }
+
         let mut runtime = Runtime::new(
             Rc::new(init_code),
             Rc::new(Vec::new()),
@@ -410,6 +806,18 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         let reason = self.execute(&mut runtime);
         log::debug!(target: "evm", "Create execution using address {}: {:?}", address, reason);
 
+        // Only the revert data of a create is returned to its caller.
+        let (reason, out) = match reason {
+            ExitReason::Revert(_) => {
+                let out = runtime.machine().return_value();
+                match self.record_return_data(out.len()) {
+                    Ok(()) => (reason, out),
+                    Err(e) => (e.into(), Vec::new()),
+                }
+            }
+            _ => (reason, Vec::new()),
+        };
+
         match reason {
             ExitReason::Succeed(s) => {
                 let out = runtime.machine().return_value();
@@ -445,17 +853,14 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
                 }
             }
             ExitReason::Error(e) => {
+                self.out_of_gas |= all_gas && e == ExitError::OutOfGas;
                 self.state.metadata_mut().gasometer.fail();
                 let _ = self.exit_substate(StackExitKind::Failed);
                 Capture::Exit((ExitReason::Error(e), None, Vec::new()))
             }
             ExitReason::Revert(e) => {
                 let _ = self.exit_substate(StackExitKind::Reverted);
-                Capture::Exit((
-                    ExitReason::Revert(e),
-                    None,
-                    runtime.machine().return_value(),
-                ))
+                Capture::Exit((ExitReason::Revert(e), None, out))
             }
             ExitReason::Fatal(e) => {
                 self.state.metadata_mut().gasometer.fail();
@@ -465,7 +870,157 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
         }
     }
 
+    /// Charge the gas forwarded to a sub-call or sub-create, the requested
+    /// gas, or all of it if none is, capped by EIP-150 to all but one 64th
+    /// of the gas left, which the caller retains.
+    fn forward_gas(&mut self, target_gas: Option<u64>, take_l64: bool) -> Result<u64, ExitError> {
+        let after_gas = if take_l64 && self.config.call_l64_after_gas {
+            let gas = self.state.metadata().gasometer.gas();
+            if self.config.estimate {
+                self.state
+                    .metadata_mut()
+                    .gasometer
+                    .record_cost(gas - gasometer::all_but_one_64th(gas))?;
+                self.state.metadata().gasometer.gas()
+            } else {
+                gasometer::all_but_one_64th(gas)
+            }
+        } else {
+            self.state.metadata().gasometer.gas()
+        };
+
+        let gas_limit = min(target_gas.unwrap_or(after_gas), after_gas);
+        self.state.metadata_mut().gasometer.record_cost(gas_limit)?;
+        Ok(gas_limit)
+    }
+
+    /// Account the return data of a sub-call about to exit, failing it if the
+    /// return data of the sub-calls of the transaction exceeds the limit of
+    /// the config.
+    fn record_return_data(&mut self, len: usize) -> Result<(), ExitError> {
+        if self.state.metadata().depth == Some(0) {
+            return Ok(());
+        }
+
+        let size = self.return_data_size.saturating_add(len);
+        if let Some(max) = self.config.max_total_return_data_size {
+            if size > max {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+        self.return_data_size = size;
+        Ok(())
+    }
+
+    /// Account a sub-call or sub-create about to start, failing it if the
+    /// sub-calls of the transaction exceed the limit of the config.
+    fn record_call(&mut self) -> Result<(), ExitError> {
+        let count = self.call_count.saturating_add(1);
+        if let Some(max) = self.config.max_call_count {
+            if count > max {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+        self.call_count = count;
+        Ok(())
+    }
+
+    /// Account the memory of the running frame expanding to cover the given
+    /// range, failing it if the memory of all the frames of the call stack
+    /// exceeds the limit of the config.
+    fn record_memory(&mut self, memory: MemoryCost) -> Result<(), ExitError> {
+        let max = match self.config.max_total_memory {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        if memory.len == U256::zero() {
+            return Ok(());
+        }
+
+        let end = memory.offset.saturating_add(memory.len);
+        let end = if end > U256::from(usize::MAX) {
+            usize::MAX
+        } else {
+            end.as_usize().div_ceil(32).saturating_mul(32)
+        };
+
+        let current = match self.memory_sizes.last_mut() {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        if end <= *current {
+            return Ok(());
+        }
+
+        let size = (self.memory_size - *current).saturating_add(end);
+        if size > max {
+            return Err(ExitError::OutOfGas);
+        }
+        *current = end;
+        self.memory_size = size;
+        Ok(())
+    }
+
     fn call_inner(
+        &mut self,
+        code_address: H160,
+        transfer: Option<Transfer>,
+        input: Vec<u8>,
+        target_gas: Option<u64>,
+        scheme: CallScheme,
+        take_l64: bool,
+        take_stipend: bool,
+        context: Context,
+    ) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+        let is_static = scheme == CallScheme::StaticCall;
+        if self.inspector.is_none() {
+            return self.call_frame(
+                code_address,
+                transfer,
+                input,
+                target_gas,
+                is_static,
+                take_l64,
+                take_stipend,
+                context,
+            );
+        }
+
+        let frame = Frame {
+            caller: context.caller,
+            address: context.address,
+            value: transfer.as_ref().map(|t| t.value).unwrap_or_default(),
+            input: &input,
+            is_create: false,
+            scheme: Some(scheme),
+        };
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.enter(&frame);
+        }
+        let gas = self.state.metadata().gasometer.gas();
+
+        let capture = self.call_frame(
+            code_address,
+            transfer,
+            input,
+            target_gas,
+            is_static,
+            take_l64,
+            take_stipend,
+            context,
+        );
+        let (reason, out) = match &capture {
+            Capture::Exit((reason, out)) => (reason, out),
+            Capture::Trap(_) => unreachable!(),
+        };
+        let gas_used = gas.saturating_sub(self.state.metadata().gasometer.gas());
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.exit(reason, out, gas_used);
+        }
+        capture
+    }
+
+    fn call_frame(
         &mut self,
         code_address: H160,
         transfer: Option<Transfer>,
@@ -485,28 +1040,11 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             };
         }
 
-        fn l64(gas: u64) -> u64 {
-            gas - gas / 64
-        }
-
-        let after_gas = if take_l64 && self.config.call_l64_after_gas {
-            if self.config.estimate {
-                let initial_after_gas = self.state.metadata().gasometer.gas();
-                let diff = initial_after_gas - l64(initial_after_gas);
-                try_or_fail!(self.state.metadata_mut().gasometer.record_cost(diff));
-                self.state.metadata().gasometer.gas()
-            } else {
-                l64(self.state.metadata().gasometer.gas())
-            }
-        } else {
-            self.state.metadata().gasometer.gas()
-        };
-
-        let target_gas = target_gas.unwrap_or(after_gas);
-        let mut gas_limit = min(target_gas, after_gas);
-
-        try_or_fail!(self.state.metadata_mut().gasometer.record_cost(gas_limit));
+        let mut gas_limit = try_or_fail!(self.forward_gas(target_gas, take_l64));
+        let all_gas = target_gas.is_none_or(|target_gas| target_gas > gas_limit);
 
+        // The stipend of value transfers is given on top of the gas charged
+        // to the caller, which only gets back what the callee leaves.
         if let Some(transfer) = transfer.as_ref() {
             if take_stipend && transfer.value != U256::zero() {
                 gas_limit = gas_limit.saturating_add(self.config.call_stipend);
@@ -540,6 +1078,7 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             .as_mut()
             .and_then(|e| e(code_address, &input, Some(gas_limit), &context))
         {
+            self.record_external_operation(ExternalOperation::PrecompileInvocation(code_address));
             return match ret {
                 Ok((s, out, cost)) => {
                     let _ = self.state.metadata_mut().gasometer.record_cost(cost);
@@ -553,23 +1092,45 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
             };
         }
 
+        if !self.is_contract(code_address) {
+            let _ = self.exit_substate(StackExitKind::Succeeded);
+            return Capture::Exit((ExitSucceed::Stopped.into(), Vec::new()));
+        }
+
+        if is_eof(&code) {
+            let _ = self.exit_substate(StackExitKind::Failed);
+            return Capture::Exit((ExitError::InvalidEofCode.into(), Vec::new()));
+        }
+
         let mut runtime = Runtime::new(Rc::new(code), Rc::new(input), context, self.config);
 
         let reason = self.execute(&mut runtime);
         log::debug!(target: "evm", "Call execution using address {}: {:?}", code_address, reason);
 
+        let (reason, out) = match reason {
+            ExitReason::Succeed(_) | ExitReason::Revert(_) => {
+                let out = runtime.machine().return_value();
+                match self.record_return_data(out.len()) {
+                    Ok(()) => (reason, out),
+                    Err(e) => (e.into(), Vec::new()),
+                }
+            }
+            _ => (reason, Vec::new()),
+        };
+
         match reason {
             ExitReason::Succeed(s) => {
                 let _ = self.exit_substate(StackExitKind::Succeeded);
-                Capture::Exit((ExitReason::Succeed(s), runtime.machine().return_value()))
+                Capture::Exit((ExitReason::Succeed(s), out))
             }
             ExitReason::Error(e) => {
+                self.out_of_gas |= all_gas && e == ExitError::OutOfGas;
                 let _ = self.exit_substate(StackExitKind::Failed);
                 Capture::Exit((ExitReason::Error(e), Vec::new()))
             }
             ExitReason::Revert(e) => {
                 let _ = self.exit_substate(StackExitKind::Reverted);
-                Capture::Exit((ExitReason::Revert(e), runtime.machine().return_value()))
+                Capture::Exit((ExitReason::Revert(e), out))
             }
             ExitReason::Fatal(e) => {
                 self.state.metadata_mut().gasometer.fail();
@@ -580,8 +1141,24 @@ impl<'config, 'precompile, S: StackState<'config>> StackExecutor<'config, 'preco
     }
 }
 
-impl<'config, 'precompile, S: StackState<'config>> Handler
-    for StackExecutor<'config, 'precompile, S>
+impl<'config, 'precompile, 'inspector, 'backend, B: Backend>
+    StackExecutor<'config, 'precompile, 'inspector, MemoryStackState<'backend, 'config, B>>
+{
+    /// Take a snapshot of the state between transactions, including its
+    /// accumulated account changes and logs.
+    pub fn snapshot(&mut self) -> MemoryStackState<'backend, 'config, B> {
+        self.state.snapshot()
+    }
+
+    /// Revert to a snapshot taken with `snapshot`, dropping the account
+    /// changes and logs of the transactions run since.
+    pub fn revert_to(&mut self, snapshot: MemoryStackState<'backend, 'config, B>) {
+        self.state = snapshot;
+    }
+}
+
+impl<'config, 'precompile, 'inspector, S: StackState<'config>> Handler
+    for StackExecutor<'config, 'precompile, 'inspector, S>
 {
     type CreateInterrupt = Infallible;
     type CreateFeedback = Infallible;
@@ -612,6 +1189,10 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         self.state.storage(address, index)
     }
 
+    fn transient_storage(&self, address: H160, index: H256) -> H256 {
+        self.state.transient_storage(address, index)
+    }
+
     fn original_storage(&self, address: H160, index: H256) -> H256 {
         self.state
             .original_storage(address, index)
@@ -630,6 +1211,14 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         U256::from(self.state.metadata().gasometer.gas())
     }
 
+    fn gas_spent(&self) -> U256 {
+        U256::from(self.state.metadata().gasometer.total_used_gas())
+    }
+
+    fn gas_refund(&self) -> i64 {
+        self.state.metadata().gasometer.refunded_gas()
+    }
+
     fn gas_price(&self) -> U256 {
         self.state.gas_price()
     }
@@ -651,6 +1240,9 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
     fn block_difficulty(&self) -> U256 {
         self.state.block_difficulty()
     }
+    fn block_randomness(&self) -> Option<H256> {
+        self.state.block_randomness()
+    }
     fn block_gas_limit(&self) -> U256 {
         self.state.block_gas_limit()
     }
@@ -667,6 +1259,20 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         Ok(())
     }
 
+    fn set_transient_storage(
+        &mut self,
+        address: H160,
+        index: H256,
+        value: H256,
+    ) -> Result<(), ExitError> {
+        self.state.set_transient_storage(address, index, value);
+        Ok(())
+    }
+
+    fn clear_transient_storage(&mut self) {
+        self.state.clear_transient_storage();
+    }
+
     fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
         self.state.log(address, topics, data);
         Ok(())
@@ -686,6 +1292,31 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         Ok(())
     }
 
+    fn transfer_balance(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+        let balance = self.balance(address);
+
+        self.state.transfer(Transfer {
+            source: address,
+            target,
+            value: balance,
+        })
+    }
+
+    fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+        match index {
+            Some(index) => self.state.is_storage_cold(address, index),
+            None => self.state.is_cold(address),
+        }
+    }
+
+    fn created_in_current_transaction(&self, address: H160) -> bool {
+        self.state.created(address)
+    }
+
+    fn pending_selfdestructs(&self) -> Vec<H160> {
+        self.state.pending_deletes().into_iter().collect()
+    }
+
     fn create(
         &mut self,
         caller: H160,
@@ -694,6 +1325,10 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         init_code: Vec<u8>,
         target_gas: Option<u64>,
     ) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
+        if let Err(e) = self.record_call() {
+            return Capture::Exit((e.into(), None, Vec::new()));
+        }
+
         self.create_inner(caller, scheme, value, init_code, target_gas, true)
     }
 
@@ -703,21 +1338,47 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
         transfer: Option<Transfer>,
         input: Vec<u8>,
         target_gas: Option<u64>,
-        is_static: bool,
+        scheme: CallScheme,
         context: Context,
     ) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
+        if let Err(e) = self.record_call() {
+            return Capture::Exit((e.into(), Vec::new()));
+        }
+
         self.call_inner(
             code_address,
             transfer,
             input,
             target_gas,
-            is_static,
+            scheme,
             true,
             true,
             context,
         )
     }
 
+    fn record_external_operation(&mut self, op: ExternalOperation) {
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.external_operation(&op);
+        }
+    }
+
+    fn step(&mut self, context: &Context, opcode: Opcode, machine: &Machine) {
+        if let Some(inspector) = self.inspector.as_mut() {
+            let step = Step {
+                context,
+                opcode,
+                machine,
+                gas: self.state.metadata().gasometer.gas(),
+            };
+            inspector.step(&step, &self.state);
+        }
+    }
+
+    fn memory_expansion_cost(&self, current_words: usize, new_words: usize) -> u64 {
+        self.memory_expansion_cost.unwrap_or(memory_expansion_cost)(current_words, new_words)
+    }
+
     #[inline]
     fn pre_validate(
         &mut self,
@@ -727,11 +1388,15 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
     ) -> Result<(), ExitError> {
         // log::trace!(target: "evm", "Running opcode: {:?}, Pre gas-left: {:?}", opcode, gasometer.gas());
 
+        if self.opcode_ban.is_banned(opcode) {
+            return Err(ExitError::BannedOpcode);
+        }
+
         if let Some(cost) = gasometer::static_opcode_cost(opcode) {
             self.state.metadata_mut().gasometer.record_cost(cost)?;
         } else {
             let is_static = self.state.metadata().is_static;
-            let (gas_cost, memory_cost) = gasometer::dynamic_opcode_cost(
+            let (gas_cost, target, memory_cost) = gasometer::dynamic_opcode_cost(
                 context.address,
                 opcode,
                 stack,
@@ -740,9 +1405,35 @@ impl<'config, 'precompile, S: StackState<'config>> Handler
                 self,
             )?;
 
+            // Priced through the handler, as the gasometer only records it. An
+            // invalid range fails recording it below.
+            let expansion = memory_cost
+                .and_then(|memory_cost| self.state.metadata().gasometer.memory_expansion(memory_cost).ok());
+            let expansion_cost = match expansion {
+                Some((current_words, new_words)) if new_words > current_words => {
+                    Handler::memory_expansion_cost(self, current_words, new_words)
+                }
+                _ => 0,
+            };
             let gasometer = &mut self.state.metadata_mut().gasometer;
 
-            gasometer.record_dynamic_cost(gas_cost, memory_cost)?;
+            gasometer.record_dynamic_cost_with(gas_cost, memory_cost, |_, _| expansion_cost)?;
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.gas_cost(&gas_cost);
+            }
+            if let Some(memory_cost) = memory_cost {
+                self.record_memory(memory_cost)?;
+            }
+
+            match target {
+                StorageTarget::Address(address) => {
+                    self.state.metadata_mut().access_address(address)
+                }
+                StorageTarget::Slot(address, key) => {
+                    self.state.metadata_mut().access_storage(address, key)
+                }
+                StorageTarget::None => (),
+            }
         }
 
         Ok(())
@@ -1,9 +1,9 @@
 use core::mem;
-use alloc::{vec::Vec, boxed::Box, collections::{BTreeMap, BTreeSet}};
+use alloc::{vec::Vec, boxed::Box, sync::Arc, collections::{BTreeMap, BTreeSet}};
 use primitive_types::{H160, H256, U256};
 use crate::{ExitError, Transfer};
 use crate::backend::{Basic, Log, Backend, Apply};
-use crate::executor::stack::StackSubstateMetadata;
+use crate::executor::stack::{Accessed, StackSubstateMetadata};
 
 #[derive(Clone, Debug)]
 struct MemoryStackAccount {
@@ -12,13 +12,19 @@ struct MemoryStackAccount {
 	pub reset: bool,
 }
 
+#[derive(Clone)]
 pub struct MemoryStackSubstate<'config> {
 	metadata: StackSubstateMetadata<'config>,
 	parent: Option<Box<MemoryStackSubstate<'config>>>,
-	logs: Vec<Log>,
+	/// Account, storage and deletion changes below the root substate, shared
+	/// with its snapshots.
+	base: Option<Arc<MemoryStackSubstate<'config>>>,
+	logs: Arc<Vec<Log>>,
 	accounts: BTreeMap<H160, MemoryStackAccount>,
 	storages: BTreeMap<(H160, H256), H256>,
+	transient_storages: BTreeMap<(H160, H256), H256>,
 	deletes: BTreeSet<H160>,
+	creates: BTreeSet<H160>,
 }
 
 impl<'config> MemoryStackSubstate<'config> {
@@ -26,10 +32,13 @@ impl<'config> MemoryStackSubstate<'config> {
 		Self {
 			metadata,
 			parent: None,
-			logs: Vec::new(),
+			base: None,
+			logs: Arc::new(Vec::new()),
 			accounts: BTreeMap::new(),
 			storages: BTreeMap::new(),
+			transient_storages: BTreeMap::new(),
 			deletes: BTreeSet::new(),
+			creates: BTreeSet::new(),
 		}
 	}
 
@@ -41,6 +50,12 @@ impl<'config> MemoryStackSubstate<'config> {
 		&mut self.metadata
 	}
 
+	/// Logs emitted so far in the substate, including those of its exited
+	/// children.
+	pub fn logs(&self) -> &[Log] {
+		&self.logs
+	}
+
 	/// Deconstruct the executor, return state to be applied. Panic if the
 	/// executor is not in the top-level substate.
 	#[must_use]
@@ -50,6 +65,7 @@ impl<'config> MemoryStackSubstate<'config> {
  		  impl IntoIterator<Item=Log>)
 	{
 		assert!(self.parent.is_none());
+		self.flatten();
 
 		let mut applies = Vec::<Apply<BTreeMap<H256, H256>>>::new();
 
@@ -94,17 +110,63 @@ impl<'config> MemoryStackSubstate<'config> {
 			applies.push(Apply::Delete { address });
 		}
 
-		(applies, self.logs)
+		let logs = Arc::try_unwrap(self.logs).unwrap_or_else(|logs| (*logs).clone());
+		(applies, logs)
+	}
+
+	/// Move the account, storage and deletion changes of the root substate
+	/// into a layer shared with its clones, so that cloning it is cheap.
+	/// Panic if the substate is not the root one.
+	pub fn freeze(&mut self) {
+		assert!(self.parent.is_none());
+
+		if self.accounts.is_empty() && self.storages.is_empty() && self.deletes.is_empty() {
+			return
+		}
+
+		let layer = Self {
+			// The metadata of a layer is not used.
+			metadata: self.metadata.spit_child(0, false),
+			parent: None,
+			base: self.base.take(),
+			logs: Arc::new(Vec::new()),
+			accounts: mem::take(&mut self.accounts),
+			storages: mem::take(&mut self.storages),
+			transient_storages: BTreeMap::new(),
+			deletes: mem::take(&mut self.deletes),
+			creates: BTreeSet::new(),
+		};
+		self.base = Some(Arc::new(layer));
+	}
+
+	/// Merge the shared layers below the substate back into it.
+	fn flatten(&mut self) {
+		if let Some(base) = self.base.take() {
+			let mut base = Arc::try_unwrap(base).unwrap_or_else(|base| (*base).clone());
+			base.flatten();
+			mem::swap(&mut base.metadata, &mut self.metadata);
+			let mut changes = mem::replace(self, base);
+			self.merge(&mut changes);
+		}
+	}
+
+	/// Substate the lookups of this one fall back to, its parent or, for the
+	/// root substate, its shared layers.
+	fn below(&self) -> Option<&Self> {
+		self.parent.as_deref().or(self.base.as_deref())
 	}
 
 	pub fn enter(&mut self, gas_limit: u64, is_static: bool) {
 		let mut entering = Self {
 			metadata: self.metadata.spit_child(gas_limit, is_static),
 			parent: None,
-			logs: Vec::new(),
+			base: None,
+			logs: Arc::new(Vec::new()),
 			accounts: BTreeMap::new(),
 			storages: BTreeMap::new(),
+			transient_storages: BTreeMap::new(),
 			deletes: BTreeSet::new(),
+			creates: BTreeSet::new(),
 		};
 		mem::swap(&mut entering, self);
 
@@ -115,8 +177,18 @@ impl<'config> MemoryStackSubstate<'config> {
 		let mut exited = *self.parent.take().expect("Cannot commit on root substate");
 		mem::swap(&mut exited, self);
 
+		self.merge(&mut exited);
 		self.metadata.swallow_commit(exited.metadata)?;
-		self.logs.append(&mut exited.logs);
+
+		Ok(())
+	}
+
+	/// Move the changes of a substate, except its metadata, on top of this
+	/// one.
+	fn merge(&mut self, exited: &mut Self) {
+		let logs = mem::take(&mut exited.logs);
+		let mut logs = Arc::try_unwrap(logs).unwrap_or_else(|logs| (*logs).clone());
+		Arc::make_mut(&mut self.logs).append(&mut logs);
 
 		let mut resets = BTreeSet::new();
 		for (address, account) in &exited.accounts {
@@ -136,9 +208,9 @@ impl<'config> MemoryStackSubstate<'config> {
 
 		self.accounts.append(&mut exited.accounts);
 		self.storages.append(&mut exited.storages);
+		self.transient_storages.append(&mut exited.transient_storages);
 		self.deletes.append(&mut exited.deletes);
-
-		Ok(())
+		self.creates.append(&mut exited.creates);
 	}
 
 	pub fn exit_revert(&mut self) -> Result<(), ExitError> {
@@ -146,7 +218,6 @@ impl<'config> MemoryStackSubstate<'config> {
 		mem::swap(&mut exited, self);
 
 		self.metadata.swallow_revert(exited.metadata)?;
-		self.logs.append(&mut exited.logs);
 
 		Ok(())
 	}
@@ -156,7 +227,6 @@ impl<'config> MemoryStackSubstate<'config> {
 		mem::swap(&mut exited, self);
 
 		self.metadata.swallow_discard(exited.metadata)?;
-		self.logs.append(&mut exited.logs);
 
 		Ok(())
 	}
@@ -164,8 +234,8 @@ impl<'config> MemoryStackSubstate<'config> {
 	fn known_account(&self, address: H160) -> Option<&MemoryStackAccount> {
 		if let Some(account) = self.accounts.get(&address) {
 			Some(account)
-		} else if let Some(parent) = self.parent.as_ref() {
-			parent.known_account(address)
+		} else if let Some(below) = self.below() {
+			below.known_account(address)
 		} else {
 			None
 		}
@@ -212,8 +282,20 @@ impl<'config> MemoryStackSubstate<'config> {
 			}
 		}
 
+		if let Some(below) = self.below() {
+			return below.known_storage(address, key)
+		}
+
+		None
+	}
+
+	pub fn known_transient_storage(&self, address: H160, key: H256) -> Option<H256> {
+		if let Some(value) = self.transient_storages.get(&(address, key)) {
+			return Some(*value)
+		}
+
 		if let Some(parent) = self.parent.as_ref() {
-			return parent.known_storage(address, key)
+			return parent.known_transient_storage(address, key)
 		}
 
 		None
@@ -226,8 +308,8 @@ impl<'config> MemoryStackSubstate<'config> {
 			}
 		}
 
-		if let Some(parent) = self.parent.as_ref() {
-			return parent.known_original_storage(address, key)
+		if let Some(below) = self.below() {
+			return below.known_original_storage(address, key)
 		}
 
 		None
@@ -238,8 +320,63 @@ impl<'config> MemoryStackSubstate<'config> {
 			return true
 		}
 
+		if let Some(below) = self.below() {
+			return below.deleted(address)
+		}
+
+		false
+	}
+
+	pub fn pending_deletes(&self) -> BTreeSet<H160> {
+		let mut deletes = self.below()
+			.map(|below| below.pending_deletes())
+			.unwrap_or_default();
+		deletes.extend(self.deletes.iter().copied());
+		deletes
+	}
+
+	pub fn modified_storage(&self) -> BTreeMap<(H160, H256), H256> {
+		let mut storages = self.below()
+			.map(|below| below.modified_storage())
+			.unwrap_or_default();
+		storages.extend(self.storages.iter().map(|(slot, value)| (*slot, *value)));
+		storages
+	}
+
+	pub fn accessed_storage(&self) -> BTreeSet<(H160, H256)> {
+		let mut storages = self.parent.as_ref()
+			.map(|parent| parent.accessed_storage())
+			.unwrap_or_default();
+		if let Some(accessed) = self.metadata.accessed() {
+			storages.extend(accessed.accessed_storage.iter().copied());
+		}
+		storages
+	}
+
+	pub fn is_cold(&self, address: H160) -> bool {
+		self.recursive_is_cold(&|a| a.accessed_addresses.contains(&address))
+	}
+
+	pub fn is_storage_cold(&self, address: H160, key: H256) -> bool {
+		self.recursive_is_cold(&|a| a.accessed_storage.contains(&(address, key)))
+	}
+
+	fn recursive_is_cold<F: Fn(&Accessed) -> bool>(&self, f: &F) -> bool {
+		let local_is_accessed = self.metadata.accessed().as_ref().map(f).unwrap_or(false);
+		if local_is_accessed {
+			false
+		} else {
+			self.parent.as_ref().map(|p| p.recursive_is_cold(f)).unwrap_or(true)
+		}
+	}
+
+	pub fn created(&self, address: H160) -> bool {
+		if self.creates.contains(&address) {
+			return true
+		}
+
 		if let Some(parent) = self.parent.as_ref() {
-			return parent.deleted(address)
+			return parent.created(address)
 		}
 
 		false
@@ -272,6 +409,17 @@ impl<'config> MemoryStackSubstate<'config> {
 		self.storages.insert((address, key), value);
 	}
 
+	pub fn set_transient_storage(&mut self, address: H160, key: H256, value: H256) {
+		self.transient_storages.insert((address, key), value);
+	}
+
+	pub fn clear_transient_storage(&mut self) {
+		self.transient_storages.clear();
+		if let Some(parent) = self.parent.as_mut() {
+			parent.clear_transient_storage();
+		}
+	}
+
 	pub fn reset_storage<B: Backend>(&mut self, address: H160, backend: &B) {
 		let mut removing = Vec::new();
 
@@ -289,7 +437,7 @@ impl<'config> MemoryStackSubstate<'config> {
 	}
 
 	pub fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
-		self.logs.push(Log {
+		Arc::make_mut(&mut self.logs).push(Log {
 			address, topics, data,
 		});
 	}
@@ -298,6 +446,17 @@ impl<'config> MemoryStackSubstate<'config> {
 		self.deletes.insert(address);
 	}
 
+	pub fn set_created(&mut self, address: H160) {
+		self.creates.insert(address);
+	}
+
+	pub fn clear_created(&mut self) {
+		self.creates.clear();
+		if let Some(parent) = self.parent.as_mut() {
+			parent.clear_created();
+		}
+	}
+
 	pub fn set_code<B: Backend>(&mut self, address: H160, code: Vec<u8>, backend: &B) {
 		self.account_mut(address, backend).code = Some(code);
 	}
@@ -356,12 +515,22 @@ pub trait StackState<'config>: Backend {
 
 	fn is_empty(&self, address: H160) -> bool;
 	fn deleted(&self, address: H160) -> bool;
+	fn created(&self, address: H160) -> bool;
+	fn pending_deletes(&self) -> BTreeSet<H160>;
+	fn is_cold(&self, address: H160) -> bool;
+	fn is_storage_cold(&self, address: H160, key: H256) -> bool;
+	fn transient_storage(&self, address: H160, key: H256) -> H256;
+	fn logs(&self) -> &[Log];
 
 	fn inc_nonce(&mut self, address: H160);
 	fn set_storage(&mut self, address: H160, key: H256, value: H256);
 	fn reset_storage(&mut self, address: H160);
+	fn set_transient_storage(&mut self, address: H160, key: H256, value: H256);
+	fn clear_transient_storage(&mut self);
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>);
 	fn set_deleted(&mut self, address: H160);
+	fn set_created(&mut self, address: H160);
+	fn clear_created(&mut self);
 	fn set_code(&mut self, address: H160, code: Vec<u8>);
 	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError>;
 	fn reset_balance(&mut self, address: H160);
@@ -381,6 +550,7 @@ impl<'backend, 'config, B: Backend> Backend for MemoryStackState<'backend, 'conf
 	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
 	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
 	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
 	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
 	fn chain_id(&self) -> U256 { self.backend.chain_id() }
 
@@ -449,6 +619,30 @@ impl<'backend, 'config, B: Backend> StackState<'config> for MemoryStackState<'ba
 		self.substate.deleted(address)
 	}
 
+	fn created(&self, address: H160) -> bool {
+		self.substate.created(address)
+	}
+
+	fn pending_deletes(&self) -> BTreeSet<H160> {
+		self.substate.pending_deletes()
+	}
+
+	fn is_cold(&self, address: H160) -> bool {
+		self.substate.is_cold(address)
+	}
+
+	fn is_storage_cold(&self, address: H160, key: H256) -> bool {
+		self.substate.is_storage_cold(address, key)
+	}
+
+	fn transient_storage(&self, address: H160, key: H256) -> H256 {
+		self.substate.known_transient_storage(address, key).unwrap_or_default()
+	}
+
+	fn logs(&self) -> &[Log] {
+		self.substate.logs()
+	}
+
 	fn inc_nonce(&mut self, address: H160) {
 		self.substate.inc_nonce(address, self.backend);
 	}
@@ -461,6 +655,14 @@ impl<'backend, 'config, B: Backend> StackState<'config> for MemoryStackState<'ba
 		self.substate.reset_storage(address, self.backend);
 	}
 
+	fn set_transient_storage(&mut self, address: H160, key: H256, value: H256) {
+		self.substate.set_transient_storage(address, key, value)
+	}
+
+	fn clear_transient_storage(&mut self) {
+		self.substate.clear_transient_storage()
+	}
+
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
 		self.substate.log(address, topics, data);
 	}
@@ -469,6 +671,14 @@ impl<'backend, 'config, B: Backend> StackState<'config> for MemoryStackState<'ba
 		self.substate.set_deleted(address)
 	}
 
+	fn set_created(&mut self, address: H160) {
+		self.substate.set_created(address)
+	}
+
+	fn clear_created(&mut self) {
+		self.substate.clear_created()
+	}
+
 	fn set_code(&mut self, address: H160, code: Vec<u8>) {
 		self.substate.set_code(address, code, self.backend)
 	}
@@ -503,6 +713,39 @@ impl<'backend, 'config, B: Backend> MemoryStackState<'backend, 'config, B> {
 		self.substate.deconstruct(self.backend)
 	}
 
+	/// Storage slots written so far, with their current value.
+	pub fn modified_storage(&self) -> BTreeMap<(H160, H256), H256> {
+		self.substate.modified_storage()
+	}
+
+	/// Storage slots accessed but not written so far, with their current
+	/// value. Accesses are only tracked with EIP-2929, and include the slots
+	/// of the transaction access list.
+	pub fn read_storage(&self) -> BTreeMap<(H160, H256), H256> {
+		let modified = self.substate.modified_storage();
+
+		self.substate.accessed_storage().into_iter()
+			.filter(|slot| !modified.contains_key(slot))
+			.map(|(address, key)| ((address, key), self.storage(address, key)))
+			.collect()
+	}
+
+	/// Snapshot of the state between transactions, to fork for running
+	/// different transactions from the same state. The account, storage and
+	/// deletion changes made so far are frozen into a layer shared by the
+	/// state and its snapshots, which copy an account or a slot only when
+	/// writing it, and the logs are shared until either side emits one. Only
+	/// the metadata, with the accesses of the last transaction, is copied.
+	/// Panic if the state is not in its top-level substate.
+	pub fn snapshot(&mut self) -> Self {
+		self.substate.freeze();
+
+		Self {
+			backend: self.backend,
+			substate: self.substate.clone(),
+		}
+	}
+
 	pub fn withdraw(&mut self, address: H160, value: U256) -> Result<(), ExitError> {
 		self.substate.withdraw(address, value, self.backend)
 	}
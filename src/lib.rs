@@ -13,3 +13,5 @@ pub use evm_gasometer as gasometer;
 
 pub mod executor;
 pub mod backend;
+#[cfg(feature = "statetest")]
+pub mod statetest;
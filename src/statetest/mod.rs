@@ -0,0 +1,196 @@
+//! # Ethereum state tests
+//!
+//! Runner for the JSON state test format used by `GeneralStateTests` of
+//! the `ethereum/tests` repository. Each post state of a test is executed
+//! through `StackExecutor` on top of `MemoryBackend`, and the resulting
+//! state root and logs hash are compared with the expected ones.
+
+mod types;
+
+pub use self::types::{StateTest, Env, Account, Transaction, PostState, Indexes};
+
+use alloc::{string::String, vec::Vec, collections::BTreeMap};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+use crate::Config;
+use crate::backend::{MemoryBackend, MemoryVicinity, MemoryAccount, ApplyBackend, Log};
+use crate::executor::{StackExecutor, MemoryStackState, StackSubstateMetadata};
+
+/// Outcome of running one post state of a state test.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StateTestOutcome {
+	/// Name of the test.
+	pub name: String,
+	/// Fork the post state is for.
+	pub fork: String,
+	/// Indexes of the executed transaction.
+	pub indexes: (usize, usize, usize),
+	/// Expected state root.
+	pub expected_hash: H256,
+	/// Actual state root after execution.
+	pub hash: H256,
+	/// Expected logs hash.
+	pub expected_logs: H256,
+	/// Actual logs hash of the execution.
+	pub logs: H256,
+}
+
+impl StateTestOutcome {
+	/// Whether the post state matched.
+	pub fn passed(&self) -> bool {
+		self.expected_hash == self.hash && self.expected_logs == self.logs
+	}
+}
+
+/// Get the config of a fork, by its name in the state tests.
+pub fn fork_config(name: &str) -> Option<Config> {
+	match name {
+		"Frontier" => Some(Config::frontier()),
+		"Istanbul" => Some(Config::istanbul()),
+		_ => None,
+	}
+}
+
+/// Parse a state test file, which maps test names to tests, and run all
+/// post states of the forks known by `fork_config`.
+pub fn run_json(json: &str) -> Result<Vec<StateTestOutcome>, serde_json::Error> {
+	let tests: BTreeMap<String, StateTest> = serde_json::from_str(json)?;
+
+	let mut outcomes = Vec::new();
+	for (name, test) in &tests {
+		outcomes.append(&mut run(name, test));
+	}
+	Ok(outcomes)
+}
+
+/// Run all post states of a state test for the forks known by
+/// `fork_config`.
+pub fn run(name: &str, test: &StateTest) -> Vec<StateTestOutcome> {
+	let mut outcomes = Vec::new();
+
+	for (fork, posts) in &test.post {
+		let config = match fork_config(fork) {
+			Some(config) => config,
+			None => continue,
+		};
+
+		for post in posts {
+			let (hash, logs) = run_post(test, post.indexes, &config);
+
+			outcomes.push(StateTestOutcome {
+				name: name.into(),
+				fork: fork.clone(),
+				indexes: (post.indexes.data, post.indexes.gas, post.indexes.value),
+				expected_hash: post.hash,
+				hash,
+				expected_logs: post.logs,
+				logs,
+			});
+		}
+	}
+
+	outcomes
+}
+
+/// Run the transaction selected by `indexes` on the test pre-state, and
+/// return the resulting state root and logs hash.
+pub fn run_post(test: &StateTest, indexes: Indexes, config: &Config) -> (H256, H256) {
+	let transaction = &test.transaction;
+	let data = transaction.data[indexes.data].clone();
+	let gas_limit = transaction.gas_limit[indexes.gas];
+	let value = transaction.value[indexes.value];
+
+	let vicinity = MemoryVicinity {
+		gas_price: transaction.gas_price,
+		origin: transaction.sender,
+		chain_id: U256::one(),
+		block_hashes: Vec::new(),
+		block_number: test.env.current_number,
+		block_coinbase: test.env.current_coinbase,
+		block_timestamp: test.env.current_timestamp,
+		block_difficulty: test.env.current_difficulty,
+		block_randomness: test.env.current_random,
+		block_gas_limit: test.env.current_gas_limit,
+	};
+	let mut backend = MemoryBackend::new(&vicinity, pre_state(&test.pre));
+	let mut logs = Vec::new();
+
+	if gas_limit <= U256::from(u64::MAX) {
+		let gas_limit = gas_limit.as_u64();
+		let metadata = StackSubstateMetadata::new(gas_limit, config);
+		let state = MemoryStackState::new(metadata, &backend);
+		let mut executor = StackExecutor::new(state, config);
+
+		let total_fee = vicinity.gas_price * gas_limit;
+		if executor.state_mut().withdraw(transaction.sender, total_fee).is_ok() {
+			match transaction.to {
+				Some(to) => {
+					let _ = executor.transact_call(transaction.sender, to, value, data, gas_limit);
+				},
+				None => {
+					let _ = executor.transact_create(transaction.sender, value, data, gas_limit);
+				},
+			}
+
+			let actual_fee = executor.fee(vicinity.gas_price);
+			executor.state_mut().deposit(vicinity.block_coinbase, actual_fee);
+			executor.state_mut().deposit(transaction.sender, total_fee - actual_fee);
+
+			let (values, applied_logs) = executor.into_state().deconstruct();
+			logs = applied_logs.into_iter().collect();
+			backend.apply(values, logs.clone(), !config.empty_considered_exists);
+		}
+	}
+
+	(state_root(backend.state()), logs_hash(&logs))
+}
+
+/// Convert a state test pre-state into memory backend accounts.
+pub fn pre_state(pre: &BTreeMap<H160, Account>) -> BTreeMap<H160, MemoryAccount> {
+	pre.iter().map(|(address, account)| {
+		let storage = account.storage.iter()
+			.filter(|(_, value)| !value.is_zero())
+			.map(|(key, value)| (u256_to_h256(*key), u256_to_h256(*value)))
+			.collect();
+
+		(*address, MemoryAccount {
+			nonce: account.nonce,
+			balance: account.balance,
+			storage,
+			code: account.code.clone(),
+		})
+	}).collect()
+}
+
+/// Compute the state root of memory backend accounts.
+pub fn state_root(state: &BTreeMap<H160, MemoryAccount>) -> H256 {
+	let accounts = state.iter().map(|(address, account)| {
+		let storage_root = ethereum::util::sec_trie_root(
+			account.storage.iter()
+				.filter(|(_, value)| **value != H256::default())
+				.map(|(key, value)| (key, rlp::encode(&U256::from_big_endian(&value[..]))))
+		);
+
+		let account = ethereum::Account {
+			nonce: account.nonce,
+			balance: account.balance,
+			storage_root,
+			code_hash: H256::from_slice(Keccak256::digest(&account.code).as_slice()),
+		};
+
+		(address, rlp::encode(&account))
+	});
+
+	ethereum::util::sec_trie_root(accounts)
+}
+
+/// Compute the logs hash, the keccak of the RLP encoded list of logs.
+pub fn logs_hash(logs: &[Log]) -> H256 {
+	H256::from_slice(Keccak256::digest(&rlp::encode_list(logs)).as_slice())
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+	let mut ret = H256::default();
+	value.to_big_endian(&mut ret[..]);
+	ret
+}
@@ -0,0 +1,129 @@
+use alloc::{string::String, vec::Vec, collections::BTreeMap};
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+
+/// A single state test, as found in `GeneralStateTests`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StateTest {
+	/// Block environment.
+	pub env: Env,
+	/// Accounts before the transaction.
+	pub pre: BTreeMap<H160, Account>,
+	/// Transaction template. Actual transactions are selected by the indexes
+	/// of each post state.
+	pub transaction: Transaction,
+	/// Expected post states, keyed by fork name.
+	pub post: BTreeMap<String, Vec<PostState>>,
+}
+
+/// Block environment of a state test.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Env {
+	/// Block coinbase.
+	pub current_coinbase: H160,
+	/// Block difficulty.
+	pub current_difficulty: U256,
+	/// Block randomness (PREVRANDAO), for tests of post-Merge forks.
+	#[serde(default)]
+	pub current_random: Option<H256>,
+	/// Block gas limit.
+	pub current_gas_limit: U256,
+	/// Block number.
+	pub current_number: U256,
+	/// Block timestamp.
+	pub current_timestamp: U256,
+	/// Hash of the previous block.
+	pub previous_hash: H256,
+}
+
+/// Pre-state account of a state test.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Account {
+	/// Account balance.
+	pub balance: U256,
+	/// Account code.
+	#[serde(deserialize_with = "deserialize_bytes")]
+	pub code: Vec<u8>,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Account storage.
+	pub storage: BTreeMap<U256, U256>,
+}
+
+/// Transaction template of a state test.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+	/// Possible transaction data.
+	#[serde(deserialize_with = "deserialize_bytes_list")]
+	pub data: Vec<Vec<u8>>,
+	/// Possible gas limits.
+	pub gas_limit: Vec<U256>,
+	/// Gas price.
+	pub gas_price: U256,
+	/// Transaction nonce.
+	pub nonce: U256,
+	/// Transaction sender.
+	pub sender: H160,
+	/// Transaction target. `None` for create transactions.
+	#[serde(deserialize_with = "deserialize_to")]
+	pub to: Option<H160>,
+	/// Possible transaction values.
+	pub value: Vec<U256>,
+}
+
+/// Expected post state of a state test.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PostState {
+	/// Expected state root.
+	pub hash: H256,
+	/// Expected logs hash.
+	pub logs: H256,
+	/// Indexes into the transaction template.
+	pub indexes: Indexes,
+}
+
+/// Indexes into the transaction template.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Indexes {
+	/// Index of the transaction data.
+	pub data: usize,
+	/// Index of the gas limit.
+	pub gas: usize,
+	/// Index of the transaction value.
+	pub value: usize,
+}
+
+fn decode_hex<E: Error>(value: &str) -> Result<Vec<u8>, E> {
+	let value = value.strip_prefix("0x").unwrap_or(value);
+	hex::decode(value).map_err(E::custom)
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+	let value = String::deserialize(deserializer)?;
+	decode_hex(&value)
+}
+
+fn deserialize_bytes_list<'de, D: Deserializer<'de>>(
+	deserializer: D
+) -> Result<Vec<Vec<u8>>, D::Error> {
+	let values = Vec::<String>::deserialize(deserializer)?;
+	values.iter().map(|value| decode_hex(value)).collect()
+}
+
+fn deserialize_to<'de, D: Deserializer<'de>>(
+	deserializer: D
+) -> Result<Option<H160>, D::Error> {
+	let value = String::deserialize(deserializer)?;
+	if value.is_empty() {
+		return Ok(None)
+	}
+
+	let bytes = decode_hex::<D::Error>(&value)?;
+	if bytes.len() != 20 {
+		return Err(D::Error::custom("invalid transaction target"))
+	}
+	Ok(Some(H160::from_slice(&bytes)))
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use std::rc::Rc;
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::executor::precompile::{standard_precompile_addresses, standard_precompiles};
+use evm::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::{AccessListRecorder, Capture, Config, Context, ExitError, ExitReason, ExitSucceed, Runtime};
+use primitive_types::{H160, H256};
+use common::*;
+
+fn used_gas(code: &str, addresses: &[H160], storage: &[(H160, H256)]) -> u64 {
+	run(vec![(address(1), contract(code))], addresses, storage)
+}
+
+fn run(
+	accounts: Vec<(H160, MemoryAccount)>,
+	addresses: &[H160],
+	storage: &[(H160, H256)],
+) -> u64 {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(accounts));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	stack.warm_addresses(addresses);
+	stack.warm_storage(storage);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	100_000 - stack.gas()
+}
+
+#[test]
+fn warm_storage_reduces_first_sload() {
+	// PUSH1 0x00 SLOAD
+	let code = "600054";
+	assert_eq!(used_gas(code, &[], &[]), 21000 + 3 + 2100);
+	// The access list is charged 1900 for every slot.
+	assert_eq!(used_gas(code, &[], &[(address(1), H256::zero())]), 21000 + 1900 + 3 + 100);
+	// Warming another contract's slot has no effect.
+	assert_eq!(used_gas(code, &[], &[(address(2), H256::zero())]), 21000 + 1900 + 3 + 2100);
+}
+
+#[test]
+fn warm_address_reduces_first_access() {
+	// PUSH1 0x09 BALANCE PUSH1 0x09 BALANCE
+	let code = "600931600931";
+	assert_eq!(used_gas(code, &[], &[]), 21000 + 3 + 2600 + 3 + 100);
+	// The access list is charged 2400 for every address.
+	assert_eq!(used_gas(code, &[address(9)], &[]), 21000 + 2400 + 3 + 100 + 3 + 100);
+}
+
+#[test]
+fn access_list_is_charged_as_intrinsic_gas() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("00"))]));
+	let access_list = vec![
+		(address(1), vec![H256::zero(), H256::repeat_byte(1)]),
+		(address(2), Vec::new()),
+	];
+	let intrinsic_gas = config.intrinsic_gas(false, &[], &access_list);
+
+	let transact = |gas_limit: u64| {
+		let mut stack = executor(&backend, gas_limit, &config);
+		stack.warm_addresses(&[address(1), address(2)]);
+		stack.warm_storage(&[(address(1), H256::zero()), (address(1), H256::repeat_byte(1))]);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), gas_limit);
+		(reason, gas_limit - stack.gas())
+	};
+
+	assert_eq!(transact(100_000), (ExitReason::Succeed(ExitSucceed::Stopped), intrinsic_gas));
+	assert_eq!(transact(intrinsic_gas - 1).0, ExitReason::Error(ExitError::OutOfGas));
+}
+
+#[test]
+fn accesses_are_cold_again_in_next_transaction() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	// PUSH1 0x00 SLOAD PUSH1 0x09 BALANCE
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("600054600931"))]));
+	let cold = 21000 + 3 + 2100 + 3 + 2600;
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let mut used_gas = |access_list: bool| {
+		// The access list only warms the transaction following it.
+		if access_list {
+			stack.warm_addresses(&[address(9)]);
+			stack.warm_storage(&[(address(1), H256::zero())]);
+		}
+		let gas = stack.gas();
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		gas - stack.gas()
+	};
+	assert_eq!(used_gas(false), cold);
+	assert_eq!(used_gas(false), cold);
+	assert_eq!(used_gas(true), cold + 2400 + 1900 - 2000 - 2500);
+	assert_eq!(used_gas(false), cold);
+}
+
+#[test]
+fn delegatecall_shares_accessed_storage() {
+	// PUSH1 0x00 SLOAD POP, DELEGATECALL address 2, POP, PUSH1 0x01 SLOAD POP
+	let parent = contract("600054506000600060006000600261fffff45060015450");
+	// PUSH1 0x00 SLOAD POP PUSH1 0x01 SLOAD POP
+	let child = contract("600054506001545000");
+	let parent_gas = 21000 + (3 + 2100 + 2) + 6 * 3 + 2600 + 2 + 3 + 2;
+
+	// The slot loaded by the parent is warm in the child, and the slot
+	// loaded by the child is warm in the parent afterwards.
+	let child_gas = 3 + 100 + 2 + 3 + 2100 + 2;
+	assert_eq!(
+		run(vec![(address(1), parent.clone()), (address(2), child)], &[], &[]),
+		parent_gas + child_gas + 100,
+	);
+
+	// Accesses of a reverted frame are reverted too.
+	// PUSH1 0x00 SLOAD POP PUSH1 0x01 SLOAD POP PUSH1 0x00 PUSH1 0x00 REVERT
+	let reverting_child = contract("600054506001545060006000fd");
+	assert_eq!(
+		run(vec![(address(1), parent), (address(2), reverting_child)], &[], &[]),
+		parent_gas + child_gas + 3 + 3 + 2100,
+	);
+}
+
+#[test]
+fn warm_coinbase_reduces_first_call() {
+	// CALL the coinbase with no gas, then STOP.
+	let code = "6000600060006000600063c014ba5e6000f100";
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(code))]));
+
+	let used_gas = |config: &Config| {
+		let mut stack = executor(&backend, 100_000, config);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		100_000 - stack.gas()
+	};
+
+	let mut config = Config::berlin();
+	assert_eq!(used_gas(&config), 21000 + 7 * 3 + 2600);
+	config.warm_coinbase = true;
+	assert_eq!(used_gas(&config), 21000 + 7 * 3 + 100);
+}
+
+#[test]
+fn precompiles_are_warm() {
+	// CALL the SHA256 precompile with no input, then STOP.
+	let code = "60006000600060006000600261fffff100";
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(code))]));
+
+	let used_gas = |addresses: &[H160]| {
+		let metadata = StackSubstateMetadata::new(100_000, &config);
+		let mut precompile = standard_precompiles;
+		let mut stack = StackExecutor::new_with_precompile(
+			MemoryStackState::new(metadata, &backend),
+			&config,
+			&mut precompile,
+		);
+		stack.set_precompile_addresses(addresses);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		100_000 - stack.gas()
+	};
+
+	assert_eq!(used_gas(&standard_precompile_addresses()), 21000 + 7 * 3 + 100 + 60);
+	assert_eq!(used_gas(&[]), 21000 + 7 * 3 + 2600 + 60);
+}
+
+#[test]
+fn recorder_collects_accessed_slots_and_addresses() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	// SLOAD 2, SLOAD 1, SLOAD 2, BALANCE of address 3
+	let code = hex::decode("60025450600154506002545060033150").unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+	let mut recorder = AccessListRecorder::new(stack);
+
+	let reason = match runtime.run(&mut recorder) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => unreachable!(),
+	};
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(recorder.access_list(), vec![
+		(address(1), vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]),
+		(address(3), Vec::new()),
+	]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::{H256, U256};
+use common::*;
+
+/// The hash the backend knows for the block of the given number.
+fn hash(number: u64) -> H256 {
+	H256::from_low_u64_be(0xb10c_0000 + number)
+}
+
+/// BLOCKHASH of the given number at block 1000, with a backend knowing the
+/// hashes of the 300 blocks before it, more than the window.
+fn block_hash(number: u64) -> H256 {
+	let config = Config::istanbul();
+	let mut vicinity = vicinity();
+	vicinity.block_number = U256::from(1000);
+	vicinity.block_hashes = (1..=300).map(|age| hash(1000 - age)).collect();
+	let code = format!("61{:04x}4060005260206000f3", number);
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(&code))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	H256::from_slice(&out)
+}
+
+#[test]
+fn block_hash_within_window() {
+	assert_eq!(block_hash(999), hash(999));
+	assert_eq!(block_hash(900), hash(900));
+	assert_eq!(block_hash(744), hash(744));
+}
+
+#[test]
+fn block_hash_outside_window_is_zero() {
+	// The backend knows the hash of block 743, but it is 257 blocks old.
+	assert_eq!(block_hash(743), H256::zero());
+	assert_eq!(block_hash(700), H256::zero());
+	assert_eq!(block_hash(1000), H256::zero());
+	assert_eq!(block_hash(1001), H256::zero());
+}
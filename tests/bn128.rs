@@ -0,0 +1,161 @@
+#![cfg(feature = "bn128")]
+
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::executor::precompile::{ec_add, ec_mul, ec_pairing, standard_precompiles, ECPAIRING_ADDRESS};
+use evm::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::{Config, Context, ExitError, ExitReason, ExitSucceed};
+use common::*;
+
+fn context() -> Context {
+	Context {
+		address: address(2),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	}
+}
+
+const G1: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+	0000000000000000000000000000000000000000000000000000000000000002";
+const G1_DOUBLE: &str = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+	15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+const G1_TRIPLE: &str = "0769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf0\
+	2ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261";
+const G1_NEG: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+	30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+const G2: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+	1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+	090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+	12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+const G2_DOUBLE: &str = "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad79\
+	27dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9\
+	195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de152\
+	04bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e";
+const ZERO_G1: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+	0000000000000000000000000000000000000000000000000000000000000000";
+
+fn bn128_input(parts: &[&str]) -> Vec<u8> {
+	hex::decode(parts.concat()).unwrap()
+}
+
+fn word(value: u8) -> Vec<u8> {
+	let mut out = vec![0; 32];
+	out[31] = value;
+	out
+}
+
+#[test]
+fn ec_add_vectors_and_gas() {
+	let vectors = [
+		(bn128_input(&[G1, G1]), G1_DOUBLE),
+		(bn128_input(&[G1_DOUBLE, G1]), G1_TRIPLE),
+		(bn128_input(&[ZERO_G1, G1]), G1),
+		(bn128_input(&[G1, ZERO_G1]), G1),
+		(bn128_input(&[G1, G1_NEG]), ZERO_G1),
+		(Vec::new(), ZERO_G1),
+		// Truncated input is padded with zeros.
+		(bn128_input(&[G1]), G1),
+	];
+	for (input, output) in &vectors {
+		let output = hex::decode(output).unwrap();
+		assert_eq!(ec_add(input, None, &context()), Ok((ExitSucceed::Returned, output, 150)));
+	}
+	assert_eq!(ec_add(&[], Some(149), &context()), Err(ExitError::OutOfGas));
+}
+
+#[test]
+fn ec_mul_vectors_and_gas() {
+	let scalar = |n: u8| hex::encode(word(n));
+	let order = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+	let vectors = [
+		(bn128_input(&[G1, &scalar(2)]), G1_DOUBLE),
+		(bn128_input(&[G1, &scalar(3)]), G1_TRIPLE),
+		(bn128_input(&[G1, &scalar(1)]), G1),
+		(bn128_input(&[G1, &scalar(0)]), ZERO_G1),
+		(bn128_input(&[G1, order]), ZERO_G1),
+		(bn128_input(&[ZERO_G1, &scalar(2)]), ZERO_G1),
+	];
+	for (input, output) in &vectors {
+		let output = hex::decode(output).unwrap();
+		assert_eq!(ec_mul(input, None, &context()), Ok((ExitSucceed::Returned, output, 6000)));
+	}
+	assert_eq!(ec_mul(&[], Some(5999), &context()), Err(ExitError::OutOfGas));
+}
+
+#[test]
+fn ec_add_and_mul_reject_invalid_points() {
+	let off_curve = "0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000003";
+	// The x coordinate of the generator plus the field modulus.
+	let above_modulus = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd48\
+		0000000000000000000000000000000000000000000000000000000000000002";
+
+	for point in &[off_curve, above_modulus] {
+		assert!(ec_add(&bn128_input(&[point, G1]), None, &context()).is_err());
+		assert!(ec_add(&bn128_input(&[G1, point]), None, &context()).is_err());
+		assert!(ec_mul(&bn128_input(&[point, &hex::encode(word(2))]), None, &context()).is_err());
+	}
+}
+
+#[test]
+fn ec_pairing_vectors_and_gas() {
+	let vectors = [
+		(bn128_input(&[G1, G2, G1_NEG, G2]), 1, 113_000),
+		(bn128_input(&[G1, G2, G1, G2]), 0, 113_000),
+		(bn128_input(&[G1_DOUBLE, G2, G1_NEG, G2_DOUBLE]), 1, 113_000),
+		(bn128_input(&[G1, G2]), 0, 79_000),
+		(bn128_input(&[ZERO_G1, G2]), 1, 79_000),
+		(Vec::new(), 1, 45_000),
+	];
+	for (input, output, cost) in &vectors {
+		assert_eq!(
+			ec_pairing(input, None, &context()),
+			Ok((ExitSucceed::Returned, word(*output), *cost))
+		);
+	}
+	assert_eq!(ec_pairing(&[], Some(44_999), &context()), Err(ExitError::OutOfGas));
+}
+
+#[test]
+fn ec_pairing_rejects_malformed_input() {
+	let input = bn128_input(&[G1, G2]);
+	assert!(ec_pairing(&input[1..], None, &context()).is_err());
+
+	// The y coordinate of the negated generator plus the field modulus.
+	let above_modulus = "0000000000000000000000000000000000000000000000000000000000000001\
+		60c89ce5c263405370a08b6d0302b0bb2f02d522d0e3951a7841182db0f9fa8c";
+	assert!(ec_pairing(&bn128_input(&[above_modulus, G2]), None, &context()).is_err());
+
+	let mut off_twist = input.clone();
+	off_twist[160..192].copy_from_slice(&word(3));
+	assert!(ec_pairing(&off_twist, None, &context()).is_err());
+
+	// On the twist, but not of the subgroup of order r.
+	let off_subgroup = "0000000000000000000000000000000000000000000000000000000000000000\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0d1271953ed9ea0836846e70a1934187998c7f790cb4d7511b7f8da82de048a4\
+		2869111d5381f072f8e2728fdb825a51aadd70e52c9830e9ab4b871c0531f1bb";
+	assert!(ec_pairing(&bn128_input(&[G1, off_subgroup]), None, &context()).is_err());
+}
+
+#[test]
+fn ec_pairing_malformed_input_consumes_call_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let metadata = StackSubstateMetadata::new(200_000, &config);
+	let mut precompile = standard_precompiles;
+	let mut stack = StackExecutor::new_with_precompile(
+		MemoryStackState::new(metadata, &backend),
+		&config,
+		&mut precompile,
+	);
+	let (reason, _) = stack.transact_call(caller(), ECPAIRING_ADDRESS, 0.into(), vec![0; 191], 200_000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+	assert_eq!(stack.used_gas(), 200_000);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::U256;
+use common::*;
+
+/// Calls address 2 ten times in a loop, and returns the number of successful
+/// calls.
+const LOOP: &str = "6000600a5b60006000600060006000600261fffff19091019060019003806004575060005260206000f3";
+/// Calls address 3.
+const NESTED: &str = "60006000600060006000600361fffff100";
+
+fn call_loop(callee: &str, max_call_count: Option<u64>) -> (U256, u64) {
+	let mut config = Config::istanbul();
+	config.max_call_count = max_call_count;
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(LOOP)),
+		(address(2), contract(callee)),
+		(address(3), contract("00")),
+	]));
+
+	let mut stack = executor(&backend, 10_000_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 10_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	(U256::from_big_endian(&out), stack.call_count())
+}
+
+#[test]
+fn call_count_is_unlimited_by_default() {
+	assert_eq!(call_loop("00", None), (10.into(), 10));
+}
+
+#[test]
+fn calls_over_limit_fail() {
+	assert_eq!(call_loop("00", Some(10)), (10.into(), 10));
+	assert_eq!(call_loop("00", Some(4)), (4.into(), 4));
+	assert_eq!(call_loop("00", Some(0)), (0.into(), 0));
+}
+
+#[test]
+fn call_count_is_shared_across_call_tree() {
+	assert_eq!(call_loop(NESTED, None), (10.into(), 20));
+	// The third outer call succeeds even though its own inner call is the
+	// sixth one, and fails.
+	assert_eq!(call_loop(NESTED, Some(5)), (3.into(), 5));
+}
+
+#[test]
+fn call_count_is_per_transaction() {
+	let mut config = Config::istanbul();
+	config.max_call_count = Some(10);
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(LOOP)),
+		(address(2), contract("00")),
+	]));
+
+	let mut stack = executor(&backend, 10_000_000, &config);
+	for _ in 0..2 {
+		let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(U256::from_big_endian(&out), 10.into());
+		assert_eq!(stack.call_count(), 10);
+	}
+}
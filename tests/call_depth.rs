@@ -0,0 +1,102 @@
+mod common;
+
+use std::collections::BTreeSet;
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::{Inspector, StackState, Step};
+use evm::{Config, ExitReason, ExitSucceed};
+use common::*;
+
+/// Records the depth of the context of every step.
+#[derive(Default)]
+struct Depths(BTreeSet<usize>);
+
+impl Inspector for Depths {
+	fn step(&mut self, step: &Step, _state: &dyn Backend) {
+		self.0.insert(step.context.depth);
+	}
+}
+
+fn config(call_stack_limit: usize) -> Config {
+	Config { call_stack_limit, ..Config::istanbul() }
+}
+
+#[test]
+fn recursive_calls_stop_at_call_stack_limit() {
+	let vicinity = vicinity();
+	// LOG0, then CALL itself with all gas
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60006000a060006000600060006000305af15000")),
+	]));
+
+	for limit in 0..4 {
+		let config = config(limit);
+		let mut stack = executor(&backend, 1_000_000, &config);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// Frames at depths 0 up to the limit run, the next call fails.
+		let (_, logs) = stack.into_state().deconstruct();
+		assert_eq!(logs.into_iter().count(), limit + 1);
+	}
+}
+
+#[test]
+fn recursive_creates_stop_at_call_stack_limit() {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	// LOG0, CODECOPY the init code itself, then CREATE with it
+	let init_code = hex::decode("60006000a03860006000393860006000f05000").unwrap();
+
+	for limit in 0..4 {
+		let config = config(limit);
+		let mut stack = executor(&backend, 10_000_000, &config);
+		let reason = stack.transact_create(caller(), 0.into(), init_code.clone(), 10_000_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// As for calls, frames at depths 0 up to the limit run.
+		let (_, logs) = stack.into_state().deconstruct();
+		assert_eq!(logs.into_iter().count(), limit + 1);
+	}
+}
+
+#[test]
+fn transaction_frame_is_at_depth_zero() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	assert_eq!(stack.state().metadata().depth(), None);
+	stack.enter_substate(100_000, false);
+	assert_eq!(stack.state().metadata().depth(), Some(0));
+	stack.enter_substate(100_000, false);
+	assert_eq!(stack.state().metadata().depth(), Some(1));
+}
+
+#[test]
+fn context_depth_of_nested_calls_and_creates() {
+	let config = config(3);
+	let vicinity = vicinity();
+	// CALL itself with all gas
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60006000600060006000305af15000")),
+	]));
+
+	let mut depths = Depths::default();
+	let mut stack = executor(&backend, 1_000_000, &config);
+	stack.set_inspector(&mut depths);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	drop(stack);
+	assert_eq!(depths.0, (0..=3).collect());
+
+	// CODECOPY the init code itself, then CREATE with it
+	let init_code = hex::decode("3860006000393860006000f05000").unwrap();
+	let mut depths = Depths::default();
+	let mut stack = executor(&backend, 10_000_000, &config);
+	stack.set_inspector(&mut depths);
+	let reason = stack.transact_create(caller(), 0.into(), init_code, 10_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	drop(stack);
+	assert_eq!(depths.0, (0..=3).collect());
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::gasometer;
+use evm::{Config, ExitError, ExitReason, ExitSucceed};
+use primitive_types::U256;
+use common::*;
+
+/// CALL of an existing account without code, requesting the given gas as
+/// PUSH3, then STOP.
+fn call_code(gas: u32) -> String {
+	format!("60006000600060006000600262{:06x}f100", gas)
+}
+
+/// Gas of the six PUSH1 and the PUSH3.
+const PUSHES: u64 = 7 * 3;
+
+fn transact(config: &Config, requested_gas: u32) -> (ExitReason, u64) {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&call_code(requested_gas))),
+		(address(2), funded(1)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	(reason, stack.used_gas())
+}
+
+#[test]
+fn frontier_call_costs_flat_40() {
+	let config = Config::frontier();
+	assert_eq!(config.gas_call, 40);
+	assert!(!config.call_l64_after_gas);
+
+	// The requested gas is forwarded in full and returned unused.
+	for requested_gas in [0, 0x1000] {
+		assert_eq!(
+			transact(&config, requested_gas),
+			(ExitReason::Succeed(ExitSucceed::Stopped), 21000 + PUSHES + 40),
+		);
+	}
+}
+
+#[test]
+fn frontier_call_fails_when_requesting_more_than_available() {
+	let (reason, _) = transact(&Config::frontier(), 0xffffff);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+}
+
+#[test]
+fn istanbul_call_costs_700() {
+	let config = Config::istanbul();
+	assert_eq!(config.gas_call, 700);
+	assert!(config.call_l64_after_gas);
+
+	for requested_gas in [0, 0x1000] {
+		assert_eq!(
+			transact(&config, requested_gas),
+			(ExitReason::Succeed(ExitSucceed::Stopped), 21000 + PUSHES + 700),
+		);
+	}
+}
+
+#[test]
+fn istanbul_call_caps_requested_gas_to_all_but_one_64th() {
+	assert_eq!(
+		transact(&Config::istanbul(), 0xffffff),
+		(ExitReason::Succeed(ExitSucceed::Stopped), 21000 + PUSHES + 700),
+	);
+}
+
+/// POP the result of the call or create, then return GAS.
+const RETURN_GAS: &str = "505a60005260206000f3";
+
+/// Run the given code, calling or creating a frame hitting INVALID, so
+/// burning all the gas it is given, and return the gas left to the caller.
+fn gas_left_after_burning_sub_call(code: &str) -> u64 {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&format!("{}{}", code, RETURN_GAS))),
+		(address(2), contract("fe")),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	U256::from_big_endian(&out).as_u64()
+}
+
+/// One 64th of the given gas, retained by the caller, minus the gas of the
+/// POP and GAS.
+fn retained(available: u64) -> u64 {
+	available - gasometer::all_but_one_64th(available) - 2 - 2
+}
+
+#[test]
+fn all_but_one_64th() {
+	assert_eq!(gasometer::all_but_one_64th(0), 0);
+	assert_eq!(gasometer::all_but_one_64th(63), 63);
+	assert_eq!(gasometer::all_but_one_64th(64), 63);
+	assert_eq!(gasometer::all_but_one_64th(6400), 6300);
+}
+
+#[test]
+fn caller_retains_one_64th_of_gas_after_call() {
+	// CALL of address 2 requesting more gas than there is.
+	let code = "60006000600060006000600262fffffff1";
+	let available = 100_000 - 21000 - PUSHES - 700;
+	assert_eq!(gas_left_after_burning_sub_call(code), retained(available));
+}
+
+#[test]
+fn caller_retains_one_64th_of_gas_after_create() {
+	// MSTORE8 of INVALID as init code, and CREATE of it.
+	let code = "60fe600053600160006000f0";
+	let available = 100_000 - 21000 - 4 * 3 - 3 * 3 - 32000;
+	assert_eq!(gas_left_after_burning_sub_call(code), retained(available));
+}
+
+/// SLOAD 0 twice and LOG0 with no data.
+const FALLBACK: &str = "600054506000545060006000a000";
+const FALLBACK_GAS: u64 = 2 * (3 + 800 + 2) + 2 * 3 + 375;
+
+/// Gas of the PUSH1, MSTORE and RETURN after the call.
+const RETURN_RESULT: u64 = 5 * 3;
+
+/// CALL address 2 with the given value and no gas, then return the result.
+fn call_with_value(value: u8) -> (u8, u64) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let code = format!("600060006000600060{:02x}60026000f160005260206000f3", value);
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), MemoryAccount { balance: 1.into(), ..contract(&code) }),
+		(address(2), contract(FALLBACK)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	(out[31], stack.used_gas())
+}
+
+#[test]
+fn value_call_runs_on_the_stipend_alone() {
+	// The callee uses part of the stipend, and the caller gets the rest back.
+	assert_eq!(
+		call_with_value(1),
+		(1, 21000 + PUSHES + 700 + 9000 + FALLBACK_GAS - 2300 + RETURN_RESULT),
+	);
+}
+
+#[test]
+fn zero_value_call_gets_no_stipend() {
+	assert_eq!(call_with_value(0), (0, 21000 + PUSHES + 700 + RETURN_RESULT));
+}
+
+/// Gas used by a CALL transferring 1 wei with no gas to address 2, in the
+/// given state, then STOP.
+fn value_call_gas(config: &Config, target: Option<MemoryAccount>) -> u64 {
+	let vicinity = vicinity();
+	let mut accounts = vec![
+		(address(1), MemoryAccount { balance: 1.into(), ..contract("6000600060006000600160026000f100") }),
+	];
+	accounts.extend(target.map(|account| (address(2), account)));
+	let backend = MemoryBackend::new(&vicinity, state(accounts));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	stack.used_gas()
+}
+
+#[test]
+fn value_call_to_new_account_pays_surcharge() {
+	let config = Config::istanbul();
+	// The whole stipend is given back, as the target has no code.
+	let base = 21000 + PUSHES + 700 + 9000 - 2300;
+
+	assert_eq!(value_call_gas(&config, Some(funded(1))), base);
+	assert_eq!(value_call_gas(&config, None), base + 25000);
+	// An empty account does not exist since EIP-161.
+	assert_eq!(value_call_gas(&config, Some(funded(0))), base + 25000);
+
+	let config = Config { gas_call_new_account: 5000, ..Config::istanbul() };
+	assert_eq!(value_call_gas(&config, None), base + 5000);
+}
+
+#[test]
+fn frontier_value_call_to_empty_account_pays_no_surcharge() {
+	let config = Config::frontier();
+	let base = 21000 + PUSHES + 40 + 9000 - 2300;
+
+	assert_eq!(value_call_gas(&config, Some(funded(0))), base);
+	assert_eq!(value_call_gas(&config, None), base + 25000);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use evm::backend::{Backend, MemoryAccount, MemoryBackend};
+use evm::executor::{CallTracer, Frame, Inspector, Step};
+use evm::{CallScheme, Config, Context, ExitReason, ExitSucceed};
+use primitive_types::{H160, U256};
+use common::*;
+
+/// Records the scheme and the context of every frame entered, read from its
+/// first step.
+#[derive(Default)]
+struct Frames {
+	entering: Option<Option<CallScheme>>,
+	frames: Vec<(Option<CallScheme>, Context)>,
+	call_schemes: Vec<CallScheme>,
+}
+
+impl Inspector for Frames {
+	fn step(&mut self, step: &Step, _state: &dyn Backend) {
+		if let Some(scheme) = self.entering.take() {
+			self.frames.push((scheme, step.context.clone()));
+		}
+		if let Some(scheme) = step.call_scheme() {
+			self.call_schemes.push(scheme);
+		}
+	}
+
+	fn enter(&mut self, frame: &Frame) {
+		self.entering = Some(frame.scheme);
+	}
+}
+
+/// Call of address 2 with the given opcode, with a value of 1 for CALL and
+/// CALLCODE, then STOP.
+fn caller_code(opcode: &str) -> String {
+	let value = if opcode == "f1" || opcode == "f2" { "6001" } else { "" };
+	format!("6000600060006000{}600261ffff{}00", value, opcode)
+}
+
+/// Transact a call of address 1 with a value of 5, which calls address 2
+/// with the given opcode, and return the frames entered.
+fn child_frame(opcode: &str) -> (Option<CallScheme>, Context, Vec<CallScheme>) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), MemoryAccount {
+			balance: 10.into(),
+			..contract(&caller_code(opcode))
+		}),
+		(address(2), contract("00")),
+	]));
+
+	let mut frames = Frames::default();
+	let mut stack = executor(&backend, 100_000, &config);
+	stack.set_inspector(&mut frames);
+	let (reason, _) = stack.transact_call(caller(), address(1), 5.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	drop(stack);
+
+	assert_eq!(frames.frames.len(), 2);
+	let (scheme, context) = &frames.frames[0];
+	assert_eq!(*scheme, Some(CallScheme::Call));
+	assert_eq!((context.address, context.caller, context.apparent_value), (address(1), caller(), 5.into()));
+
+	let (scheme, context) = frames.frames[1].clone();
+	(scheme, context, frames.call_schemes)
+}
+
+fn fields(context: &Context) -> (H160, H160, U256, bool) {
+	(context.address, context.caller, context.apparent_value, context.is_static)
+}
+
+#[test]
+fn call_runs_the_callee_as_itself() {
+	let (scheme, context, steps) = child_frame("f1");
+	assert_eq!(scheme, Some(CallScheme::Call));
+	assert_eq!(steps, vec![CallScheme::Call]);
+	assert_eq!(fields(&context), (address(2), address(1), 1.into(), false));
+}
+
+#[test]
+fn callcode_keeps_only_the_address() {
+	let (scheme, context, steps) = child_frame("f2");
+	assert_eq!(scheme, Some(CallScheme::CallCode));
+	assert_eq!(steps, vec![CallScheme::CallCode]);
+	assert_eq!(fields(&context), (address(1), address(1), 1.into(), false));
+}
+
+#[test]
+fn delegatecall_keeps_the_address_caller_and_value() {
+	let (scheme, context, steps) = child_frame("f4");
+	assert_eq!(scheme, Some(CallScheme::DelegateCall));
+	assert_eq!(steps, vec![CallScheme::DelegateCall]);
+	assert_eq!(fields(&context), (address(1), caller(), 5.into(), false));
+}
+
+#[test]
+fn staticcall_runs_the_callee_as_itself_and_static() {
+	let (scheme, context, steps) = child_frame("fa");
+	assert_eq!(scheme, Some(CallScheme::StaticCall));
+	assert_eq!(steps, vec![CallScheme::StaticCall]);
+	assert_eq!(fields(&context), (address(2), address(1), 0.into(), true));
+}
+
+#[test]
+fn call_tracer_records_the_schemes() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&caller_code("f4"))),
+		(address(2), contract("00")),
+	]));
+
+	let mut tracer = CallTracer::default();
+	let mut stack = executor(&backend, 100_000, &config);
+	stack.set_inspector(&mut tracer);
+	stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	drop(stack);
+
+	let call = &tracer.calls()[0];
+	assert_eq!(call.scheme, Some(CallScheme::Call));
+	assert_eq!(call.calls[0].scheme, Some(CallScheme::DelegateCall));
+}
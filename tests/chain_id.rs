@@ -0,0 +1,70 @@
+mod common;
+
+use std::rc::Rc;
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::{
+	BlockContext, Capture, Config, Context, ExitError, ExitReason, ExitSucceed, Runtime,
+};
+use primitive_types::{H256, U256};
+use common::*;
+
+/// CHAINID PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN
+const RETURN_CHAIN_ID: &str = "4660005260206000f3";
+
+fn large_chain_id() -> U256 {
+	U256::from(0x1_0000_0001u64)
+}
+
+#[test]
+fn chain_id_keeps_full_width() {
+	let config = Config::istanbul();
+	let vicinity = MemoryVicinity { chain_id: large_chain_id(), ..vicinity() };
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(RETURN_CHAIN_ID))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, H256::from_low_u64_be(0x1_0000_0001).as_bytes());
+}
+
+fn run_with_block(config: &Config) -> (ExitReason, Vec<u8>) {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let block = BlockContext { chain_id: large_chain_id(), ..Default::default() };
+	let code = hex::decode(RETURN_CHAIN_ID).unwrap();
+	let mut runtime = Runtime::new_with_block(Rc::new(code), Rc::new(Vec::new()), context, block, config);
+
+	let reason = match runtime.run(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	(reason, runtime.machine().return_value())
+}
+
+#[test]
+fn chain_id_from_block_context() {
+	let (reason, out) = run_with_block(&Config::istanbul());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, H256::from_low_u64_be(0x1_0000_0001).as_bytes());
+}
+
+#[test]
+fn chain_id_fails_without_eip1344() {
+	let (reason, _) = run_with_block(&Config::frontier());
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+
+	// Also without metering, when the gasometer does not reject the opcode.
+	let config = Config { metering: false, ..Config::frontier() };
+	let (reason, _) = run_with_block(&config);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+}
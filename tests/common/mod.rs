@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::Config;
+use primitive_types::{H160, U256};
+
+pub fn address(n: u64) -> H160 {
+	H160::from_low_u64_be(n)
+}
+
+pub fn caller() -> H160 {
+	address(0xca11e7)
+}
+
+pub fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::one(),
+		origin: caller(),
+		chain_id: U256::one(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: address(0xc014ba5e),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(u64::MAX),
+	}
+}
+
+pub fn contract(code: &str) -> MemoryAccount {
+	MemoryAccount {
+		code: hex::decode(code).unwrap(),
+		..Default::default()
+	}
+}
+
+pub fn funded(balance: u64) -> MemoryAccount {
+	MemoryAccount {
+		balance: balance.into(),
+		..Default::default()
+	}
+}
+
+/// State with a funded caller plus the given accounts.
+pub fn state(accounts: Vec<(H160, MemoryAccount)>) -> BTreeMap<H160, MemoryAccount> {
+	let mut state = BTreeMap::new();
+	state.insert(caller(), funded(1_000_000_000));
+	state.extend(accounts);
+	state
+}
+
+pub fn executor<'backend, 'vicinity, 'config, 'inspector>(
+	backend: &'backend MemoryBackend<'vicinity>,
+	gas_limit: u64,
+	config: &'config Config,
+) -> StackExecutor<'config, 'static, 'inspector, MemoryStackState<'backend, 'config, MemoryBackend<'vicinity>>> {
+	let metadata = StackSubstateMetadata::new(gas_limit, config);
+	StackExecutor::new(MemoryStackState::new(metadata, backend), config)
+}
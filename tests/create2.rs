@@ -0,0 +1,37 @@
+mod common;
+
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::{create2_address, Config, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+/// Deploys the single byte at memory offset 5 with value 7 and salt 0x42,
+/// then stores the created address in slot 0.
+///
+/// PUSH1 0x42 (salt) PUSH1 1 (length) PUSH1 5 (offset) PUSH1 7 (value)
+/// CREATE2 PUSH1 0 SSTORE STOP
+const FACTORY: &str = "6042600160056007f560005500";
+
+#[test]
+fn create2_pops_value_offset_length_salt() {
+	let vicinity = vicinity();
+	let factory = MemoryAccount {
+		balance: 1000.into(),
+		..contract(FACTORY)
+	};
+	let mut backend = MemoryBackend::new(&vicinity, state(vec![(address(1), factory)]));
+
+	let config = Config::istanbul();
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let (values, logs) = stack.into_state().deconstruct();
+	backend.apply(values, logs, true);
+
+	let expected = create2_address(address(1), H256::from_low_u64_be(0x42), &[0x00]);
+	let state = backend.state();
+	assert_eq!(state[&address(1)].storage[&H256::zero()], H256::from(expected));
+	assert_eq!(state[&expected].balance, 7.into());
+	assert_eq!(state[&address(1)].balance, 993.into());
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::executor::{estimate_gas, EstimateOutcome};
+use evm::{Config, ExitError, ExitReason, ExitRevert};
+use common::*;
+
+fn outcome(code: &str) -> EstimateOutcome {
+	let mut config = Config::istanbul();
+	config.estimate = true;
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(code)),
+		// JUMPDEST PUSH1 0x00 JUMP
+		(address(2), contract("5b600056")),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	stack.estimate_outcome(&reason)
+}
+
+#[test]
+fn explicit_revert_is_not_raised() {
+	// PUSH1 0x00 PUSH1 0x00 REVERT
+	assert_eq!(outcome("60006000fd"), EstimateOutcome::Revert);
+}
+
+#[test]
+fn out_of_gas_is_raised() {
+	// JUMPDEST PUSH1 0x00 JUMP
+	assert_eq!(outcome("5b600056"), EstimateOutcome::OutOfGas);
+}
+
+#[test]
+fn revert_after_out_of_gas_sub_call_is_raised() {
+	// CALL address 2 with all the gas, then PUSH1 0x00 PUSH1 0x00 REVERT
+	assert_eq!(outcome("60006000600060006000600262fffffff160006000fd"), EstimateOutcome::OutOfGas);
+}
+
+#[test]
+fn revert_after_caught_out_of_gas_sub_call_is_not_raised() {
+	// CALL address 2 with 0x1000 gas, then PUSH1 0x00 PUSH1 0x00 REVERT
+	assert_eq!(outcome("600060006000600060006002611000f160006000fd"), EstimateOutcome::Revert);
+}
+
+#[test]
+fn outcome_is_reset_between_transactions() {
+	let mut config = Config::istanbul();
+	config.estimate = true;
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		// CALL address 2 with all the gas, then STOP
+		(address(1), contract("60006000600060006000600262fffffff100")),
+		// JUMPDEST PUSH1 0x00 JUMP
+		(address(2), contract("5b600056")),
+		// PUSH1 0x00 PUSH1 0x00 REVERT
+		(address(3), contract("60006000fd")),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(stack.estimate_outcome(&reason), EstimateOutcome::Succeed);
+	let (reason, _) = stack.transact_call(caller(), address(3), 0.into(), Vec::new(), 100_000);
+	assert_eq!(stack.estimate_outcome(&reason), EstimateOutcome::Revert);
+}
+
+#[test]
+fn success() {
+	assert_eq!(outcome("00"), EstimateOutcome::Succeed);
+}
+
+/// Estimate the gas of a call of a contract with the given code, between the
+/// intrinsic gas and 1,000,000.
+fn estimate(code: &str) -> Result<u64, ExitReason> {
+	let mut config = Config::istanbul();
+	config.estimate = true;
+	let vicinity = vicinity();
+	let state = state(vec![(address(1), contract(code))]);
+
+	estimate_gas(|gas_limit| {
+		let backend = MemoryBackend::new(&vicinity, state.clone());
+		let mut stack = executor(&backend, gas_limit, &config);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), gas_limit);
+		let outcome = stack.estimate_outcome(&reason);
+		(reason, outcome)
+	}, 21000, 1_000_000)
+}
+
+#[test]
+fn estimate_gas_finds_minimal_limit() {
+	assert_eq!(estimate("00"), Ok(21000));
+	// PUSH1 0x01 PUSH1 0x00 SSTORE
+	assert_eq!(estimate("6001600055"), Ok(21000 + 2 * 3 + 20000));
+}
+
+#[test]
+fn estimate_gas_returns_error_of_failing_transaction() {
+	// PUSH1 0x00 PUSH1 0x00 REVERT
+	assert_eq!(estimate("60006000fd"), Err(ExitReason::Revert(ExitRevert::Reverted)));
+	// JUMPDEST PUSH1 0x00 JUMP
+	assert_eq!(estimate("5b600056"), Err(ExitReason::Error(ExitError::OutOfGas)));
+}
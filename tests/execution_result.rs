@@ -0,0 +1,76 @@
+#![cfg(feature = "with-serde")]
+
+mod common;
+
+use evm::backend::{Log, MemoryBackend};
+use evm::executor::{ExecutionResult, StackState};
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+/// Clears slot 0, logs the word 42 with topic 1, and returns it.
+const CODE: &str = "6000600055602a600052600160206000a160206000f3";
+
+fn run() -> ExecutionResult {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let mut account = contract(CODE);
+	account.storage.insert(H256::zero(), H256::from_low_u64_be(1));
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	let result = stack.finish(reason, out);
+	assert_eq!(result.gas_used, stack.used_gas());
+	result
+}
+
+fn word(value: u64) -> Vec<u8> {
+	H256::from_low_u64_be(value).as_bytes().to_vec()
+}
+
+#[test]
+fn finish_summarizes_the_transaction() {
+	let result = run();
+	assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(result.return_data, word(42));
+	assert_eq!(result.logs, vec![Log {
+		address: address(1),
+		topics: vec![H256::from_low_u64_be(1)],
+		data: word(42),
+	}]);
+	// The refund of clearing the slot is capped at half of the gas used.
+	assert!(result.gas_refunded > 0);
+	assert_eq!(result.gas_refunded, (result.gas_used + result.gas_refunded) / 2);
+}
+
+#[test]
+fn finish_only_has_logs_of_the_last_transaction() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(CODE))]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	for _ in 0..2 {
+		let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		let result = stack.finish(reason, out);
+		assert_eq!(result.logs.len(), 1);
+	}
+	assert_eq!(stack.state().logs().len(), 2);
+}
+
+#[test]
+fn execution_result_serializes() {
+	let result = run();
+	let json = serde_json::to_value(&result).unwrap();
+
+	assert_eq!(json["exit_reason"], serde_json::json!({ "Succeed": "Returned" }));
+	assert_eq!(json["return_data"], serde_json::to_value(word(42)).unwrap());
+	assert_eq!(json["gas_used"], result.gas_used);
+	assert_eq!(json["gas_refunded"], result.gas_refunded);
+	assert_eq!(json["logs"].as_array().unwrap().len(), 1);
+	assert_eq!(json["logs"][0]["data"], serde_json::to_value(word(42)).unwrap());
+
+	let decoded: ExecutionResult = serde_json::from_value(json).unwrap();
+	assert_eq!(decoded, result);
+}
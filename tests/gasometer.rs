@@ -0,0 +1,202 @@
+mod common;
+
+use std::rc::Rc;
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::{Inspector, Step};
+use evm::{Capture, Config, Context, ExitError, ExitReason, ExitSucceed, Handler, Runtime};
+use common::*;
+
+#[test]
+fn out_of_gas_at_exact_opcode() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// PUSH1 0x01 PUSH1 0x01 ADD PUSH1 0x00 MSTORE
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("6001600101600052")),
+	]));
+
+	// Intrinsic gas plus the three cheap opcodes, one short for the fourth.
+	let mut stack = executor(&backend, 21000 + 9 + 2, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 21000 + 9 + 2);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(stack.gas(), 0);
+
+	// Enough for PUSH1 and the memory expansion of MSTORE.
+	let gas_limit = 21000 + 9 + 3 + 3 + 3;
+	let mut stack = executor(&backend, gas_limit, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), gas_limit);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack.gas(), 0);
+}
+
+#[test]
+fn refund_reduces_used_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// PUSH1 0x00 PUSH1 0x00 SSTORE
+	let mut clearing = contract("6000600055");
+	clearing.storage.insert(Default::default(), primitive_types::H256::from_low_u64_be(1));
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), clearing)]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let gross = 21000 + 3 + 3 + 5000;
+	assert_eq!(100_000 - stack.gas(), gross);
+	assert_eq!(stack.used_gas(), gross - gross / 2);
+}
+
+#[test]
+fn calldata_floor_dominates_light_execution() {
+	let mut config = Config::istanbul();
+	config.has_calldata_floor = true;
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("00"))]));
+	let data = vec![0xff; 1000];
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), data.clone(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(100_000 - stack.gas(), 21000 + 16 * 1000);
+	assert_eq!(stack.used_gas(), 21000 + 10 * 4 * 1000);
+
+	// A gas limit below the floor cannot pay for the transaction.
+	let mut stack = executor(&backend, 60_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), data, 60_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+}
+
+#[test]
+fn runtime_gas_used_applies_capped_refund() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// PUSH1 0x00 PUSH1 0x00 SSTORE
+	let mut clearing = contract("6000600055");
+	clearing.storage.insert(Default::default(), primitive_types::H256::from_low_u64_be(1));
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), clearing.clone())]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let mut runtime = Runtime::new(Rc::new(clearing.code), Rc::new(Vec::new()), context, &config);
+	match runtime.run(&mut stack) {
+		Capture::Exit(reason) => assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped)),
+		Capture::Trap(_) => panic!("unexpected trap"),
+	}
+
+	let gross = 3 + 3 + 5000;
+	// Execution is charged the gross amount, the refund is only applied to
+	// the reported figure.
+	assert_eq!(stack.gas_left(), (100_000 - gross).into());
+	assert_eq!(runtime.gas_used(&stack), (gross - gross / 2).into());
+}
+
+#[test]
+fn reverted_transaction_discards_refund() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// PUSH1 0x00 PUSH1 0x00 SSTORE PUSH1 0x00 PUSH1 0x00 REVERT
+	let mut clearing = contract("600060005560006000fd");
+	clearing.storage.insert(Default::default(), primitive_types::H256::from_low_u64_be(1));
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), clearing)]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert!(matches!(reason, ExitReason::Revert(_)));
+
+	// The gas consumed by execution is still charged in full, while the
+	// storage refund accrued before the revert is dropped.
+	let gross = 21000 + 3 + 3 + 5000 + 3 + 3;
+	assert_eq!(100_000 - stack.gas(), gross);
+	assert_eq!(stack.gas_refund(), 0);
+	assert_eq!(stack.used_gas(), gross);
+	assert_eq!(stack.fee(vicinity.gas_price), gross.into());
+}
+
+#[test]
+fn unmetered_execution_runs_to_completion() {
+	let mut config = Config::istanbul();
+	let vicinity = vicinity();
+	// Sum the integers from 10000 down to 1, and return the sum.
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("61271060005b811560165781019060019003906005565b60005260206000f3")),
+		// CALL address 1 with 0xffff gas and return its return data.
+		(address(2), contract("60006000600060006000600161fffff13d6000803e3d6000f3")),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+
+	config.metering = false;
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(primitive_types::U256::from_big_endian(&out), (10_000 * 10_001 / 2).into());
+	assert_eq!(stack.used_gas(), 0);
+
+	// Sub-calls are not limited by the gas they are given either.
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(2), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(primitive_types::U256::from_big_endian(&out), (10_000 * 10_001 / 2).into());
+}
+
+#[derive(Default)]
+struct StepCounter {
+	steps: usize,
+}
+
+impl Inspector for StepCounter {
+	fn step(&mut self, _step: &Step, _state: &dyn Backend) {
+		self.steps += 1;
+	}
+}
+
+#[test]
+fn infinite_loop_exits_out_of_gas_at_limit() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// JUMPDEST PUSH1 0x00 JUMP, 12 gas per iteration
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("5b600056")),
+	]));
+
+	// Ten iterations, then JUMPDEST and PUSH1 leave one short of the JUMP.
+	let gas_limit = 21000 + 10 * 12 + 1 + 3 + 7;
+	let mut counter = StepCounter::default();
+	{
+		let mut stack = executor(&backend, gas_limit, &config);
+		stack.set_inspector(&mut counter);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), gas_limit);
+		assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+		assert_eq!(stack.used_gas(), gas_limit);
+	}
+	assert_eq!(counter.steps, 10 * 3 + 3);
+}
+
+#[test]
+fn sub_call_out_of_gas_leaves_caller_running() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// CALL address 2 with all gas, MSTORE its success flag, RETURN it
+	let parent = contract("6000600060006000600060025af160005260206000f3");
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), parent),
+		(address(2), contract("5b600056")),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![0; 32]);
+	// The caller keeps the gas withheld from the sub-call (EIP-150).
+	assert!(stack.gas() > 0);
+}
@@ -0,0 +1,331 @@
+mod common;
+
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::precompile::{standard_precompiles, IDENTITY_ADDRESS};
+use evm::executor::{
+	CallTracer, Inspector, MemoryDiffTracer, MemoryStackState, StackExecutor,
+	StackSubstateMetadata, Step, StorageStats,
+};
+use evm::{Config, ExitReason, ExitSucceed, ExternalOperation, Opcode};
+use primitive_types::{H256, U256};
+use common::*;
+
+#[test]
+fn storage_stats_counts_warm_cold_and_noop() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	// SLOAD 0, SLOAD 0, SLOAD 1, SSTORE 0 <- 5 (unchanged), SSTORE 1 <- 7
+	let mut account = contract("60005450600054506001545060056000556007600155");
+	account.storage.insert(H256::zero(), H256::from_low_u64_be(5));
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+	let mut stats = StorageStats::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut stats);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	assert_eq!(stats.cold_loads, 2);
+	assert_eq!(stats.warm_loads, 1);
+	assert_eq!(stats.noop_stores, 1);
+}
+
+#[test]
+fn storage_stats_counts_as_charged() {
+	let vicinity = vicinity();
+	// SLOAD 0, SLOAD 0
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("6000545060005450"))]));
+
+	let stats = |config: &Config, transactions: usize| {
+		let mut stats = StorageStats::default();
+		{
+			let mut stack = executor(&backend, 1_000_000, config);
+			stack.set_inspector(&mut stats);
+			for _ in 0..transactions {
+				let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+				assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+			}
+		}
+		(stats.cold_loads, stats.warm_loads)
+	};
+
+	assert_eq!(stats(&Config::berlin(), 1), (1, 1));
+	// The slot is cold again in the next transaction.
+	assert_eq!(stats(&Config::berlin(), 2), (2, 2));
+	// Without EIP-2929, every load is charged as cold.
+	assert_eq!(stats(&Config::istanbul(), 1), (2, 0));
+}
+
+/// Records the program counter, opcode and gas left of each step, and the
+/// memory size only when a step is about to return.
+#[derive(Default)]
+struct Tracer {
+	steps: Vec<(usize, Opcode, u64)>,
+	memory_on_return: Option<usize>,
+}
+
+impl Inspector for Tracer {
+	fn step(&mut self, step: &Step, _state: &dyn Backend) {
+		let position = step.position().unwrap();
+		self.steps.push((position, step.opcode, step.gas));
+		if step.opcode == Opcode::RETURN {
+			self.memory_on_return = Some(step.memory().len());
+		}
+	}
+}
+
+#[test]
+fn tracer_reads_position_opcode_and_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("602a60005260206000f3")),
+	]));
+
+	let mut tracer = Tracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	}
+
+	let gas = 100_000 - 21000;
+	assert_eq!(tracer.steps, vec![
+		(0, Opcode::PUSH1, gas),
+		(2, Opcode::PUSH1, gas - 3),
+		(4, Opcode::MSTORE, gas - 6),
+		(5, Opcode::PUSH1, gas - 12),
+		(7, Opcode::PUSH1, gas - 15),
+		(9, Opcode::RETURN, gas - 18),
+	]);
+	assert_eq!(tracer.memory_on_return, Some(32));
+}
+
+#[derive(Default)]
+struct PushTracer {
+	values: Vec<H256>,
+}
+
+impl Inspector for PushTracer {
+	fn step(&mut self, step: &Step, _state: &dyn Backend) {
+		self.values.extend(step.push_value());
+	}
+}
+
+#[test]
+fn tracer_reads_push_values() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let constant = "0102030405060708091011121314151617181920212223242526272829303132";
+	// PUSH32 constant PUSH2 0xbeef POP POP
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&format!("7f{}61beef5050", constant))),
+	]));
+
+	let mut tracer = PushTracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	assert_eq!(tracer.values, vec![
+		H256::from_slice(&hex::decode(constant).unwrap()),
+		H256::from_low_u64_be(0xbeef),
+	]);
+}
+
+#[test]
+fn call_tracer_records_call_tree() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// MSTORE 0 <- 0x11223344, CALL address 2 with its 4 bytes as input,
+	// then RETURN the 32 bytes it returned
+	let parent = contract("6311223344600052602060006004601c6000600261fffff15060206000f3");
+	// RETURN CALLDATALOAD 0 + 1
+	let child = contract("60003560010160005260206000f3");
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), parent),
+		(address(2), child),
+	]));
+
+	let mut tracer = CallTracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), vec![0xab, 0xcd], 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	}
+
+	let mut output = hex::decode("11223344").unwrap();
+	output.resize(32, 0);
+	output[31] = 1;
+
+	assert_eq!(tracer.calls().len(), 1);
+	let root = &tracer.calls()[0];
+	assert_eq!((root.caller, root.address), (caller(), address(1)));
+	assert_eq!(root.input, vec![0xab, 0xcd]);
+	assert_eq!(root.output, output);
+	assert!(root.reason.is_succeed());
+	assert!(!root.is_create);
+
+	assert_eq!(root.calls.len(), 1);
+	let call = &root.calls[0];
+	assert_eq!((call.caller, call.address), (address(1), address(2)));
+	assert_eq!(call.input, hex::decode("11223344").unwrap());
+	assert_eq!(call.output, output);
+	assert!(call.reason.is_succeed());
+	assert!(call.calls.is_empty());
+	assert!(call.gas_used > 0 && call.gas_used < root.gas_used);
+}
+
+#[test]
+fn call_tracer_records_deployed_code() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	// Deploy the single byte 0xfe
+	let init_code = hex::decode("60fe60005360016000f3").unwrap();
+
+	let mut tracer = CallTracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let reason = stack.transact_create(caller(), 0.into(), init_code.clone(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	}
+
+	let root = &tracer.calls()[0];
+	assert!(root.is_create);
+	assert_eq!(root.address, evm::create_address(caller(), 0.into()));
+	assert_eq!(root.input, init_code);
+	assert_eq!(root.output, vec![0xfe]);
+}
+
+#[derive(Default)]
+struct ExternalOperations(Vec<ExternalOperation>);
+
+impl Inspector for ExternalOperations {
+	fn step(&mut self, _step: &Step, _state: &dyn Backend) {}
+
+	fn external_operation(&mut self, op: &ExternalOperation) {
+		self.0.push(*op);
+	}
+}
+
+#[test]
+fn inspector_sees_external_operations() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// CALL the identity precompile with no input
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60006000600060006000600461fffff100")),
+	]));
+
+	let mut operations = ExternalOperations::default();
+	{
+		let metadata = StackSubstateMetadata::new(100_000, &config);
+		let mut precompile = standard_precompiles;
+		let mut stack = StackExecutor::new_with_precompile(
+			MemoryStackState::new(metadata, &backend),
+			&config,
+			&mut precompile,
+		);
+		stack.set_inspector(&mut operations);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	assert_eq!(operations.0, vec![
+		ExternalOperation::AccountAccess(IDENTITY_ADDRESS),
+		ExternalOperation::PrecompileInvocation(IDENTITY_ADDRESS),
+	]);
+}
+
+#[test]
+fn call_step_records_gas_before_the_call_is_charged() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// CALL address 2 with all the gas left, then STOP
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("6000600060006000600060025af100")),
+		(address(2), contract("00")),
+	]));
+
+	let mut tracer = Tracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	// As geth, the CALL step records the gas left before the call, and the
+	// callee starts with all but one 64th of what is left after the base
+	// cost of 700, which the caller gets back as the callee uses none.
+	let gas = 100_000 - 21000 - 6 * 3 - 2;
+	let forwarded = (gas - 700) - (gas - 700) / 64;
+	assert_eq!(&tracer.steps[7..], &[
+		(13, Opcode::CALL, gas),
+		(0, Opcode::STOP, forwarded),
+		(14, Opcode::STOP, gas - 700),
+	]);
+	assert_eq!(forwarded, 77057);
+}
+
+#[test]
+fn memory_diff_tracer_reports_changed_words() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// MSTORE 0xaa at word 0, 0xbb at word 1, 0xcc at word 0, then RETURN
+	// word 1
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60aa60005260bb60205260cc60005260206020f3")),
+	]));
+
+	let mut tracer = MemoryDiffTracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	}
+
+	let changed: Vec<_> = tracer.deltas().iter().filter(|delta| !delta.is_empty()).collect();
+	assert_eq!(changed, vec![
+		&vec![(0, U256::from(0xaa))],
+		&vec![(1, U256::from(0xbb))],
+		&vec![(0, U256::from(0xcc))],
+	]);
+	assert_eq!(tracer.deltas().len(), 12);
+}
+
+#[test]
+fn memory_diff_tracer_reports_grown_words() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// MSTORE 0xaa at word 2
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60aa60405200")),
+	]));
+
+	let mut tracer = MemoryDiffTracer::default();
+	{
+		let mut stack = executor(&backend, 100_000, &config);
+		stack.set_inspector(&mut tracer);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	assert_eq!(tracer.deltas(), &[
+		vec![],
+		vec![],
+		vec![],
+		vec![(0, U256::zero()), (1, U256::zero()), (2, U256::from(0xaa))],
+	]);
+}
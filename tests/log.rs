@@ -0,0 +1,51 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use common::*;
+
+/// LOG4 of 100 bytes of memory with topics 1 to 4, then STOP.
+const LOG4_CODE: &str = "600460036002600160646000a400";
+
+/// Gas of the six PUSH1.
+const PUSHES: u64 = 6 * 3;
+
+/// Gas of expanding the memory to 4 words, the quadratic part being zero.
+const MEMORY: u64 = 3 * 4;
+
+fn transact(config: &Config) -> (ExitReason, u64) {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(LOG4_CODE)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	(reason, stack.used_gas())
+}
+
+#[test]
+fn log4_costs_375_plus_375_per_topic_plus_8_per_byte() {
+	let config = Config::istanbul();
+	assert_eq!((config.gas_log, config.gas_log_topic, config.gas_log_data), (375, 375, 8));
+
+	assert_eq!(
+		transact(&config),
+		(ExitReason::Succeed(ExitSucceed::Stopped), 21000 + PUSHES + MEMORY + 375 + 4 * 375 + 100 * 8),
+	);
+}
+
+#[test]
+fn log_costs_follow_the_config() {
+	let config = Config {
+		gas_log: 100,
+		gas_log_topic: 10,
+		gas_log_data: 1,
+		..Config::istanbul()
+	};
+
+	assert_eq!(
+		transact(&config),
+		(ExitReason::Succeed(ExitSucceed::Stopped), 21000 + PUSHES + MEMORY + 100 + 4 * 10 + 100),
+	);
+}
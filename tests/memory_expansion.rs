@@ -0,0 +1,50 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{memory_expansion_cost, Config, ExitReason, ExitSucceed};
+use common::*;
+
+/// PUSH1 0x2a PUSH3 0x010000 MSTORE PUSH1 0x2a PUSH1 0 MSTORE STOP, expanding
+/// the memory to 2049 words, then storing in memory already paid for.
+const MSTORE: &str = "602a6201000052602a60005200";
+/// Gas of the PUSH and MSTORE opcodes of `MSTORE`, besides memory expansion.
+const OPCODES_GAS: u64 = 6 * 3;
+
+fn linear(current_words: usize, new_words: usize) -> u64 {
+	(new_words - current_words) as u64
+}
+
+fn call(cost: Option<fn(usize, usize) -> u64>) -> (ExitReason, u64) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(MSTORE)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	if let Some(cost) = cost {
+		stack.set_memory_expansion_cost(cost);
+	}
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	(reason, stack.used_gas())
+}
+
+#[test]
+fn standard_memory_expansion_cost() {
+	assert_eq!(memory_expansion_cost(0, 0), 0);
+	assert_eq!(memory_expansion_cost(0, 1), 3);
+	assert_eq!(memory_expansion_cost(0, 2049), 3 * 2049 + 2049 * 2049 / 512);
+	assert_eq!(memory_expansion_cost(1, 2049), memory_expansion_cost(0, 2049) - 3);
+	assert_eq!(memory_expansion_cost(0, usize::MAX), u64::MAX);
+
+	let (reason, used_gas) = call(None);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(used_gas, 21_000 + OPCODES_GAS + memory_expansion_cost(0, 2049));
+}
+
+#[test]
+fn overridden_memory_expansion_cost() {
+	let (reason, used_gas) = call(Some(linear));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(used_gas, 21_000 + OPCODES_GAS + 2049);
+}
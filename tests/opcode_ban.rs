@@ -0,0 +1,68 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::executor::OpcodeBan;
+use evm::{Config, ExitError, ExitReason, ExitSucceed, Opcode};
+use primitive_types::{H160, U256};
+use common::*;
+
+/// PUSH20 beneficiary SUICIDE
+const SUICIDE: &str = "7300000000000000000000000000000000000be11eff";
+/// Stores 1 at slot 0, and returns it.
+const STORE: &str = "600160005560005460005260206000f3";
+/// Calls address 1, and returns the call result.
+const CALL_SUICIDE: &str = "60006000600060006000600161fffff160005260206000f3";
+
+fn call(to: H160, ban: OpcodeBan) -> (ExitReason, Vec<u8>, u64) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(SUICIDE)),
+		(address(2), contract(STORE)),
+		(address(3), contract(CALL_SUICIDE)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	stack.set_opcode_ban(ban);
+	let (reason, out) = stack.transact_call(caller(), to, 0.into(), Vec::new(), 100_000);
+	(reason, out, stack.used_gas())
+}
+
+fn suicide_ban() -> OpcodeBan {
+	vec![Opcode::SUICIDE].into_iter().collect()
+}
+
+#[test]
+fn banned_opcode_fails_the_frame() {
+	let (reason, _, used_gas) = call(address(1), suicide_ban());
+	assert_eq!(reason, ExitReason::Error(ExitError::BannedOpcode));
+	assert_eq!(used_gas, 100_000);
+
+	let (reason, _, _) = call(address(1), OpcodeBan::new());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+}
+
+#[test]
+fn other_opcodes_run() {
+	let (reason, out, _) = call(address(2), suicide_ban());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&out), 1.into());
+}
+
+#[test]
+fn banned_opcode_in_sub_call_fails_only_the_sub_call() {
+	let (reason, out, _) = call(address(3), suicide_ban());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&out), 0.into());
+}
+
+#[test]
+fn ban_and_unban() {
+	let mut ban = OpcodeBan::new();
+	assert!(ban.is_empty());
+	ban.ban(Opcode::DELEGATECALL);
+	assert!(ban.is_banned(Opcode::DELEGATECALL));
+	assert!(!ban.is_banned(Opcode::CALL));
+	ban.unban(Opcode::DELEGATECALL);
+	assert_eq!(ban, OpcodeBan::default());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed, Handler};
+use primitive_types::{H160, H256};
+use common::*;
+
+/// Stores ORIGIN at slot 0 and CALLER at slot 1.
+const STORE_ORIGIN: &str = "326000553360015500";
+
+/// Calls the given address with CALL, with no value, or DELEGATECALL, passing
+/// all the gas.
+fn call_code(opcode: &str, to: u8) -> String {
+	let value = if opcode == "f1" { "6000" } else { "" };
+	format!("6000600060006000{}60{:02x}62ffffff{}00", value, to, opcode)
+}
+
+/// Run address 1 calling address 2 calling address 3 storing its ORIGIN and
+/// CALLER, with the given call opcode, and return what the storage of the
+/// given address holds.
+fn nested_origin(opcode: &str, storage_of: H160) -> (H160, H160) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&call_code(opcode, 2))),
+		(address(2), contract(&call_code(opcode, 3))),
+		(address(3), contract(STORE_ORIGIN)),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	(
+		stack.storage(storage_of, H256::zero()).into(),
+		stack.storage(storage_of, H256::from_low_u64_be(1)).into(),
+	)
+}
+
+#[test]
+fn origin_is_the_sender_in_nested_calls() {
+	assert_eq!(nested_origin("f1", address(3)), (caller(), address(2)));
+}
+
+#[test]
+fn origin_is_the_sender_in_nested_delegate_calls() {
+	// The delegate calls run in the frame of address 1, called by the sender.
+	assert_eq!(nested_origin("f4", address(1)), (caller(), caller()));
+}
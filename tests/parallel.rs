@@ -0,0 +1,47 @@
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::{transact_calls_parallel, CallTransaction};
+use evm::Config;
+use primitive_types::H256;
+use common::*;
+
+#[test]
+fn parallel_calls_match_sequential_execution() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SSTORE 0 <- CALLDATALOAD 0, then MSTORE and RETURN it
+	let backend = MemoryBackend::new(&vicinity, state(
+		(1..=8).map(|n| (address(n), contract("6000358060005560005260206000f3"))).collect(),
+	));
+
+	let transactions = (1..=8).map(|n| CallTransaction {
+		caller: caller(),
+		address: address(n),
+		value: 0.into(),
+		data: H256::from_low_u64_be(n * 7).as_bytes().to_vec(),
+		gas_limit: 100_000,
+	}).collect::<Vec<_>>();
+
+	let results = transact_calls_parallel(&backend, &config, &transactions);
+	assert_eq!(results.len(), transactions.len());
+
+	for (transaction, (reason, out, state)) in transactions.iter().zip(results) {
+		let mut stack = executor(&backend, transaction.gas_limit, &config);
+		let expected = stack.transact_call(
+			transaction.caller,
+			transaction.address,
+			transaction.value,
+			transaction.data.clone(),
+			transaction.gas_limit,
+		);
+		assert_eq!((reason, out.clone()), expected);
+		assert_eq!(out, transaction.data);
+		assert_eq!(
+			state.storage(transaction.address, H256::zero()),
+			stack.state().storage(transaction.address, H256::zero()),
+		);
+	}
+}
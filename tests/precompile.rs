@@ -0,0 +1,148 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::executor::precompile::{
+	blake2f, identity, sha256, standard_precompiles, BLAKE2F_ADDRESS, IDENTITY_ADDRESS,
+};
+use evm::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::{Config, Context, ExitError, ExitReason, ExitSucceed};
+use common::*;
+
+fn context() -> Context {
+	Context {
+		address: address(2),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	}
+}
+
+#[test]
+fn sha256_vectors_and_gas() {
+	let vectors = [
+		("", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855", 60),
+		("616263", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", 72),
+		(
+			"0000000000000000000000000000000000000000000000000000000000000000",
+			"66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+			72,
+		),
+		(
+			"000000000000000000000000000000000000000000000000000000000000000000",
+			"7f9c9e31ac8256ca2f258583df262dbc7d6f68f2a03043d5c99a4ae5a7396ce9",
+			84,
+		),
+	];
+
+	for (input, output, cost) in &vectors {
+		let input = hex::decode(input).unwrap();
+		let result = sha256(&input, None, &context());
+		assert_eq!(result, Ok((ExitSucceed::Returned, hex::decode(output).unwrap(), *cost)));
+	}
+}
+
+#[test]
+fn identity_returns_input_and_gas() {
+	for (len, cost) in &[(0, 15), (1, 18), (32, 18), (33, 21), (64, 21)] {
+		let input = (0..*len).map(|i| i as u8).collect::<Vec<_>>();
+		let result = identity(&input, None, &context());
+		assert_eq!(result, Ok((ExitSucceed::Returned, input, *cost)));
+	}
+}
+
+#[test]
+fn precompiles_fail_above_target_gas() {
+	assert_eq!(sha256(&[], Some(59), &context()), Err(ExitError::OutOfGas));
+	assert_eq!(identity(&[0; 33], Some(20), &context()), Err(ExitError::OutOfGas));
+	assert!(standard_precompiles(address(3), &[], None, &context()).is_none());
+}
+
+#[test]
+fn identity_precompile_through_call() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let metadata = StackSubstateMetadata::new(100_000, &config);
+	let mut precompile = standard_precompiles;
+	let mut stack = StackExecutor::new_with_precompile(
+		MemoryStackState::new(metadata, &backend),
+		&config,
+		&mut precompile,
+	);
+	let input = vec![0xab; 33];
+	let (reason, out) = stack.transact_call(caller(), IDENTITY_ADDRESS, 0.into(), input.clone(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, input);
+	// Intrinsic gas of 33 non-zero bytes, plus the precompile.
+	assert_eq!(stack.used_gas(), 21000 + 33 * 16 + 21);
+}
+
+/// EIP-152 input hashing "abc" in a single final block, with the given
+/// number of rounds and final block flag.
+fn blake2f_input(rounds: u32, flag: u8) -> Vec<u8> {
+	let mut input = rounds.to_be_bytes().to_vec();
+	input.extend(hex::decode(
+		"48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+		 d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b"
+	).unwrap());
+	let mut message = b"abc".to_vec();
+	message.resize(128, 0);
+	input.extend(message);
+	input.extend(hex::decode("03000000000000000000000000000000").unwrap());
+	input.push(flag);
+	input
+}
+
+#[test]
+fn blake2f_vectors_and_gas() {
+	let vectors = [
+		(0, 1, "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+			d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b"),
+		(12, 1, "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+			7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"),
+		(12, 0, "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752\
+			98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735"),
+		(1, 1, "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fb\
+			a551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421"),
+	];
+
+	for (rounds, flag, output) in &vectors {
+		let output = hex::decode(output).unwrap();
+		let result = blake2f(&blake2f_input(*rounds, *flag), None, &context());
+		assert_eq!(result, Ok((ExitSucceed::Returned, output, u64::from(*rounds))));
+	}
+}
+
+#[test]
+fn blake2f_rejects_malformed_input() {
+	let input = blake2f_input(12, 1);
+
+	assert!(blake2f(&[], None, &context()).is_err());
+	assert!(blake2f(&input[1..], None, &context()).is_err());
+	let mut longer = input.clone();
+	longer.push(0);
+	assert!(blake2f(&longer, None, &context()).is_err());
+	assert!(blake2f(&blake2f_input(12, 2), None, &context()).is_err());
+	assert_eq!(blake2f(&input, Some(11), &context()), Err(ExitError::OutOfGas));
+}
+
+#[test]
+fn blake2f_malformed_input_consumes_call_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let metadata = StackSubstateMetadata::new(100_000, &config);
+	let mut precompile = standard_precompiles;
+	let mut stack = StackExecutor::new_with_precompile(
+		MemoryStackState::new(metadata, &backend),
+		&config,
+		&mut precompile,
+	);
+	let (reason, _) = stack.transact_call(caller(), BLAKE2F_ADDRESS, 0.into(), vec![0; 212], 100_000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+	assert_eq!(stack.used_gas(), 100_000);
+}
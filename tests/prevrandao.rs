@@ -0,0 +1,97 @@
+mod common;
+
+use evm::backend::{Backend, Basic, MemoryBackend, MemoryVicinity};
+use evm::executor::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::{H160, H256, U256};
+use common::*;
+
+/// DIFFICULTY PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN
+const DIFFICULTY: &str = "4460005260206000f3";
+
+fn randomness() -> H256 {
+	H256::repeat_byte(0xab)
+}
+
+fn post_merge() -> Config {
+	let mut config = Config::berlin();
+	config.has_prevrandao = true;
+	config
+}
+
+fn difficulty_opcode(config: &Config, vicinity: MemoryVicinity) -> H256 {
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(DIFFICULTY))]));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	H256::from_slice(&out)
+}
+
+fn merge_vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		block_difficulty: 0x20000.into(),
+		block_randomness: Some(randomness()),
+		..vicinity()
+	}
+}
+
+#[test]
+fn difficulty_pushes_randomness_after_merge() {
+	assert_eq!(difficulty_opcode(&post_merge(), merge_vicinity()), randomness());
+}
+
+#[test]
+fn difficulty_pushes_difficulty_before_merge() {
+	assert_eq!(
+		difficulty_opcode(&Config::berlin(), merge_vicinity()),
+		H256::from_low_u64_be(0x20000),
+	);
+}
+
+#[test]
+fn difficulty_falls_back_without_randomness() {
+	let vicinity = MemoryVicinity {
+		block_randomness: None,
+		..merge_vicinity()
+	};
+	assert_eq!(difficulty_opcode(&post_merge(), vicinity), H256::from_low_u64_be(0x20000));
+}
+
+/// Backend relying on the default of `Backend::block_randomness`.
+struct WithoutRandomness<'vicinity>(MemoryBackend<'vicinity>);
+
+impl<'vicinity> Backend for WithoutRandomness<'vicinity> {
+	fn gas_price(&self) -> U256 { self.0.gas_price() }
+	fn origin(&self) -> H160 { self.0.origin() }
+	fn block_hash(&self, number: U256) -> H256 { self.0.block_hash(number) }
+	fn block_number(&self) -> U256 { self.0.block_number() }
+	fn block_coinbase(&self) -> H160 { self.0.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.0.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.0.block_difficulty() }
+	fn block_gas_limit(&self) -> U256 { self.0.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.0.chain_id() }
+	fn exists(&self, address: H160) -> bool { self.0.exists(address) }
+	fn basic(&self, address: H160) -> Basic { self.0.basic(address) }
+	fn code(&self, address: H160) -> Vec<u8> { self.0.code(address) }
+	fn storage(&self, address: H160, index: H256) -> H256 { self.0.storage(address, index) }
+	fn original_storage(&self, address: H160, index: H256) -> Option<H256> {
+		self.0.original_storage(address, index)
+	}
+}
+
+#[test]
+fn backend_without_randomness_falls_back_to_difficulty() {
+	let config = post_merge();
+	let vicinity = merge_vicinity();
+	let backend = WithoutRandomness(
+		MemoryBackend::new(&vicinity, state(vec![(address(1), contract(DIFFICULTY))])),
+	);
+	assert_eq!(backend.block_randomness(), None);
+
+	let metadata = StackSubstateMetadata::new(100_000, &config);
+	let mut stack = StackExecutor::new(MemoryStackState::new(metadata, &backend), &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H256::from_slice(&out), H256::from_low_u64_be(0x20000));
+}
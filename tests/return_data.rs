@@ -0,0 +1,118 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitError, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+/// Calls address 2 and returns the call result and RETURNDATASIZE.
+const OUTER: &str = "60006000600060006000600261fffff16000523d60205260406000f3";
+/// Calls address 3, then returns 100 bytes.
+const MIDDLE: &str = "60006000600060006000600361fffff160646000f3";
+/// Returns 100 bytes.
+const INNER: &str = "60646000f3";
+
+fn call_chain(max_total_return_data_size: Option<usize>) -> (H256, H256) {
+	let mut config = Config::istanbul();
+	config.max_total_return_data_size = max_total_return_data_size;
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(OUTER)),
+		(address(2), contract(MIDDLE)),
+		(address(3), contract(INNER)),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	(H256::from_slice(&out[..32]), H256::from_slice(&out[32..]))
+}
+
+#[test]
+fn cumulative_return_data_within_limit() {
+	let expected = (H256::from_low_u64_be(1), H256::from_low_u64_be(100));
+	assert_eq!(call_chain(None), expected);
+	assert_eq!(call_chain(Some(200)), expected);
+}
+
+#[test]
+fn cumulative_return_data_over_limit_fails_sub_call() {
+	// The inner call fits, but the middle one returning on top of it does not.
+	assert_eq!(call_chain(Some(199)), (H256::zero(), H256::zero()));
+}
+
+#[test]
+fn cumulative_return_data_is_per_transaction() {
+	let mut config = Config::istanbul();
+	config.max_total_return_data_size = Some(200);
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(OUTER)),
+		(address(2), contract(MIDDLE)),
+		(address(3), contract(INNER)),
+	]));
+
+	let mut stack = executor(&backend, 10_000_000, &config);
+	for _ in 0..2 {
+		let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(H256::from_slice(&out[..32]), H256::from_low_u64_be(1));
+	}
+}
+
+/// Returns 100 bytes, the word 0xff and zeros.
+const RETURN_FF: &str = "60ff60005260646000f3";
+
+/// Calls address 2, returning 100 bytes, then RETURNDATACOPY of the given
+/// range to memory 0, and returns RETURNDATASIZE and the first copied word.
+fn copy_return_data(data_offset: u8, len: u8) -> (ExitReason, Vec<u8>, u64) {
+	let config = Config::istanbul();
+	let code = format!(
+		"60006000600060006000600261fffff15060{:02x}60{:02x}60003e3d60205260406000f3",
+		len, data_offset,
+	);
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&code)),
+		(address(2), contract(RETURN_FF)),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	(reason, out, stack.used_gas())
+}
+
+#[test]
+fn return_data_copy_within_return_data() {
+	let (reason, out, _) = copy_return_data(0, 100);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out[31], 0xff);
+	assert_eq!(H256::from_slice(&out[32..]), H256::from_low_u64_be(100));
+
+	let (reason, out, _) = copy_return_data(1, 99);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out[30], 0xff);
+
+	let (reason, _, _) = copy_return_data(100, 0);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+}
+
+#[test]
+fn return_data_copy_past_return_data_fails() {
+	for (data_offset, len) in [(0, 101), (1, 100), (100, 1), (101, 0)] {
+		let (reason, out, used_gas) = copy_return_data(data_offset, len);
+		assert_eq!(reason, ExitReason::Error(ExitError::OutOfOffset));
+		assert!(out.is_empty());
+		assert_eq!(used_gas, 100_000);
+	}
+}
+
+#[test]
+fn return_data_copy_charges_per_word() {
+	let (_, _, one_word) = copy_return_data(0, 32);
+	let (_, _, two_words) = copy_return_data(0, 33);
+	let (_, _, four_words) = copy_return_data(0, 100);
+	// The memory up to 64 bytes is expanded by the return anyway.
+	assert_eq!(two_words - one_word, 3);
+	assert_eq!(four_words - two_words, 2 * 3 + 2 * 3);
+}
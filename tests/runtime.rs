@@ -0,0 +1,353 @@
+mod common;
+
+use std::rc::Rc;
+use evm::backend::MemoryBackend;
+use evm::{
+	BlockContext, BreakpointSet, Capture, Config, Context, ExitError, ExitReason, ExitSucceed,
+	FromHexError, Log, Opcode, Runtime,
+};
+use primitive_types::H256;
+use common::*;
+
+#[test]
+fn step_observes_injected_stack() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	// ADD
+	let mut runtime = Runtime::new(Rc::new(vec![0x01]), Rc::new(Vec::new()), context, &config);
+
+	let machine_stack = runtime.machine_mut().stack_mut();
+	machine_stack.push(H256::from_low_u64_be(2)).unwrap();
+	machine_stack.push(H256::from_low_u64_be(0)).unwrap();
+	machine_stack.set(0, H256::from_low_u64_be(3)).unwrap();
+
+	assert!(runtime.step(&mut stack).is_ok());
+	assert_eq!(runtime.machine().stack().len(), 1);
+	assert_eq!(runtime.machine().stack().peek(0), Ok(H256::from_low_u64_be(5)));
+}
+
+#[test]
+fn step_n_runs_bounded_batch() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	// PUSH1 1 PUSH1 2 ADD PUSH1 3 MUL POP
+	let code = hex::decode("600160020160030250").unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+
+	assert_eq!(runtime.step_n(&mut stack, 5).ok(), Some(5));
+	assert_eq!(runtime.machine().position(), Ok(8));
+	assert_eq!(runtime.machine().stack().peek(0), Ok(H256::from_low_u64_be(9)));
+
+	let stopped = match runtime.step_n(&mut stack, 10) {
+		Err(Capture::Exit(reason)) => reason,
+		_ => panic!("expected the runtime to stop"),
+	};
+	assert_eq!(stopped, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+fn run_read_only(code: &str) -> (ExitReason, Vec<u8>) {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SSTORE 0 <- 1
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(2), contract("6001600055"))]));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let code = hex::decode(code).unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+
+	let reason = match runtime.run_read_only(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	(reason, runtime.machine().return_value())
+}
+
+#[test]
+fn read_only_run_allows_views() {
+	// MSTORE 0 <- SLOAD 0, RETURN it
+	let (reason, out) = run_read_only("60005460005260206000f3");
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![0; 32]);
+}
+
+#[test]
+fn read_only_run_rejects_state_writes() {
+	// SSTORE 0 <- 1
+	let (reason, _) = run_read_only("6001600055");
+	assert_eq!(reason, ExitReason::Error(ExitError::StaticModeViolation));
+
+	// LOG0
+	let (reason, _) = run_read_only("60006000a0");
+	assert_eq!(reason, ExitReason::Error(ExitError::StaticModeViolation));
+
+	// CALL address 2 writing storage, MSTORE its success flag, RETURN it
+	let (reason, out) = run_read_only("6000600060006000600060025af160005260206000f3");
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![0; 32]);
+}
+
+#[test]
+fn log_buffer_keeps_emitted_logs() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	// MSTORE 0 <- 0xbeef, LOG2 of memory[30..32] with topics 0xaa and 0xbb
+	let code = Rc::new(hex::decode("61beef60005260bb60aa6002601ea200").unwrap());
+
+	let mut runtime = Runtime::new(code.clone(), Rc::new(Vec::new()), context.clone(), &config);
+	let _ = runtime.run(&mut stack);
+	assert!(runtime.logs().is_empty());
+
+	let mut runtime = Runtime::new(code, Rc::new(Vec::new()), context, &config);
+	runtime.enable_log_buffer();
+	let reason = match runtime.run(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(runtime.logs(), &[Log {
+		address: address(1),
+		topics: vec![H256::from_low_u64_be(0xaa), H256::from_low_u64_be(0xbb)],
+		data: vec![0xbe, 0xef],
+	}]);
+}
+
+#[test]
+fn block_context_overrides_handler() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let block = BlockContext {
+		number: 100.into(),
+		timestamp: 12345.into(),
+		gas_limit: 30_000_000.into(),
+		..Default::default()
+	};
+	// Return NUMBER, TIMESTAMP and GASLIMIT
+	let code = hex::decode("43600052426020524560405260606000f3").unwrap();
+	let mut runtime = Runtime::new_with_block(Rc::new(code), Rc::new(Vec::new()), context, block, &config);
+
+	let reason = match runtime.run(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	let values: Vec<H256> = runtime.machine().return_value()
+		.chunks(32)
+		.map(H256::from_slice)
+		.collect();
+	assert_eq!(values, vec![
+		H256::from_low_u64_be(100),
+		H256::from_low_u64_be(12345),
+		H256::from_low_u64_be(30_000_000),
+	]);
+}
+
+/// PUSH1 1 JUMPDEST PUSH1 2 JUMPDEST PUSH1 3 JUMPDEST PUSH1 4 STOP, with the
+/// JUMPDEST at 2, 5 and 8.
+const JUMPDESTS: &str = "60015b60025b60035b600400";
+
+fn stack_values(runtime: &Runtime) -> Vec<H256> {
+	let stack = runtime.machine().stack();
+	(0..stack.len()).rev().map(|i| stack.peek(i).unwrap()).collect()
+}
+
+#[test]
+fn run_until_pauses_at_position() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let code = hex::decode(JUMPDESTS).unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+
+	let mut breakpoints = BreakpointSet::new();
+	breakpoints.add_position(8);
+
+	assert_eq!(runtime.run_until(&mut stack, &breakpoints).ok(), Some(8));
+	assert_eq!(runtime.machine().position(), Ok(8));
+	assert_eq!(stack_values(&runtime), vec![
+		H256::from_low_u64_be(1),
+		H256::from_low_u64_be(2),
+		H256::from_low_u64_be(3),
+	]);
+
+	let stopped = match runtime.run_until(&mut stack, &breakpoints) {
+		Err(Capture::Exit(reason)) => reason,
+		_ => panic!("expected the runtime to stop"),
+	};
+	assert_eq!(stopped, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack_values(&runtime).len(), 4);
+}
+
+#[test]
+fn run_until_pauses_at_each_opcode_occurrence() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let code = hex::decode(JUMPDESTS).unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+
+	let mut breakpoints = BreakpointSet::new();
+	breakpoints.add_opcode(Opcode::JUMPDEST);
+
+	for position in [2, 5, 8] {
+		assert_eq!(runtime.run_until(&mut stack, &breakpoints).ok(), Some(position));
+		assert_eq!(stack_values(&runtime).len(), position / 3 + 1);
+	}
+	assert!(matches!(
+		runtime.run_until(&mut stack, &breakpoints),
+		Err(Capture::Exit(ExitReason::Succeed(ExitSucceed::Stopped))),
+	));
+}
+
+#[test]
+fn run_until_pauses_before_the_first_step() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	let code = hex::decode(JUMPDESTS).unwrap();
+	let mut runtime = Runtime::new(Rc::new(code), Rc::new(Vec::new()), context, &config);
+
+	let mut breakpoints = BreakpointSet::new();
+	breakpoints.add_position(0);
+	breakpoints.add_opcode(Opcode::PUSH1);
+
+	assert_eq!(runtime.run_until(&mut stack, &breakpoints).ok(), Some(0));
+	assert!(stack_values(&runtime).is_empty());
+	assert_eq!(runtime.run_until(&mut stack, &breakpoints).ok(), Some(3));
+	assert_eq!(stack_values(&runtime).len(), 1);
+
+	// Resuming after a single step checks the position it reached.
+	runtime.step(&mut stack).ok().unwrap();
+	assert_eq!(runtime.run_until(&mut stack, &breakpoints).ok(), Some(6));
+	assert_eq!(stack_values(&runtime).len(), 2);
+}
+
+#[test]
+fn from_hex_code_runs_decoded_code() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let mut stack = executor(&backend, 100_000, &config);
+
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+	// PUSH1 1 PUSH1 1 ADD
+	let mut runtime = Runtime::from_hex_code("0x6001600101", &[], context, &config).unwrap();
+
+	let reason = match runtime.run(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack_values(&runtime), vec![H256::from_low_u64_be(2)]);
+}
+
+#[test]
+fn from_hex_code_rejects_invalid_hex() {
+	let config = Config::istanbul();
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+
+	assert!(Runtime::from_hex_code("6001600101", &[], context.clone(), &config).is_ok());
+	assert_eq!(
+		Runtime::from_hex_code("0x600", &[], context.clone(), &config).err(),
+		Some(FromHexError::OddLength),
+	);
+	assert_eq!(
+		Runtime::from_hex_code("0x60zz", &[], context, &config).err(),
+		Some(FromHexError::InvalidHexCharacter { c: 'z', index: 2 }),
+	);
+}
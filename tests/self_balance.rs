@@ -0,0 +1,45 @@
+mod common;
+
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+#[test]
+fn self_balance_reflects_value_received_mid_execution() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SELFBALANCE, CALL address 2, POP, SELFBALANCE, MSTORE both and RETURN them
+	let parent = MemoryAccount {
+		balance: 10.into(),
+		..contract("476000600060006000600060025af1504760205260005260406000f3")
+	};
+	// SUICIDE to address 1
+	let child = MemoryAccount {
+		balance: 5.into(),
+		..contract("6001ff")
+	};
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), parent),
+		(address(2), child),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H256::from_slice(&out[..32]), H256::from_low_u64_be(10));
+	assert_eq!(H256::from_slice(&out[32..]), H256::from_low_u64_be(15));
+}
+
+#[test]
+fn self_balance_costs_low_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SELFBALANCE STOP
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("4700"))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack.used_gas(), 21000 + 5);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitError, ExitReason, ExitSucceed, Handler};
+use primitive_types::H256;
+use common::*;
+
+/// Run `code` on a contract whose slot 0 holds `original`, and return the
+/// gas used by execution and the storage refund.
+fn run(config: &Config, code: &str, original: u64) -> (u64, i64) {
+	let vicinity = vicinity();
+	let mut account = contract(code);
+	if original != 0 {
+		account.storage.insert(H256::zero(), H256::from_low_u64_be(original));
+	}
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	(100_000 - 21000 - stack.gas(), stack.gas_refund())
+}
+
+/// Cases of the EIP-2200 test table: code, original value, used gas and
+/// refund.
+const EIP2200: &[(&str, u64, u64, i64)] = &[
+	("60006000556000600055", 0, 1612, 0),
+	("60006000556001600055", 0, 20812, 0),
+	("60016000556000600055", 0, 20812, 19200),
+	("60016000556002600055", 0, 20812, 0),
+	("60016000556001600055", 0, 20812, 0),
+	("60006000556000600055", 1, 5812, 15000),
+	("60006000556001600055", 1, 5812, 4200),
+	("60006000556002600055", 1, 5812, 0),
+	("60026000556000600055", 1, 5812, 15000),
+	("60026000556003600055", 1, 5812, 0),
+	("60026000556001600055", 1, 5812, 4200),
+	("60026000556002600055", 1, 5812, 0),
+	("60016000556000600055", 1, 5812, 15000),
+	("60016000556002600055", 1, 5812, 0),
+	("60016000556001600055", 1, 1612, 0),
+	("600160005560006000556001600055", 0, 40818, 19200),
+	("600060005560016000556000600055", 1, 10818, 19200),
+];
+
+#[test]
+fn istanbul_sstore_metering_matches_eip2200() {
+	let config = Config::istanbul();
+	for &(code, original, used, refund) in EIP2200 {
+		assert_eq!(run(&config, code, original), (used, refund), "code {} original {}", code, original);
+	}
+}
+
+#[test]
+fn set_then_reset_to_original_refunds_set_cost() {
+	// Slot originally zero: SSTORE 1 then SSTORE 0.
+	let (_, refund) = run(&Config::istanbul(), "60016000556000600055", 0);
+	assert_eq!(refund, 20000 - 800);
+
+	let (used, refund) = run(&Config::berlin(), "60016000556000600055", 0);
+	assert_eq!(used, 2100 + 20000 + 100 + 4 * 3);
+	assert_eq!(refund, 20000 - 100);
+}
+
+#[test]
+fn reset_then_restore_original_refunds_reset_cost() {
+	// Slot originally one: SSTORE 2 then SSTORE 1.
+	let (_, refund) = run(&Config::istanbul(), "60026000556001600055", 1);
+	assert_eq!(refund, 5000 - 800);
+
+	let (used, refund) = run(&Config::berlin(), "60026000556001600055", 1);
+	assert_eq!(used, 2100 + 2900 + 100 + 4 * 3);
+	assert_eq!(refund, 2900 - 100);
+}
+
+#[test]
+fn london_clear_refunds_less() {
+	// Slot originally one: SSTORE 0.
+	let (_, refund) = run(&Config::berlin(), "6000600055", 1);
+	assert_eq!(refund, 15000);
+
+	let (_, refund) = run(&Config::london(), "6000600055", 1);
+	assert_eq!(refund, 4800);
+}
+
+/// Run a no-op SSTORE of zero to slot 0 with the given gas left at the
+/// SSTORE.
+fn run_with_gas_left(config: &Config, gas_left: u64) -> ExitReason {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("6000600055"))]));
+
+	let gas_limit = 21000 + 2 * 3 + gas_left;
+	let mut stack = executor(&backend, gas_limit, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), gas_limit);
+	reason
+}
+
+#[test]
+fn sstore_fails_with_stipend_or_less_left() {
+	let config = Config::istanbul();
+	assert!(config.sstore_revert_under_stipend);
+
+	assert_eq!(run_with_gas_left(&config, 2300), ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(run_with_gas_left(&config, 2301), ExitReason::Succeed(ExitSucceed::Stopped));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, Context, ExitError, ExitReason, Handler, Opcode, Stack};
+use common::*;
+
+/// Opcodes whose gas cost depends on memory operands read from the stack.
+const MEMORY_OPCODES: [Opcode; 17] = [
+	Opcode::MLOAD, Opcode::MSTORE, Opcode::MSTORE8, Opcode::SHA3,
+	Opcode::CALLDATACOPY, Opcode::CODECOPY, Opcode::EXTCODECOPY, Opcode::RETURNDATACOPY,
+	Opcode::RETURN, Opcode::REVERT, Opcode::LOG0,
+	Opcode::CALL, Opcode::CALLCODE, Opcode::DELEGATECALL, Opcode::STATICCALL,
+	Opcode::CREATE, Opcode::CREATE2,
+];
+
+#[test]
+fn memory_opcodes_check_stack_before_charging_gas() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: false,
+		depth: 0,
+	};
+
+	for opcode in &MEMORY_OPCODES {
+		let mut stack = executor(&backend, 100_000, &config);
+		assert_eq!(
+			stack.pre_validate(&context, *opcode, &Stack::new(1024)),
+			Err(ExitError::StackUnderflow),
+			"{:?}", opcode
+		);
+		assert_eq!(stack.gas(), 100_000, "{:?}", opcode);
+	}
+}
+
+#[test]
+fn memory_opcodes_exit_with_stack_underflow() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+
+	for opcode in &MEMORY_OPCODES {
+		let mut account = contract("");
+		account.code = vec![opcode.as_u8()];
+		let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+		let mut stack = executor(&backend, 100_000, &config);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Error(ExitError::StackUnderflow), "{:?}", opcode);
+	}
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "statetest")]
+
+use evm::statetest;
+
+fn run_file(json: &str) {
+	let outcomes = statetest::run_json(json).unwrap();
+	assert!(!outcomes.is_empty());
+
+	for outcome in outcomes {
+		assert!(outcome.passed(), "state test failed: {:?}", outcome);
+	}
+}
+
+#[test]
+fn add() {
+	run_file(include_str!("statetests/add.json"));
+}
+
+#[test]
+fn sstore() {
+	run_file(include_str!("statetests/sstore.json"));
+}
+
+#[test]
+fn log() {
+	run_file(include_str!("statetests/log.json"));
+}
+
+#[test]
+fn empty_logs_hash() {
+	assert_eq!(
+		statetest::logs_hash(&[]),
+		"1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347".parse().unwrap(),
+	);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::rc::Rc;
+use evm::backend::MemoryBackend;
+use evm::{Capture, Config, Context, ExitError, ExitReason, ExitSucceed, Handler, Runtime};
+use primitive_types::H256;
+use common::*;
+
+fn run_static(code: &str) -> ExitReason {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(code)),
+		(address(2), contract("00")),
+	]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let context = Context {
+		address: address(1),
+		caller: caller(),
+		tx_origin: caller(),
+		apparent_value: 0.into(),
+		is_static: true,
+		depth: 0,
+	};
+	let mut runtime = Runtime::new(
+		Rc::new(hex::decode(code).unwrap()), Rc::new(Vec::new()), context, &config,
+	);
+
+	let reason = match runtime.run(&mut stack) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	reason
+}
+
+#[test]
+fn sstore_in_static_context_fails() {
+	// PUSH1 0x01 PUSH1 0x00 SSTORE
+	assert_eq!(run_static("6001600055"), ExitReason::Error(ExitError::StaticModeViolation));
+}
+
+#[test]
+fn log_in_static_context_fails() {
+	// PUSH1 0xff PUSH1 0x00 PUSH1 0x00 LOG1
+	assert_eq!(run_static("60ff60006000a1"), ExitReason::Error(ExitError::StaticModeViolation));
+	// LOG4 of 100 bytes
+	assert_eq!(
+		run_static("600460036002600160646000a4"),
+		ExitReason::Error(ExitError::StaticModeViolation),
+	);
+}
+
+#[test]
+fn call_in_static_context_requires_zero_value() {
+	// CALL address 2 with a value of 0, then STOP
+	assert_eq!(
+		run_static("6000600060006000600060026103e8f100"),
+		ExitReason::Succeed(ExitSucceed::Stopped),
+	);
+	// CALL address 2 with a value of 1
+	assert_eq!(
+		run_static("6000600060006000600160026103e8f1"),
+		ExitReason::Error(ExitError::StaticModeViolation),
+	);
+}
+
+#[test]
+fn staticcall_propagates_to_nested_frames() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// STATICCALL addresses 2, 3 and 4, storing each success flag at slots
+	// 0, 1 and 2.
+	let caller_code = [2, 3, 4].iter().enumerate().map(|(slot, target)| {
+		format!("600060006000600060{:02x}6103e8fa60{:02x}55", target, slot)
+	}).collect::<String>();
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract(&caller_code)),
+		// PUSH1 0x01 PUSH1 0x00 SSTORE
+		(address(2), contract("6001600055")),
+		// PUSH1 0xff PUSH1 0x00 PUSH1 0x00 LOG1
+		(address(3), contract("60ff60006000a1")),
+		// CALL address 5 with a value of 0
+		(address(4), contract("6000600060006000600060056103e8f1")),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	assert_eq!(stack.storage(address(1), H256::from_low_u64_be(0)), H256::zero());
+	assert_eq!(stack.storage(address(1), H256::from_low_u64_be(1)), H256::zero());
+	assert_eq!(stack.storage(address(1), H256::from_low_u64_be(2)), H256::from_low_u64_be(1));
+}
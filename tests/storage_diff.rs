@@ -0,0 +1,52 @@
+mod common;
+
+use std::collections::BTreeMap;
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+fn slot(n: u64) -> H256 {
+	H256::from_low_u64_be(n)
+}
+
+#[test]
+fn modified_and_read_storage_are_reported_separately() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	// SSTORE 1 <- 1, SSTORE 2 <- 2, SSTORE 3 <- 3, SLOAD 5, SSTORE 2 <- 4
+	let mut account = contract("600160015560026002556003600355600554506004600255");
+	account.storage = vec![(slot(5), slot(7)), (slot(3), slot(9))].into_iter().collect();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let modified = vec![
+		((address(1), slot(1)), slot(1)),
+		((address(1), slot(2)), slot(4)),
+		((address(1), slot(3)), slot(3)),
+	].into_iter().collect::<BTreeMap<_, _>>();
+	assert_eq!(stack.state().modified_storage(), modified);
+
+	let read = vec![((address(1), slot(5)), slot(7))].into_iter().collect::<BTreeMap<_, _>>();
+	assert_eq!(stack.state().read_storage(), read);
+}
+
+#[test]
+fn reverted_writes_are_not_reported() {
+	let config = Config::berlin();
+	let vicinity = vicinity();
+	// SSTORE 1 <- 1, then REVERT
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("600160015560006000fd")),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert!(matches!(reason, ExitReason::Revert(_)));
+
+	assert!(stack.state().modified_storage().is_empty());
+	assert!(stack.state().read_storage().is_empty());
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use evm::backend::{ApplyBackend, Backend, MemoryAccount, MemoryBackend};
+use evm::executor::{MemoryStackState, StackExecutor};
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::H256;
+use common::*;
+
+#[test]
+fn reverted_sub_call_leaves_parent_state_unchanged() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// CALL address 2 with a value of 1, then STOP
+	let parent = MemoryAccount {
+		balance: 10.into(),
+		..contract("60006000600060006001600261fffff100")
+	};
+	// SSTORE 0 <- 1, LOG0, then REVERT
+	let child = contract("600160005560006000a060006000fd");
+	let mut backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), parent),
+		(address(2), child),
+	]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let (values, logs) = stack.into_state().deconstruct();
+	let logs = logs.into_iter().collect::<Vec<_>>();
+	assert!(logs.is_empty());
+	backend.apply(values, logs, false);
+
+	let state = backend.state();
+	assert_eq!(state[&address(1)].balance, 10.into());
+	assert_eq!(state[&address(2)].balance, 0.into());
+	assert_eq!(state[&address(2)].storage.get(&H256::zero()), None);
+}
+
+#[test]
+fn snapshot_forks_are_isolated() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SSTORE CALLDATALOAD 0 <- 1
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("600160003555")),
+	]));
+	let slot = |n: u64| H256::from_low_u64_be(n);
+	let input = |n: u64| slot(n).as_bytes().to_vec();
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), input(1), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let mut snapshot = stack.into_state();
+
+	let mut forks = Vec::new();
+	for n in 2..4 {
+		let mut fork = StackExecutor::new(snapshot.snapshot(), &config);
+		let (reason, _) = fork.transact_call(caller(), address(1), 0.into(), input(n), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		forks.push(fork.into_state());
+	}
+
+	let stored = |state: &MemoryStackState<_>, n| state.storage(address(1), slot(n)) == slot(1);
+	assert!(stored(&forks[0], 1) && stored(&forks[0], 2) && !stored(&forks[0], 3));
+	assert!(stored(&forks[1], 1) && !stored(&forks[1], 2) && stored(&forks[1], 3));
+	assert!(stored(&snapshot, 1) && !stored(&snapshot, 2) && !stored(&snapshot, 3));
+}
+
+#[test]
+fn revert_to_snapshot_drops_later_changes_and_logs() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SSTORE CALLDATALOAD 0 <- 1, then LOG0
+	let backend = MemoryBackend::new(&vicinity, state(vec![
+		(address(1), contract("60016000355560006000a0")),
+	]));
+	let slot = |n: u64| H256::from_low_u64_be(n);
+	let input = |n: u64| slot(n).as_bytes().to_vec();
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), input(1), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let snapshot = stack.snapshot();
+
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), input(2), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack.state().storage(address(1), slot(2)), slot(1));
+
+	stack.revert_to(snapshot);
+	assert_eq!(stack.state().storage(address(1), slot(1)), slot(1));
+	assert_eq!(stack.state().storage(address(1), slot(2)), H256::zero());
+	assert_eq!(stack.nonce(caller()), 1.into());
+
+	let (_, logs) = stack.into_state().deconstruct();
+	assert_eq!(logs.into_iter().count(), 1);
+}
+
+#[test]
+fn snapshot_layers_are_applied_on_deconstruct() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	// SSTORE CALLDATALOAD 0 <- 1, then LOG0
+	let accounts = state(vec![(address(1), contract("60016000355560006000a0"))]);
+	let backend = MemoryBackend::new(&vicinity, accounts.clone());
+	let slot = |n: u64| H256::from_low_u64_be(n);
+	let input = |n: u64| slot(n).as_bytes().to_vec();
+
+	// Slots set by a state applied on the backend, and its logs count.
+	let applied = |state: MemoryStackState<_>| {
+		let (values, logs) = state.deconstruct();
+		let logs = logs.into_iter().count();
+		let mut backend = MemoryBackend::new(&vicinity, accounts.clone());
+		backend.apply(values, Vec::new(), false);
+		let slots = (1..4).filter(|n| backend.storage(address(1), slot(*n)) == slot(1)).collect::<Vec<_>>();
+		(slots, logs)
+	};
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let mut snapshots = Vec::new();
+	for n in 1..4 {
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), input(n), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		snapshots.push(stack.snapshot());
+	}
+	// Changes after the last snapshot are on top of all the shared layers.
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), input(1), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	assert_eq!(applied(stack.into_state()), (vec![1, 2, 3], 4));
+	let mut snapshots = snapshots.into_iter();
+	assert_eq!(applied(snapshots.next().unwrap()), (vec![1], 1));
+	assert_eq!(applied(snapshots.next().unwrap()), (vec![1, 2], 2));
+	assert_eq!(applied(snapshots.next().unwrap()), (vec![1, 2, 3], 3));
+}
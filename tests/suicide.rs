@@ -0,0 +1,146 @@
+mod common;
+
+use std::collections::BTreeMap;
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::{Config, CreateScheme, ExitReason, ExitSucceed, Handler};
+use primitive_types::H160;
+use common::*;
+
+/// PUSH20 beneficiary SUICIDE
+fn suicide_code() -> String {
+	format!("73{}ff", hex::encode(beneficiary()))
+}
+
+fn beneficiary() -> H160 {
+	address(0xbe11e)
+}
+
+fn eip6780() -> Config {
+	let mut config = Config::istanbul();
+	config.suicide_only_if_created_same_tx = true;
+	config
+}
+
+fn call_suicide(config: &Config) -> BTreeMap<H160, MemoryAccount> {
+	let vicinity = vicinity();
+	let account = MemoryAccount {
+		balance: 1000.into(),
+		..contract(&suicide_code())
+	};
+	let mut backend = MemoryBackend::new(&vicinity, state(vec![(address(1), account)]));
+
+	let mut stack = executor(&backend, 100_000, config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+
+	let (values, logs) = stack.into_state().deconstruct();
+	backend.apply(values, logs, true);
+	backend.state().clone()
+}
+
+#[test]
+fn suicide_of_existing_contract_only_transfers() {
+	let state = call_suicide(&eip6780());
+
+	let account = &state[&address(1)];
+	assert_eq!(account.balance, 0.into());
+	assert_eq!(account.code, hex::decode(suicide_code()).unwrap());
+	assert_eq!(state[&beneficiary()].balance, 1000.into());
+}
+
+#[test]
+fn suicide_of_existing_contract_deletes_without_eip6780() {
+	let state = call_suicide(&Config::istanbul());
+
+	assert!(!state.contains_key(&address(1)));
+	assert_eq!(state[&beneficiary()].balance, 1000.into());
+}
+
+#[test]
+fn suicide_of_contract_created_in_transaction_deletes() {
+	let config = eip6780();
+	let vicinity = vicinity();
+	let mut backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let created = stack.create_address(CreateScheme::Legacy { caller: caller() });
+	let reason = stack.transact_create(
+		caller(), 1000.into(), hex::decode(suicide_code()).unwrap(), 100_000,
+	);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+
+	let (values, logs) = stack.into_state().deconstruct();
+	backend.apply(values, logs, true);
+
+	assert!(!backend.state().contains_key(&created));
+	assert_eq!(backend.state()[&beneficiary()].balance, 1000.into());
+}
+
+#[test]
+fn contract_created_in_previous_transaction_is_not_deleted() {
+	let config = eip6780();
+	let vicinity = vicinity();
+	let mut backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	// PUSH22 <suicide code> PUSH1 0 MSTORE PUSH1 22 PUSH1 10 RETURN
+	let init_code = format!("75{}6000526016600af3", suicide_code());
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let created = stack.create_address(CreateScheme::Legacy { caller: caller() });
+	let reason = stack.transact_create(caller(), 1000.into(), hex::decode(init_code).unwrap(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert!(!stack.created_in_current_transaction(created));
+
+	let (reason, _) = stack.transact_call(caller(), created, 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+	assert!(stack.pending_selfdestructs().is_empty());
+
+	let (values, logs) = stack.into_state().deconstruct();
+	backend.apply(values, logs, true);
+
+	let account = &backend.state()[&created];
+	assert_eq!(account.balance, 0.into());
+	assert_eq!(account.code, hex::decode(suicide_code()).unwrap());
+	assert_eq!(backend.state()[&beneficiary()].balance, 1000.into());
+}
+
+#[test]
+fn pending_selfdestructs_until_finalization() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let mut backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(&suicide_code()))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	assert!(stack.pending_selfdestructs().is_empty());
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+	assert_eq!(stack.pending_selfdestructs(), vec![address(1)]);
+
+	let (values, logs) = stack.into_state().deconstruct();
+	backend.apply(values, logs, true);
+	assert!(!backend.state().contains_key(&address(1)));
+}
+
+#[test]
+fn eip6780_transfer_is_not_pending_selfdestruct() {
+	let config = eip6780();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(&suicide_code()))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+	assert!(stack.pending_selfdestructs().is_empty());
+}
+
+#[test]
+fn suicide_refund_is_removed_in_london() {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(&suicide_code()))]));
+
+	for (config, refund) in [(Config::istanbul(), 24000), (Config::london(), 0)] {
+		let mut stack = executor(&backend, 100_000, &config);
+		let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+		assert_eq!(stack.gas_refund(), refund);
+	}
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use common::*;
+
+/// Create transaction recursively creating with its own init code, each
+/// frame expanding its memory to 4096 bytes, and return the number of
+/// frames run.
+fn recursive_creates(config: &Config) -> usize {
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	// MSTORE8 at 0x0fff, LOG0, CODECOPY the init code itself, then CREATE
+	// with it
+	let init_code = hex::decode("6000610fff5360006000a03860006000393860006000f05000").unwrap();
+
+	let mut stack = executor(&backend, 10_000_000, config);
+	let reason = stack.transact_create(caller(), 0.into(), init_code, 10_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let (_, logs) = stack.into_state().deconstruct();
+	logs.into_iter().count()
+}
+
+#[test]
+fn total_memory_limit_stops_recursion() {
+	let config = Config { max_total_memory: Some(5 * 4096), ..Config::istanbul() };
+	assert_eq!(recursive_creates(&config), 5);
+
+	let config = Config { max_total_memory: Some(5 * 4096 - 1), ..Config::istanbul() };
+	assert_eq!(recursive_creates(&config), 4);
+
+	assert!(recursive_creates(&Config::istanbul()) > 5);
+}
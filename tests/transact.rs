@@ -0,0 +1,123 @@
+mod common;
+
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::{Config, ExitError, ExitReason, ExitSucceed, Handler};
+use common::*;
+
+#[test]
+fn upfront_cost_covers_value_and_max_fee() {
+	let mut config = Config::istanbul();
+	config.validate_upfront_cost = true;
+	let vicinity = vicinity();
+	let mut state = state(vec![(address(1), contract("00"))]);
+	state.insert(caller(), funded(100_000));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	// The balance covers the value, but not the value plus the maximum fee.
+	let mut stack = executor(&backend, 60_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 50_000.into(), Vec::new(), 60_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfUpfrontFund));
+
+	let mut stack = executor(&backend, 50_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 50_000.into(), Vec::new(), 50_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	// Without the check, only the value itself needs to be covered.
+	config.validate_upfront_cost = false;
+	let mut stack = executor(&backend, 60_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 50_000.into(), Vec::new(), 60_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn eof_code_is_rejected() {
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("ef000100"))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::InvalidEofCode));
+}
+
+#[test]
+fn contract_and_eoa_are_distinguished() {
+	let mut config = Config::istanbul();
+	let vicinity = vicinity();
+	let mut state = state(vec![(address(1), contract("00")), (address(2), funded(1000))]);
+	state.insert(address(3), MemoryAccount {
+		balance: 1_000_000.into(),
+		..contract("00")
+	});
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let stack = executor(&backend, 100_000, &config);
+	assert!(stack.is_contract(address(1)));
+	assert!(!stack.is_contract(address(2)));
+	assert!(!stack.is_contract(caller()));
+
+	// Calling an EOA runs no code.
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(2), 1.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack.used_gas(), 21000);
+
+	// Senders with deployed code are rejected under EIP-3607.
+	config.reject_sender_with_code = true;
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(address(3), address(2), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::SenderHasCode));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(2), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+fn eip3860() -> Config {
+	let mut config = Config::istanbul();
+	config.max_initcode_size = Some(1024);
+	config
+}
+
+#[test]
+fn oversized_init_code_transaction_is_rejected() {
+	let config = eip3860();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let reason = stack.transact_create(caller(), 0.into(), vec![0; 1025], 100_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::InitCodeLimit));
+	assert_eq!(stack.used_gas(), 0);
+	assert_eq!(stack.nonce(caller()), 0.into());
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let reason = stack.transact_create(caller(), 0.into(), vec![0; 1024], 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn oversized_init_code_create_opcode_fails() {
+	let config = eip3860();
+	let vicinity = vicinity();
+	// PUSH2 0x0401 PUSH1 0 PUSH1 0 CREATE STOP
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract("61040160006000f000"))]));
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let (reason, _) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Error(ExitError::InitCodeLimit));
+}
+
+#[test]
+fn create_transaction_pays_init_code_words() {
+	let config = eip3860();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(Vec::new()));
+	let init_code = vec![0; 1024];
+
+	let mut stack = executor(&backend, 100_000, &config);
+	let reason = stack.transact_create(caller(), 0.into(), init_code.clone(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(stack.used_gas(), 53000 + 1024 * 4 + 32 * 2);
+	assert_eq!(stack.used_gas(), config.intrinsic_gas(true, &init_code, &[]));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use evm::backend::MemoryBackend;
+use evm::{Config, ExitReason, ExitSucceed};
+use primitive_types::U256;
+use common::*;
+
+fn eip1153() -> Config {
+	let mut config = Config::berlin();
+	config.has_transient_storage = true;
+	config
+}
+
+/// With calldata, TSTORE 42 at slot 0. Then return TLOAD of slot 0.
+const STORE_AND_LOAD: &str = "3615600a57602a60005d5b60005c60005260206000f3";
+
+/// Without calldata, TSTORE 1 at slot 0, call itself with calldata, and
+/// return TLOAD of slot 0. With calldata, TSTORE 7 at slot 0 and end with
+/// the given opcodes.
+fn self_call(child_end: &str) -> String {
+	format!(
+		"36602257600160005d60006000600160006000305af15060005c60005260206000f35b600760005d{}",
+		child_end,
+	)
+}
+
+fn call(code: &str) -> U256 {
+	let config = eip1153();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(code))]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	U256::from_big_endian(&out)
+}
+
+#[test]
+fn transient_storage_is_cleared_between_transactions() {
+	let config = eip1153();
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(vec![(address(1), contract(STORE_AND_LOAD))]));
+
+	let mut stack = executor(&backend, 1_000_000, &config);
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), vec![1], 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&out), 42.into());
+
+	let (reason, out) = stack.transact_call(caller(), address(1), 0.into(), Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&out), 0.into());
+}
+
+#[test]
+fn committed_sub_call_keeps_transient_storage() {
+	// STOP
+	assert_eq!(call(&self_call("00")), 7.into());
+}
+
+#[test]
+fn reverted_sub_call_rolls_back_transient_storage() {
+	// PUSH1 0 PUSH1 0 REVERT
+	assert_eq!(call(&self_call("60006000fd")), 1.into());
+}